@@ -0,0 +1,36 @@
+//! In-memory cache of account details.
+
+use crate::models::Account;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long account details are cached.
+const ACCOUNT_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Account details cached by client, shared between its clones.
+#[derive(Default)]
+pub(crate) struct AccountCache {
+    entry: Mutex<Option<(Instant, Arc<Account>)>>,
+}
+
+impl AccountCache {
+    pub(crate) fn get(&self) -> Option<Arc<Account>> {
+        let entry = self.entry.lock().ok()?;
+        match entry.as_ref() {
+            Some((stored, account)) if stored.elapsed() < ACCOUNT_TTL => Some(account.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, account: Arc<Account>) {
+        if let Ok(mut entry) = self.entry.lock() {
+            *entry = Some((Instant::now(), account));
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut entry) = self.entry.lock() {
+            *entry = None;
+        }
+    }
+}
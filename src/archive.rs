@@ -0,0 +1,166 @@
+//! Bulk download of invoice PDFs into directory or ZIP archive.
+
+use crate::client::Fakturoid;
+use crate::error::{FakturoidError, PdfNotReady};
+use crate::filters::Filter;
+use crate::models::Invoice;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::time::delay_for;
+
+/// How many times download of PDF which is not generated yet is attempted.
+const MAX_ATTEMPTS: u32 = 5;
+/// Delay before next attempt, it is multiplied by number of attempt.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Where downloaded PDFs are written.
+pub enum PdfArchive {
+    /// Directory, it is created if it doesn't exist.
+    Directory(PathBuf),
+    /// ZIP archive, existing file is overwritten.
+    #[cfg(feature = "zip")]
+    Zip(PathBuf),
+}
+
+enum ArchiveWriter {
+    Directory(PathBuf),
+    #[cfg(feature = "zip")]
+    Zip(zip::ZipWriter<fs::File>),
+}
+
+impl ArchiveWriter {
+    fn open(archive: &PdfArchive) -> Result<Self, FakturoidError> {
+        match archive {
+            PdfArchive::Directory(dir) => {
+                fs::create_dir_all(dir).map_err(FakturoidError::from_std_err)?;
+                Ok(ArchiveWriter::Directory(dir.clone()))
+            }
+            #[cfg(feature = "zip")]
+            PdfArchive::Zip(path) => {
+                let file = fs::File::create(path).map_err(FakturoidError::from_std_err)?;
+                Ok(ArchiveWriter::Zip(zip::ZipWriter::new(file)))
+            }
+        }
+    }
+
+    fn write(&mut self, name: &str, content: &[u8]) -> Result<(), FakturoidError> {
+        match self {
+            ArchiveWriter::Directory(dir) => {
+                fs::write(dir.join(name), content).map_err(FakturoidError::from_std_err)
+            }
+            #[cfg(feature = "zip")]
+            ArchiveWriter::Zip(writer) => {
+                use std::io::Write;
+                writer
+                    .start_file(name, zip::write::FileOptions::default())
+                    .map_err(FakturoidError::from_std_err)?;
+                writer
+                    .write_all(content)
+                    .map_err(FakturoidError::from_std_err)
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), FakturoidError> {
+        match self {
+            ArchiveWriter::Directory(_) => Ok(()),
+            #[cfg(feature = "zip")]
+            ArchiveWriter::Zip(mut writer) => writer
+                .finish()
+                .map(|_| ())
+                .map_err(FakturoidError::from_std_err),
+        }
+    }
+}
+
+/// Names of PDFs of invoices, number of invoice with characters unsafe in file names replaced
+/// by dashes (id if invoice has no number). Id is appended to name which is already taken, e.g.
+/// by `2024/1` and `2024-1` or names differing in case only.
+fn file_names(invoices: &[Invoice]) -> Vec<(i32, String)> {
+    let mut taken = HashSet::new();
+    invoices
+        .iter()
+        .filter_map(|invoice| {
+            let id = invoice.id?;
+            let mut stem = match invoice.number.as_ref() {
+                Some(number) => {
+                    number.replace(|c: char| !c.is_alphanumeric() && c != '-' && c != '_', "-")
+                }
+                None => id.to_string(),
+            };
+            while !taken.insert(stem.to_lowercase()) {
+                stem = format!("{}-{}", stem, id);
+            }
+            Some((id, format!("{}.pdf", stem)))
+        })
+        .collect()
+}
+
+impl Fakturoid {
+    /// Downloads PDFs of all invoices matching given filter and writes them into directory or
+    /// ZIP archive under their numbers. At most `concurrency` PDFs are downloaded at once. PDFs
+    /// which are not generated yet are requested again after a while. Number of written PDFs is
+    /// returned in case of success.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::archive::PdfArchive;
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::filters::InvoiceFilters;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let filter = InvoiceFilters::new().subject_id(16).into();
+    ///     let count = client
+    ///         .download_invoice_pdfs(Some(filter), PdfArchive::Directory("invoices".into()), 4)
+    ///         .await?;
+    ///     println!("{} invoices downloaded", count);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn download_invoice_pdfs(
+        &self,
+        filter: Option<Filter>,
+        archive: PdfArchive,
+        concurrency: usize,
+    ) -> Result<usize, FakturoidError> {
+        let invoices: Vec<Invoice> = self
+            .list::<Invoice>(filter)
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?;
+
+        let mut writer = ArchiveWriter::open(&archive)?;
+        let mut downloads = stream::iter(file_names(&invoices))
+            .map(|(id, name)| async move { self.pdf_when_ready(id).await.map(|pdf| (name, pdf)) })
+            .buffer_unordered(concurrency.max(1));
+
+        let mut count = 0;
+        while let Some(download) = downloads.next().await {
+            let (name, pdf) = download?;
+            writer.write(&name, &pdf)?;
+            count += 1;
+        }
+        writer.finish()?;
+        Ok(count)
+    }
+
+    async fn pdf_when_ready(&self, id: i32) -> Result<Vec<u8>, FakturoidError> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            if let Some(pdf) = self.invoice_pdf(id).await? {
+                return Ok(pdf);
+            }
+            if attempt < MAX_ATTEMPTS {
+                delay_for(RETRY_DELAY * attempt).await;
+            }
+        }
+        Err(FakturoidError::from_std_err(PdfNotReady::new(id)))
+    }
+}
@@ -0,0 +1,247 @@
+//! Lookup of Czech companies in public registry
+//! [ARES](https://ares.gov.cz/stranky/vyvojar-info) (Administrativní registr ekonomických
+//! subjektů). Available with `ares` feature.
+//!
+//! Company is found by its registration number (IČO) and its name, address and VAT number
+//! are filled into [`Subject`](../models/struct.Subject.html), so new customer can be created
+//! from registration number only, like in web application of Fakturoid. Registration number is
+//! checked before request is sent, unknown companies are reported as
+//! `Kind::EntityDoesNotExists`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::ares::Ares;
+//! use fakturoid::client::Fakturoid;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let mut subject = Ares::new().subject("27074358").await?;
+//!     subject.email = Some("info@company.com".to_string());
+//!     let subject = client.create(subject).await?;
+//!     println!("{:?}", subject.name);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::{FakturoidError, InvalidRegistrationNo};
+use crate::models::Subject;
+use crate::transport::Transport;
+use reqwest::{Client, Method};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// URL of REST API of ARES with economic subjects.
+const ARES_URL: &str = "https://ares.gov.cz/ekonomicke-subjekty-v-be/rest/ekonomicke-subjekty";
+
+/// Client of ARES registry.
+#[derive(Clone)]
+pub struct Ares {
+    client: Client,
+    transport: Option<Arc<dyn Transport>>,
+    url: String,
+}
+
+impl Default for Ares {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ares {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            transport: None,
+            url: ARES_URL.to_string(),
+        }
+    }
+
+    /// Sends requests by given HTTP client, e.g. client with proxy or timeout.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sends requests through given transport, see [`transport`](../transport/index.html)
+    /// module.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Company with given registration number (IČO).
+    pub async fn lookup(&self, registration_no: &str) -> Result<AresRecord, FakturoidError> {
+        let registration_no =
+            normalize_registration_no(registration_no).map_err(FakturoidError::from_std_err)?;
+        let request = self
+            .client
+            .request(Method::GET, &format!("{}/{}", self.url, registration_no))
+            .header("Accept", "application/json")
+            .build()?;
+        let response = match self.transport.as_ref() {
+            Some(transport) => transport.execute(request).await?,
+            None => self.client.execute(request).await?,
+        };
+        let subject = response.error_for_status()?.json::<AresSubject>().await?;
+        Ok(subject.into_record(registration_no))
+    }
+
+    /// New subject filled from ARES record of company with given registration number.
+    pub async fn subject(&self, registration_no: &str) -> Result<Subject, FakturoidError> {
+        let mut subject = Subject::default();
+        self.lookup(registration_no).await?.apply_to(&mut subject);
+        Ok(subject)
+    }
+
+    /// Fills name, address and VAT number of subject from ARES by its `registration_no`.
+    pub async fn fill_subject(&self, subject: &mut Subject) -> Result<(), FakturoidError> {
+        let registration_no = subject.registration_no.clone().unwrap_or_default();
+        self.lookup(&registration_no).await?.apply_to(subject);
+        Ok(())
+    }
+}
+
+/// Company found in ARES.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AresRecord {
+    /// Registration number (IČO), eight digits.
+    pub registration_no: String,
+    pub name: String,
+    /// VAT number (DIČ), `None` if company isn't registered for VAT.
+    pub vat_no: Option<String>,
+    /// Street with house number, part of municipality if municipality has no streets.
+    pub street: Option<String>,
+    pub city: Option<String>,
+    pub zip: Option<String>,
+    /// ISO code of country, e.g. `CZ`.
+    pub country: Option<String>,
+}
+
+impl AresRecord {
+    /// Overwrites registration number, name, address and VAT number of subject. Fields which
+    /// are not known in ARES are left unchanged.
+    pub fn apply_to(&self, subject: &mut Subject) {
+        subject.registration_no = Some(self.registration_no.clone());
+        subject.name = Some(self.name.clone());
+        if self.vat_no.is_some() {
+            subject.vat_no = self.vat_no.clone();
+        }
+        if self.street.is_some() {
+            subject.street = self.street.clone();
+            subject.street2 = None;
+        }
+        if self.city.is_some() {
+            subject.city = self.city.clone();
+        }
+        if self.zip.is_some() {
+            subject.zip = self.zip.clone();
+        }
+        if self.country.is_some() {
+            subject.country = self.country.clone();
+        }
+    }
+}
+
+/// Economic subject in response of ARES.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AresSubject {
+    obchodni_jmeno: String,
+    #[serde(default)]
+    dic: Option<String>,
+    #[serde(default)]
+    sidlo: Option<AresAddress>,
+}
+
+/// Registered office in response of ARES.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AresAddress {
+    #[serde(default)]
+    kod_statu: Option<String>,
+    #[serde(default)]
+    nazev_obce: Option<String>,
+    #[serde(default)]
+    nazev_casti_obce: Option<String>,
+    #[serde(default)]
+    nazev_ulice: Option<String>,
+    #[serde(default)]
+    cislo_domovni: Option<u32>,
+    #[serde(default)]
+    cislo_orientacni: Option<u32>,
+    #[serde(default)]
+    cislo_orientacni_pismeno: Option<String>,
+    #[serde(default)]
+    psc: Option<u32>,
+}
+
+impl AresSubject {
+    fn into_record(self, registration_no: String) -> AresRecord {
+        let address = self.sidlo;
+        AresRecord {
+            registration_no,
+            name: self.obchodni_jmeno,
+            vat_no: self.dic.filter(|dic| !dic.is_empty()),
+            street: address.as_ref().and_then(AresAddress::street),
+            city: address.as_ref().and_then(|a| a.nazev_obce.clone()),
+            zip: address
+                .as_ref()
+                .and_then(|a| a.psc)
+                .map(|psc| format!("{:05}", psc)),
+            country: address.and_then(|a| a.kod_statu),
+        }
+    }
+}
+
+impl AresAddress {
+    /// Street and house number, e.g. `Budějovická 778/3a`.
+    fn street(&self) -> Option<String> {
+        let name = self
+            .nazev_ulice
+            .as_ref()
+            .or(self.nazev_casti_obce.as_ref())?;
+        let mut number = self
+            .cislo_domovni
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+        if let Some(orientation) = self.cislo_orientacni {
+            if !number.is_empty() {
+                number.push('/');
+            }
+            number.push_str(&orientation.to_string());
+            number.push_str(self.cislo_orientacni_pismeno.as_deref().unwrap_or_default());
+        }
+        if number.is_empty() {
+            Some(name.clone())
+        } else {
+            Some(format!("{} {}", name, number))
+        }
+    }
+}
+
+/// Registration number padded by zeros to eight digits, its check digit is verified.
+fn normalize_registration_no(registration_no: &str) -> Result<String, InvalidRegistrationNo> {
+    let trimmed: String = registration_no
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if trimmed.is_empty() || trimmed.len() > 8 || !trimmed.chars().all(|c| c.is_ascii_digit()) {
+        return Err(InvalidRegistrationNo::new(registration_no));
+    }
+    let padded = format!("{:0>8}", trimmed);
+    let digits: Vec<u32> = padded.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits[..7]
+        .iter()
+        .zip((2..=8).rev())
+        .map(|(digit, weight)| digit * weight)
+        .sum();
+    let check = (11 - sum % 11) % 10;
+    if digits[7] == check {
+        Ok(padded)
+    } else {
+        Err(InvalidRegistrationNo::new(registration_no))
+    }
+}
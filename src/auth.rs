@@ -0,0 +1,150 @@
+//! Authentication of client. HTTP Basic auth with email and API key is used by default, OAuth 2.0
+//! client credentials flow can be chosen by `Fakturoid::oauth_builder()` or
+//! `FakturoidBuilder::auth()`.
+//!
+//! With client credentials access token is requested before first request, it is refreshed
+//! shortly before it expires and also when API rejects it with `401 Unauthorized`, so request is
+//! sent again with new token.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Fakturoid::oauth_builder("client_id", "client_secret", "slug")
+//!     .user_agent("My app (developer@company.com)")
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::FakturoidError;
+use crate::transport::Transport;
+use futures::lock::Mutex;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Endpoint issuing access tokens, relative to base URL of client.
+const TOKEN_PATH: &str = "api/v3/oauth/token";
+
+/// Token is refreshed this long before it expires, so it doesn't expire during request.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Credentials used by client.
+#[derive(Clone, PartialEq)]
+pub enum Auth {
+    /// HTTP Basic auth with email of user and API key.
+    Basic { user: String, password: String },
+    /// OAuth 2.0 client credentials flow, `Authorization: Bearer` header is sent.
+    ClientCredentials {
+        client_id: String,
+        client_secret: String,
+    },
+}
+
+impl Auth {
+    pub fn basic(user: &str, password: &str) -> Self {
+        Auth::Basic {
+            user: user.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    pub fn client_credentials(client_id: &str, client_secret: &str) -> Self {
+        Auth::ClientCredentials {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        }
+    }
+
+    /// True if access token is sent instead of basic auth.
+    pub fn is_bearer(&self) -> bool {
+        matches!(self, Auth::ClientCredentials { .. })
+    }
+}
+
+/// Secrets are never printed.
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::Basic { user, .. } => f
+                .debug_struct("Basic")
+                .field("user", user)
+                .field("password", &"[redacted]")
+                .finish(),
+            Auth::ClientCredentials { client_id, .. } => f
+                .debug_struct("ClientCredentials")
+                .field("client_id", client_id)
+                .field("client_secret", &"[redacted]")
+                .finish(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+/// Access token shared by client and all its clones.
+#[derive(Default)]
+pub(crate) struct TokenStore {
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl TokenStore {
+    /// Valid access token, new one is requested if there is none or it is about to expire.
+    /// Concurrent requests wait for one token request. Token is requested through transport
+    /// of client if it has one.
+    pub(crate) async fn token(
+        &self,
+        client: &Client,
+        transport: Option<&dyn Transport>,
+        base_url: &str,
+        user_agent: &str,
+        client_id: &str,
+        client_secret: &str,
+    ) -> Result<String, FakturoidError> {
+        let mut token = self.token.lock().await;
+        if let Some((value, expires)) = token.as_ref() {
+            if Instant::now() + EXPIRY_MARGIN < *expires {
+                return Ok(value.clone());
+            }
+        }
+        let request = client
+            .post(&format!("{}{}", base_url, TOKEN_PATH))
+            .basic_auth(client_id, Some(client_secret))
+            .header("User-Agent", user_agent)
+            .json(&serde_json::json!({ "grant_type": "client_credentials" }))
+            .build()?;
+        let response = match transport {
+            Some(transport) => transport.execute(request).await?,
+            None => client.execute(request).await?,
+        };
+        let response = response.error_for_status()?.json::<TokenResponse>().await?;
+        let expires = Instant::now() + Duration::from_secs(response.expires_in.unwrap_or(7200));
+        *token = Some((response.access_token.clone(), expires));
+        Ok(response.access_token)
+    }
+
+    /// Forgets token rejected by API, unless it was already replaced by another request.
+    pub(crate) async fn invalidate(&self, rejected: &str) {
+        let mut token = self.token.lock().await;
+        if token.as_ref().map(|t| t.0 == rejected).unwrap_or(false) {
+            *token = None;
+        }
+    }
+}
+
+/// Adds basic auth to request, bearer token is added when request is sent.
+pub(crate) fn apply_basic(auth: &Auth, request: RequestBuilder) -> RequestBuilder {
+    match auth {
+        Auth::Basic { user, password } => request.basic_auth(user, Some(password)),
+        Auth::ClientCredentials { .. } => request,
+    }
+}
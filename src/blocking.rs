@@ -0,0 +1,642 @@
+//! Blocking client, available with `blocking` feature.
+//!
+//! [`Fakturoid`](struct.Fakturoid.html) has the same methods as asynchronous
+//! [`client::Fakturoid`](../client/struct.Fakturoid.html), but they block current thread until
+//! response is received. Like `reqwest::blocking`, it runs asynchronous client on its own
+//! runtime, so it must not be used within asynchronous code. Methods not mirrored here (e.g.
+//! `overdue_invoices()`) can be called through
+//! [`block_on()`](struct.Fakturoid.html#method.block_on).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::blocking::Fakturoid;
+//! use fakturoid::models::Subject;
+//!
+//! fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None)?;
+//!     let subjects = client.list::<Subject>(None)?;
+//!     for subject in subjects.iter() {
+//!         println!("{}", subject);
+//!     }
+//!     let overdue = client.block_on(client.inner().overdue_invoices())?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::{
+    self, Action, ApiVersion, Cached, Entity, EntityAction, FakturoidBuilder,
+    PagedResponse as AsyncPage, PingStatus, ResponseMeta,
+};
+use crate::error::FakturoidError;
+use crate::filters::{EntityFilter, Filter};
+use crate::models::{
+    Account, AccountSettings, BankAccount, Event, ExpensePayment, Generator, GeneratorKind,
+    InventoryMove, Invoice, InvoicePayData, InvoicePayment, NumberFormat, RemoteAttachment, Todo,
+    User,
+};
+use bytes::Bytes;
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+/// Runtime shared by client, its clones and pages returned by it. Its worker thread drives IO
+/// and timers, futures are run on calling threads through runtime handle, so clients used from
+/// several threads don't wait for each other.
+type SharedRuntime = Arc<Runtime>;
+
+fn block_on<F: Future>(runtime: &SharedRuntime, future: F) -> F::Output {
+    runtime.handle().block_on(future)
+}
+
+/// Blocking Fakturoid client, see [module](index.html) documentation.
+#[derive(Clone)]
+pub struct Fakturoid {
+    client: client::Fakturoid,
+    runtime: SharedRuntime,
+}
+
+impl Fakturoid {
+    /// Creates new instance of client, see `client::Fakturoid::new()`. Fails if runtime can't
+    /// be created.
+    pub fn new(
+        user: &str,
+        password: &str,
+        slug: &str,
+        user_agent: Option<&str>,
+    ) -> Result<Self, FakturoidError> {
+        Self::from_async(client::Fakturoid::new(user, password, slug, user_agent))
+    }
+
+    /// Creates builder for client with non default settings, client is created by
+    /// `FakturoidBuilder::build_blocking()`.
+    pub fn builder(user: &str, password: &str, slug: &str) -> FakturoidBuilder {
+        client::Fakturoid::builder(user, password, slug)
+    }
+
+    /// Creates builder for client authenticated by OAuth 2.0 client credentials flow.
+    pub fn oauth_builder(client_id: &str, client_secret: &str, slug: &str) -> FakturoidBuilder {
+        client::Fakturoid::oauth_builder(client_id, client_secret, slug)
+    }
+
+    /// Blocking client which sends requests through given asynchronous client.
+    pub fn from_async(client: client::Fakturoid) -> Result<Self, FakturoidError> {
+        let runtime = Builder::new()
+            .threaded_scheduler()
+            .core_threads(1)
+            .enable_all()
+            .build()
+            .map_err(FakturoidError::from_std_err)?;
+        Ok(Self {
+            client,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Asynchronous client used by this client.
+    pub fn inner(&self) -> &client::Fakturoid {
+        &self.client
+    }
+
+    /// Runs future, typically method of [`inner()`](#method.inner) client, to completion. It
+    /// panics if it's called within asynchronous code, including future passed to it.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(&self.runtime, future)
+    }
+
+    /// Returns client working with account with given slug, see
+    /// `client::Fakturoid::with_slug()`. Runtime is shared with this client.
+    pub fn with_slug(&self, slug: &str) -> Fakturoid {
+        Self {
+            client: self.client.with_slug(slug),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Slug of account used by client.
+    pub fn slug(&self) -> &str {
+        self.client.slug()
+    }
+
+    /// Version of API used by client.
+    pub fn api_version(&self) -> ApiVersion {
+        self.client.api_version()
+    }
+
+    /// Detail of account.
+    pub fn account(&self) -> Result<Account, FakturoidError> {
+        self.block_on(self.client.account())
+    }
+
+    /// Changes account settings, see `client::Fakturoid::update_account()`.
+    pub fn update_account(&self, settings: &AccountSettings) -> Result<Account, FakturoidError> {
+        self.block_on(self.client.update_account(settings))
+    }
+
+    /// Checks that credentials and slug are valid, see `client::Fakturoid::ping()`.
+    pub fn ping(&self) -> PingStatus {
+        self.block_on(self.client.ping())
+    }
+
+    /// Detail of entity with given id.
+    pub fn detail<T>(&self, id: i32) -> Result<T, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.block_on(self.client.detail(id))
+    }
+
+    /// Detail of entity with given id together with metadata of response.
+    pub fn detail_with_meta<T>(&self, id: i32) -> Result<(T, ResponseMeta), FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.block_on(self.client.detail_with_meta(id))
+    }
+
+    /// Detail of entity with given id together with information whether it was served from
+    /// response cache.
+    pub fn detail_cached<T>(&self, id: i32) -> Result<Cached<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.block_on(self.client.detail_cached(id))
+    }
+
+    /// Creates new entity, created entity is returned.
+    pub fn create<T>(&self, entity: T) -> Result<T, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        self.block_on(self.client.create(entity))
+    }
+
+    /// Creates all given entities concurrently, see `client::Fakturoid::create_many()`.
+    pub fn create_many<T>(
+        &self,
+        entities: Vec<T>,
+        concurrency: usize,
+    ) -> Vec<Result<T, FakturoidError>>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        self.block_on(self.client.create_many(entities, concurrency))
+    }
+
+    /// Updates entity with given id, updated entity is returned.
+    pub fn update<T>(&self, id: i32, entity: T) -> Result<T, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        self.block_on(self.client.update(id, entity))
+    }
+
+    /// Changes entity with given id by closure and sends only changed fields, see
+    /// `Fakturoid::update_fields()`.
+    pub fn update_fields<T, F>(&self, id: i32, change: F) -> Result<T, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        self.block_on(self.client.update_fields(id, change))
+    }
+
+    /// Deletes entity with given id.
+    pub fn delete<T>(&self, id: i32) -> Result<(), FakturoidError>
+    where
+        T: Entity,
+    {
+        self.block_on(self.client.delete::<T>(id))
+    }
+
+    /// First page of list of entities.
+    pub fn list<T>(&self, filter: Option<Filter>) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.page(self.client.list(filter))
+    }
+
+    /// Lists entities matching typed filter, see `client::Fakturoid::list_filtered()`.
+    pub fn list_filtered<F>(&self, filter: F) -> Result<PagedResponse<F::Entity>, FakturoidError>
+    where
+        F: EntityFilter,
+        F::Entity: DeserializeOwned,
+    {
+        self.page(self.client.list_filtered(filter))
+    }
+
+    /// Lists entities at custom URL, see `client::Fakturoid::list_url()`.
+    pub fn list_url<T>(
+        &self,
+        path: &str,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.page(self.client.list_url(path, filter))
+    }
+
+    /// Items of all pages of list, see `client::Fakturoid::list_all()`.
+    pub fn list_all<T>(
+        &self,
+        filter: Option<Filter>,
+        max_pages: Option<usize>,
+    ) -> Result<Vec<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.block_on(self.client.list_all(filter, max_pages))
+    }
+
+    /// Recurring or template generators.
+    pub fn list_generators(
+        &self,
+        kind: GeneratorKind,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<Generator>, FakturoidError> {
+        self.page(self.client.list_generators(kind, filter))
+    }
+
+    /// Fulltext search of entities narrowed by filter.
+    pub fn fulltext<T>(
+        &self,
+        search: &str,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.page(self.client.fulltext(search, filter))
+    }
+
+    /// Fulltext search of entities with all given tags.
+    pub fn fulltext_tagged<T>(
+        &self,
+        search: &str,
+        tags: &[&str],
+    ) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.page(self.client.fulltext_tagged(search, tags))
+    }
+
+    /// Fires action on entity with given id.
+    pub fn action<T: Action, D: Serialize>(
+        &self,
+        id: i32,
+        action: T,
+        data: Option<D>,
+    ) -> Result<(), FakturoidError> {
+        self.block_on(self.client.action(id, action, data))
+    }
+
+    /// Marks invoice as sent without sending email.
+    pub fn mark_as_sent(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.mark_as_sent(id))
+    }
+
+    /// Sends invoice to client by email.
+    pub fn deliver(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.deliver(id))
+    }
+
+    /// Marks invoice as paid.
+    pub fn pay(&self, id: i32, data: InvoicePayData) -> Result<(), FakturoidError> {
+        self.block_on(self.client.pay(id, data))
+    }
+
+    /// Marks proforma as paid, tax document is created.
+    pub fn pay_proforma(&self, id: i32, data: InvoicePayData) -> Result<(), FakturoidError> {
+        self.block_on(self.client.pay_proforma(id, data))
+    }
+
+    /// Marks partial proforma as paid.
+    pub fn pay_partial_proforma(
+        &self,
+        id: i32,
+        data: InvoicePayData,
+    ) -> Result<(), FakturoidError> {
+        self.block_on(self.client.pay_partial_proforma(id, data))
+    }
+
+    /// Removes payment of invoice.
+    pub fn remove_payment(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.remove_payment(id))
+    }
+
+    /// Sends payment reminder of overdue invoice.
+    pub fn deliver_reminder(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.deliver_reminder(id))
+    }
+
+    /// Cancels invoice.
+    pub fn cancel(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.cancel(id))
+    }
+
+    /// Reverts cancellation of invoice.
+    pub fn undo_cancel(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.undo_cancel(id))
+    }
+
+    /// Locks invoice, so it can't be changed.
+    pub fn lock(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.lock(id))
+    }
+
+    /// Unlocks invoice.
+    pub fn unlock(&self, id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.unlock(id))
+    }
+
+    /// Fires action and returns updated entity, see `client::Fakturoid::action_updated()`.
+    pub fn action_updated<T: EntityAction, D: Serialize>(
+        &self,
+        id: i32,
+        action: T,
+        data: Option<D>,
+    ) -> Result<T::Entity, FakturoidError> {
+        self.block_on(self.client.action_updated(id, action, data))
+    }
+
+    /// Sends request to endpoint not covered by this library, see
+    /// `client::Fakturoid::raw_request()`.
+    pub fn raw_request<T, D>(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<D>,
+    ) -> Result<T, FakturoidError>
+    where
+        T: DeserializeOwned,
+        D: Serialize,
+    {
+        self.block_on(self.client.raw_request(method, path, data))
+    }
+
+    /// PDF of invoice, `None` if it is not generated yet.
+    pub fn invoice_pdf(&self, id: i32) -> Result<Option<Vec<u8>>, FakturoidError> {
+        self.block_on(self.client.invoice_pdf(id))
+    }
+
+    /// Downloads content of invoice or expense attachment.
+    pub fn download_attachment(
+        &self,
+        attachment: &RemoteAttachment,
+    ) -> Result<Bytes, FakturoidError> {
+        self.block_on(self.client.download_attachment(attachment))
+    }
+
+    /// Bank accounts of account.
+    pub fn bank_accounts(&self) -> Result<Vec<BankAccount>, FakturoidError> {
+        self.block_on(self.client.bank_accounts())
+    }
+
+    /// User authenticated by credentials of client, see `client::Fakturoid::current_user()`.
+    pub fn current_user(&self) -> Result<User, FakturoidError> {
+        self.block_on(self.client.current_user())
+    }
+
+    /// Users with access to account.
+    pub fn account_users(&self) -> Result<Vec<User>, FakturoidError> {
+        self.block_on(self.client.account_users())
+    }
+
+    /// Number formats of invoices.
+    pub fn number_formats(&self) -> Result<Vec<NumberFormat>, FakturoidError> {
+        self.block_on(self.client.number_formats())
+    }
+
+    /// Checks number format of invoice, see `client::Fakturoid::validate_number_format()`.
+    pub fn validate_number_format(&self, invoice: &Invoice) -> Result<(), FakturoidError> {
+        self.block_on(self.client.validate_number_format(invoice))
+    }
+
+    /// Activity feed of account.
+    pub fn events(&self, filter: Option<Filter>) -> Result<PagedResponse<Event>, FakturoidError> {
+        self.page(self.client.events(filter))
+    }
+
+    /// Events of paid invoices.
+    pub fn events_paid(
+        &self,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<Event>, FakturoidError> {
+        self.page(self.client.events_paid(filter))
+    }
+
+    /// Todos of account.
+    pub fn todos(&self, filter: Option<Filter>) -> Result<PagedResponse<Todo>, FakturoidError> {
+        self.page(self.client.todos(filter))
+    }
+
+    /// Marks todo as completed or not completed.
+    pub fn toggle_todo_completion(&self, id: i32) -> Result<Todo, FakturoidError> {
+        self.block_on(self.client.toggle_todo_completion(id))
+    }
+
+    /// Records payment of invoice. Available in API v3.
+    pub fn create_payment(
+        &self,
+        invoice_id: i32,
+        payment: InvoicePayment,
+    ) -> Result<InvoicePayment, FakturoidError> {
+        self.block_on(self.client.create_payment(invoice_id, payment))
+    }
+
+    /// Removes payment from invoice. Available in API v3.
+    pub fn delete_payment(&self, invoice_id: i32, payment_id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.delete_payment(invoice_id, payment_id))
+    }
+
+    /// Payments of invoice. Available in API v3.
+    pub fn list_payments(&self, invoice_id: i32) -> Result<Vec<InvoicePayment>, FakturoidError> {
+        self.block_on(self.client.list_payments(invoice_id))
+    }
+
+    /// Records payment of expense. Available in API v3.
+    pub fn create_expense_payment(
+        &self,
+        expense_id: i32,
+        payment: ExpensePayment,
+    ) -> Result<ExpensePayment, FakturoidError> {
+        self.block_on(self.client.create_expense_payment(expense_id, payment))
+    }
+
+    /// Removes payment from expense. Available in API v3.
+    pub fn delete_expense_payment(
+        &self,
+        expense_id: i32,
+        payment_id: i32,
+    ) -> Result<(), FakturoidError> {
+        self.block_on(self.client.delete_expense_payment(expense_id, payment_id))
+    }
+
+    /// Payments of expense. Available in API v3.
+    pub fn list_expense_payments(
+        &self,
+        expense_id: i32,
+    ) -> Result<Vec<ExpensePayment>, FakturoidError> {
+        self.block_on(self.client.list_expense_payments(expense_id))
+    }
+
+    /// Moves of inventory item, see `client::Fakturoid::list_inventory_moves()`.
+    pub fn list_inventory_moves(
+        &self,
+        item_id: i32,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<InventoryMove>, FakturoidError> {
+        self.page(self.client.list_inventory_moves(item_id, filter))
+    }
+
+    /// Creates move of inventory item.
+    pub fn create_inventory_move(
+        &self,
+        item_id: i32,
+        inventory_move: InventoryMove,
+    ) -> Result<InventoryMove, FakturoidError> {
+        self.block_on(self.client.create_inventory_move(item_id, inventory_move))
+    }
+
+    /// Detail of inventory move.
+    pub fn inventory_move(
+        &self,
+        item_id: i32,
+        move_id: i32,
+    ) -> Result<InventoryMove, FakturoidError> {
+        self.block_on(self.client.inventory_move(item_id, move_id))
+    }
+
+    /// Updates inventory move.
+    pub fn update_inventory_move(
+        &self,
+        item_id: i32,
+        move_id: i32,
+        inventory_move: InventoryMove,
+    ) -> Result<InventoryMove, FakturoidError> {
+        self.block_on(
+            self.client
+                .update_inventory_move(item_id, move_id, inventory_move),
+        )
+    }
+
+    /// Deletes inventory move.
+    pub fn delete_inventory_move(&self, item_id: i32, move_id: i32) -> Result<(), FakturoidError> {
+        self.block_on(self.client.delete_inventory_move(item_id, move_id))
+    }
+
+    /// Waits until requests in flight are finished, new requests are rejected.
+    pub fn shutdown(&self, timeout: Duration) -> Result<(), FakturoidError> {
+        self.block_on(self.client.shutdown(timeout))
+    }
+
+    fn page<T, F>(&self, future: F) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: DeserializeOwned,
+        F: Future<Output = Result<AsyncPage<T>, FakturoidError>>,
+    {
+        Ok(PagedResponse {
+            page: self.block_on(future)?,
+            runtime: self.runtime.clone(),
+        })
+    }
+}
+
+/// Page of list returned by blocking client, see `client::PagedResponse`.
+pub struct PagedResponse<T: DeserializeOwned> {
+    page: AsyncPage<T>,
+    runtime: SharedRuntime,
+}
+
+impl<T: DeserializeOwned> PagedResponse<T> {
+    fn navigate<N, F>(self, navigate: N) -> Result<Self, FakturoidError>
+    where
+        N: FnOnce(AsyncPage<T>) -> F,
+        F: Future<Output = Result<AsyncPage<T>, FakturoidError>>,
+    {
+        let page = block_on(&self.runtime, navigate(self.page))?;
+        Ok(Self {
+            page,
+            runtime: self.runtime,
+        })
+    }
+
+    /// Reference to vector of items. There could be max 20 items.
+    pub fn data(&self) -> &Vec<T> {
+        self.page.data()
+    }
+
+    /// Moves items out of page.
+    pub fn into_data(self) -> Vec<T> {
+        self.page.into_data()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.page.iter()
+    }
+
+    /// Metadata of response of this page.
+    pub fn meta(&self) -> &ResponseMeta {
+        self.page.meta()
+    }
+
+    /// True if page was served from response cache.
+    pub fn was_cached(&self) -> bool {
+        self.page.was_cached()
+    }
+
+    /// Number of last page of list, `None` if this is the only or last page.
+    pub fn last_page_number(&self) -> Option<u32> {
+        self.page.last_page_number()
+    }
+
+    /// True if next page exists.
+    pub fn has_next(&self) -> bool {
+        self.page.has_next()
+    }
+
+    /// True if previous page exists.
+    pub fn has_prev(&self) -> bool {
+        self.page.has_prev()
+    }
+
+    /// First page of list.
+    pub fn first_page(self) -> Result<Self, FakturoidError> {
+        self.navigate(AsyncPage::first_page)
+    }
+
+    /// Previous page of list, the same page is returned if this is the first page.
+    pub fn prev_page(self) -> Result<Self, FakturoidError> {
+        self.navigate(AsyncPage::prev_page)
+    }
+
+    /// Next page of list, the same page is returned if this is the last page.
+    pub fn next_page(self) -> Result<Self, FakturoidError> {
+        self.navigate(AsyncPage::next_page)
+    }
+
+    /// Last page of list.
+    pub fn last_page(self) -> Result<Self, FakturoidError> {
+        self.navigate(AsyncPage::last_page)
+    }
+
+    /// Items of this and all following pages, see `client::PagedResponse::collect_all()`.
+    pub fn collect_all(self, max_pages: Option<usize>) -> Result<Vec<T>, FakturoidError> {
+        block_on(&self.runtime, self.page.collect_all(max_pages))
+    }
+}
+
+impl FakturoidBuilder {
+    /// Creates new instance of blocking client, see [`blocking`](../blocking/index.html)
+    /// module.
+    pub fn build_blocking(self) -> Result<Fakturoid, FakturoidError> {
+        Fakturoid::from_async(self.build()?)
+    }
+}
@@ -0,0 +1,278 @@
+//! Fluent builders of invoices and subjects. Fields required by API are checked by `build()`, so
+//! incomplete record is rejected before it is sent.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::models::{Invoice, PaymentMethod};
+//! use rust_decimal::Decimal;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let invoice = Invoice::builder()
+//!         .subject_id(16)
+//!         .due(14)
+//!         .payment_method(PaymentMethod::Bank)
+//!         .line("Consulting", Decimal::from(10), Some("hour"), Decimal::from(1500), 21)
+//!         .build()?;
+//!     let invoice = client.create(invoice).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::currency::Currency;
+use crate::dates::Date;
+use crate::error::MissingField;
+use crate::models::{
+    Invoice, InvoiceDocumentType, InvoiceLanguage, InvoiceLine, PaymentMethod, Subject,
+    SubjectType, VatPriceMode,
+};
+use rust_decimal::Decimal;
+
+/// Builder of invoice, `subject_id` and at least one line are required.
+#[derive(Clone, Debug, Default)]
+pub struct InvoiceBuilder {
+    invoice: Invoice,
+}
+
+impl InvoiceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder of invoice for given subject, see `Invoice::for_subject()`.
+    pub fn for_subject(subject: &Subject) -> Self {
+        Self {
+            invoice: Invoice::for_subject(subject),
+        }
+    }
+
+    pub fn subject_id(mut self, subject_id: i32) -> Self {
+        self.invoice.subject_id = Some(subject_id);
+        self
+    }
+
+    pub fn custom_id(mut self, custom_id: &str) -> Self {
+        self.invoice.custom_id = Some(custom_id.to_string());
+        self
+    }
+
+    pub fn document_type(mut self, document_type: InvoiceDocumentType) -> Self {
+        self.invoice.document_type = Some(document_type);
+        self
+    }
+
+    pub fn number(mut self, number: &str) -> Self {
+        self.invoice.number = Some(number.to_string());
+        self
+    }
+
+    pub fn order_number(mut self, order_number: &str) -> Self {
+        self.invoice.order_number = Some(order_number.to_string());
+        self
+    }
+
+    pub fn variable_symbol(mut self, variable_symbol: &str) -> Self {
+        self.invoice.variable_symbol = Some(variable_symbol.to_string());
+        self
+    }
+
+    pub fn issued_on(mut self, issued_on: Date) -> Self {
+        self.invoice.issued_on = Some(issued_on);
+        self
+    }
+
+    pub fn taxable_fulfillment_due(mut self, taxable_fulfillment_due: Date) -> Self {
+        self.invoice.taxable_fulfillment_due = Some(taxable_fulfillment_due);
+        self
+    }
+
+    /// Number of days until invoice is due.
+    pub fn due(mut self, days: i32) -> Self {
+        self.invoice.due = Some(days);
+        self
+    }
+
+    pub fn payment_method(mut self, payment_method: PaymentMethod) -> Self {
+        self.invoice.payment_method = Some(payment_method);
+        self
+    }
+
+    pub fn currency(mut self, currency: Currency) -> Self {
+        self.invoice.currency = Some(currency);
+        self
+    }
+
+    pub fn language(mut self, language: InvoiceLanguage) -> Self {
+        self.invoice.language = Some(language);
+        self
+    }
+
+    pub fn vat_price_mode(mut self, vat_price_mode: VatPriceMode) -> Self {
+        self.invoice.vat_price_mode = Some(vat_price_mode);
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> Self {
+        self.invoice.note = Some(note.to_string());
+        self
+    }
+
+    pub fn footer_note(mut self, footer_note: &str) -> Self {
+        self.invoice.footer_note = Some(footer_note.to_string());
+        self
+    }
+
+    pub fn private_note(mut self, private_note: &str) -> Self {
+        self.invoice.private_note = Some(private_note.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.invoice
+            .tags
+            .get_or_insert_with(Vec::new)
+            .push(tag.to_string());
+        self
+    }
+
+    /// Adds line, see `InvoiceLine::new()`.
+    pub fn line<R: Into<Decimal>>(
+        self,
+        name: &str,
+        quantity: Decimal,
+        unit_name: Option<&str>,
+        unit_price: Decimal,
+        vat_rate: R,
+    ) -> Self {
+        self.add_line(InvoiceLine::new(
+            name, quantity, unit_name, unit_price, vat_rate,
+        ))
+    }
+
+    /// Adds line with quantity one and no unit.
+    pub fn item<R: Into<Decimal>>(self, name: &str, price: Decimal, vat_rate: R) -> Self {
+        self.line(name, Decimal::ONE, None, price, vat_rate)
+    }
+
+    pub fn add_line(mut self, line: InvoiceLine) -> Self {
+        self.invoice.add_line(line);
+        self
+    }
+
+    /// Invoice with subject and at least one line.
+    pub fn build(self) -> Result<Invoice, MissingField> {
+        if self.invoice.subject_id.is_none() {
+            return Err(MissingField::new("subject_id"));
+        }
+        if self
+            .invoice
+            .lines
+            .as_ref()
+            .map(|l| l.is_empty())
+            .unwrap_or(true)
+        {
+            return Err(MissingField::new("lines"));
+        }
+        Ok(self.invoice)
+    }
+}
+
+/// Builder of subject, `name` is required.
+#[derive(Clone, Debug, Default)]
+pub struct SubjectBuilder {
+    subject: Subject,
+}
+
+impl SubjectBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.subject.name = Some(name.to_string());
+        self
+    }
+
+    pub fn custom_id(mut self, custom_id: &str) -> Self {
+        self.subject.custom_id = Some(custom_id.to_string());
+        self
+    }
+
+    pub fn subject_type(mut self, subject_type: SubjectType) -> Self {
+        self.subject.sub_type = Some(subject_type);
+        self
+    }
+
+    /// Street, city, ZIP code and ISO code of country.
+    pub fn address(mut self, street: &str, city: &str, zip: &str, country: &str) -> Self {
+        self.subject.street = Some(street.to_string());
+        self.subject.city = Some(city.to_string());
+        self.subject.zip = Some(zip.to_string());
+        self.subject.country = Some(country.to_string());
+        self
+    }
+
+    pub fn registration_no(mut self, registration_no: &str) -> Self {
+        self.subject.registration_no = Some(registration_no.to_string());
+        self
+    }
+
+    pub fn vat_no(mut self, vat_no: &str) -> Self {
+        self.subject.vat_no = Some(vat_no.to_string());
+        self
+    }
+
+    pub fn bank_account(mut self, bank_account: &str) -> Self {
+        self.subject.bank_account = Some(bank_account.to_string());
+        self
+    }
+
+    pub fn iban(mut self, iban: &str) -> Self {
+        self.subject.iban = Some(iban.to_string());
+        self
+    }
+
+    pub fn full_name(mut self, full_name: &str) -> Self {
+        self.subject.full_name = Some(full_name.to_string());
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.subject.email = Some(email.to_string());
+        self
+    }
+
+    pub fn phone(mut self, phone: &str) -> Self {
+        self.subject.phone = Some(phone.to_string());
+        self
+    }
+
+    pub fn web(mut self, web: &str) -> Self {
+        self.subject.web = Some(web.to_string());
+        self
+    }
+
+    /// Subject with non-empty name.
+    pub fn build(self) -> Result<Subject, MissingField> {
+        match self.subject.name.as_deref() {
+            Some(name) if !name.trim().is_empty() => Ok(self.subject),
+            _ => Err(MissingField::new("name")),
+        }
+    }
+}
+
+impl Invoice {
+    pub fn builder() -> InvoiceBuilder {
+        InvoiceBuilder::new()
+    }
+}
+
+impl Subject {
+    pub fn builder() -> SubjectBuilder {
+        SubjectBuilder::new()
+    }
+}
@@ -1,21 +1,115 @@
-use crate::error::{DataErrors, FakturoidError, UnknownError};
-use crate::filters::{Filter, FilterBuilder, InvoiceFilter, NoneFilter, SubjectFilter};
-use crate::models::{Account, Invoice, InvoiceAction, Subject};
-use reqwest::{Client, Response};
+use crate::account_cache::AccountCache;
+use crate::auth::{self, Auth, TokenStore};
+use crate::dump::HttpDump;
+use crate::error::{
+    BasicAuthNotSupported, ClientShutDown, DataErrors, FakturoidError, ForeignUrl,
+    InvalidRateLimit, InvalidUserAgent, Kind, PageLimitExceeded, ShutdownTimeout, UnknownError,
+    UnknownNumberFormat,
+};
+use crate::filters::{
+    AllFilter, EntityFilter, EventFilter, Filter, FilterBuilder, GeneratorFilter,
+    InventoryItemFilter, InventoryMoveFilter, NoneFilter, SubjectFilter, TodoFilter,
+};
+use crate::models::{
+    Account, AccountSettings, BankAccount, Event, Expense, ExpenseAction, ExpensePayment,
+    Generator, GeneratorAction, GeneratorKind, InventoryItem, InventoryMove, Invoice,
+    InvoiceAction, InvoicePayment, InvoiceSummary, NumberFormat, RemoteAttachment, Subject,
+    SubjectSummary, Todo, User, Webhook,
+};
+pub use crate::queue::Priority;
+use crate::queue::RequestQueue;
+#[cfg(feature = "cache")]
+use crate::response_cache::{self, CachedResponse, ResponseCache};
+pub use crate::response_meta::ResponseMeta;
+use crate::retry;
+pub use crate::retry::RetryPolicy;
+use crate::shutdown::{InFlight, ShutdownState};
+use crate::streaming::ArrayParser;
+use crate::subject_cache::SubjectCache;
+use crate::transport::Transport;
+#[cfg(feature = "performance")]
+use crate::views::RawPage;
+use bytes::Bytes;
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use reqwest::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, ClientBuilder, Method, Request, RequestBuilder, Response, StatusCode, Url};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::delay_for;
 
-/// Object in fakturoid.cz.
+/// Object in fakturoid.cz. Can be implemented for own types (most easily by `entity!` macro),
+/// so endpoints which are not covered by this library can be used with generic methods of
+/// client, see also `Fakturoid::list_url()` and `Fakturoid::raw_request()`.
 pub trait Entity {
     fn url_part() -> &'static str;
     fn filter_builder() -> Box<dyn FilterBuilder>;
 }
 
-/// Actions on invoices.
+/// Implements `Entity` trait for custom struct, so endpoints which are not covered by this
+/// library can be used with generic methods of `Fakturoid` client. Second argument is part of
+/// URL after account slug, optional third argument is filter builder. If filter builder is
+/// omitted all filter parameters are passed to API.
+///
+/// # Example
+///
+/// ```
+/// use fakturoid::entity;
+/// use fakturoid::filters::NoneFilter;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct InboxFile {
+///     pub id: i32,
+///     pub filename: String,
+/// }
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// pub struct Message {
+///     pub subject: String,
+/// }
+///
+/// entity!(InboxFile, "inbox_files");
+/// entity!(Message, "messages", NoneFilter);
+/// ```
+#[macro_export]
+macro_rules! entity {
+    ($entity:ty, $url_part:expr) => {
+        $crate::entity!($entity, $url_part, $crate::filters::AllFilter);
+    };
+    ($entity:ty, $url_part:expr, $filter:expr) => {
+        impl $crate::client::Entity for $entity {
+            fn url_part() -> &'static str {
+                $url_part
+            }
+
+            fn filter_builder() -> Box<dyn $crate::filters::FilterBuilder> {
+                Box::new($filter)
+            }
+        }
+    };
+}
+
+/// Actions on entities, e.g. invoices or recurring generators.
 pub trait Action: ToString {
     fn url_part() -> &'static str;
     fn query(&self) -> HashMap<String, String>;
+
+    /// Name of endpoint of entity which performs action, `fire` by default.
+    fn endpoint(&self) -> String {
+        "fire".to_string()
+    }
+
+    /// HTTP method of action, `POST` by default.
+    fn method(&self) -> Method {
+        Method::POST
+    }
 }
 
 impl Entity for Account {
@@ -44,22 +138,172 @@ impl Entity for Invoice {
     }
 
     fn filter_builder() -> Box<dyn FilterBuilder> {
-        Box::new(InvoiceFilter)
+        Box::new(AllFilter)
+    }
+}
+
+impl Entity for SubjectSummary {
+    fn url_part() -> &'static str {
+        Subject::url_part()
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Subject::filter_builder()
+    }
+}
+
+impl SubjectSummary {
+    /// Downloads complete subject.
+    pub async fn hydrate(&self, client: &Fakturoid) -> Result<Subject, FakturoidError> {
+        client.detail(self.id).await
+    }
+}
+
+impl Entity for InvoiceSummary {
+    fn url_part() -> &'static str {
+        Invoice::url_part()
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Invoice::filter_builder()
+    }
+}
+
+impl InvoiceSummary {
+    /// Downloads complete invoice.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::InvoiceSummary;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let invoices = client.list::<InvoiceSummary>(None).await?;
+    ///     if let Some(summary) = invoices.data().first() {
+    ///         let invoice = summary.hydrate(&client).await?;
+    ///         println!("{:?}", invoice.lines);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn hydrate(&self, client: &Fakturoid) -> Result<Invoice, FakturoidError> {
+        client.detail(self.id).await
+    }
+}
+
+impl Entity for Expense {
+    fn url_part() -> &'static str {
+        "expenses"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(AllFilter)
+    }
+}
+
+impl Entity for Generator {
+    fn url_part() -> &'static str {
+        "generators"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(GeneratorFilter)
+    }
+}
+
+impl Entity for BankAccount {
+    fn url_part() -> &'static str {
+        "bank_accounts"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(NoneFilter)
+    }
+}
+
+impl Entity for User {
+    fn url_part() -> &'static str {
+        "users"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(NoneFilter)
+    }
+}
+
+impl Entity for NumberFormat {
+    fn url_part() -> &'static str {
+        "number_formats/invoices"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(NoneFilter)
+    }
+}
+
+impl Entity for Webhook {
+    fn url_part() -> &'static str {
+        "webhooks"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(NoneFilter)
+    }
+}
+
+impl Entity for InventoryItem {
+    fn url_part() -> &'static str {
+        "inventory_items"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(InventoryItemFilter)
+    }
+}
+
+impl Entity for Event {
+    fn url_part() -> &'static str {
+        "events"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(EventFilter)
+    }
+}
+
+impl Entity for Todo {
+    fn url_part() -> &'static str {
+        "todos"
+    }
+
+    fn filter_builder() -> Box<dyn FilterBuilder> {
+        Box::new(TodoFilter)
     }
 }
 /// Response from list or fulltext method.
-pub struct PagedResponse<T: Entity + DeserializeOwned> {
+pub struct PagedResponse<T: DeserializeOwned> {
     collection: Vec<T>,
     client: Fakturoid,
     links: HashMap<String, String>,
+    meta: ResponseMeta,
 }
 
-impl<T: Entity + DeserializeOwned> PagedResponse<T> {
-    fn new(collection: Vec<T>, client: Fakturoid, links: HashMap<String, String>) -> Self {
+impl<T: DeserializeOwned> PagedResponse<T> {
+    fn new(
+        collection: Vec<T>,
+        client: Fakturoid,
+        links: HashMap<String, String>,
+        meta: ResponseMeta,
+    ) -> Self {
         Self {
             collection,
             client,
             links,
+            meta,
         }
     }
 
@@ -71,11 +315,43 @@ impl<T: Entity + DeserializeOwned> PagedResponse<T> {
         }
     }
 
+    /// True if page was served from response cache (see `FakturoidBuilder::memory_cache()`),
+    /// because API reported it unchanged since previous request.
+    pub fn was_cached(&self) -> bool {
+        self.meta.was_cached()
+    }
+
+    /// Metadata of response of this page, e.g. remaining rate limit.
+    pub fn meta(&self) -> &ResponseMeta {
+        &self.meta
+    }
+
+    /// Number of last page of list according to `Link` header, `None` if this is the only or
+    /// last page. Useful for progress reporting.
+    pub fn last_page_number(&self) -> Option<u32> {
+        let url = self.links.get("last")?;
+        let query = &url[url.find('?')? + 1..];
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("page="))
+            .and_then(|page| page.parse().ok())
+    }
+
     /// Reference to vector of items. There could be max 20 items.
     pub fn data(&self) -> &Vec<T> {
         &self.collection
     }
 
+    /// Transforms response into vector of items of this page.
+    pub fn into_data(self) -> Vec<T> {
+        self.collection
+    }
+
+    /// Iterator over items of this page.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.collection.iter()
+    }
+
     /// First page of list with more than 20 items. New instance of `PagedResponse` will be returned
     /// in case of success, otherwise `FakturoidError` will be returned. If there is only one page
     /// method returns the same instance.
@@ -113,6 +389,117 @@ impl<T: Entity + DeserializeOwned> PagedResponse<T> {
     pub fn has_prev(&self) -> bool {
         self.links.contains_key("prev")
     }
+
+    /// Downloads all following pages and returns items of this and all following pages. Fails
+    /// with `PageLimitExceeded` error if there are more than `max_pages` pages including this
+    /// one, so runaway scripts don't exhaust rate limit.
+    pub async fn collect_all(self, max_pages: Option<usize>) -> Result<Vec<T>, FakturoidError> {
+        let mut items = self.collection;
+        let mut next = self.links.get("next").cloned();
+        let mut pages = 1;
+        while let Some(url) = next {
+            if let Some(max) = max_pages.filter(|max| pages >= *max) {
+                return Err(FakturoidError::from_std_err(PageLimitExceeded::new(max)));
+            }
+            let page = self.client.get_url::<T>(url.as_str(), None).await?;
+            next = page.links.get("next").cloned();
+            items.extend(page.collection);
+            pages += 1;
+        }
+        Ok(items)
+    }
+
+    /// Transforms response into stream of individual items. Items of this page are yielded
+    /// first, following pages are downloaded lazily when stream reaches their items. Stream ends
+    /// after first error. The stream is boxed, so it can be polled by `StreamExt::next()`
+    /// without pinning it first.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Invoice;
+    /// use fakturoid::client::Fakturoid;
+    /// use futures::StreamExt;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut invoices = client.list::<Invoice>(None).await?.into_stream();
+    ///     while let Some(invoice) = invoices.next().await {
+    ///         println!("{}", invoice?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_stream(self) -> BoxStream<'static, Result<T, FakturoidError>>
+    where
+        T: Send + 'static,
+    {
+        self.items().boxed()
+    }
+
+    #[deprecated(since = "0.1.4", note = "use `into_stream()`")]
+    pub fn into_item_stream(self) -> impl Stream<Item = Result<T, FakturoidError>> {
+        self.items()
+    }
+
+    fn items(self) -> impl Stream<Item = Result<T, FakturoidError>> {
+        let next = self.links.get("next").cloned();
+        let state = (self.collection.into_iter(), next, self.client);
+        stream::unfold(Some(state), |state| async move {
+            let (mut items, mut next, client) = state?;
+            loop {
+                if let Some(item) = items.next() {
+                    return Some((Ok(item), Some((items, next, client))));
+                }
+                let url = next?;
+                match client.get_url::<T>(url.as_str(), None).await {
+                    Ok(page) => {
+                        next = page.links.get("next").cloned();
+                        items = page.collection.into_iter();
+                    }
+                    Err(err) => return Some((Err(err), None)),
+                }
+            }
+        })
+    }
+}
+
+impl<T: DeserializeOwned> IntoIterator for PagedResponse<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.collection.into_iter()
+    }
+}
+
+impl<'a, T: DeserializeOwned> IntoIterator for &'a PagedResponse<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.collection.iter()
+    }
+}
+
+/// Action which changes entity, see `Fakturoid::action_updated()`.
+pub trait EntityAction: Action {
+    /// Entity changed by action, its detail is at the same URL as actions.
+    type Entity: DeserializeOwned;
+}
+
+impl EntityAction for InvoiceAction {
+    type Entity = Invoice;
+}
+
+impl EntityAction for ExpenseAction {
+    type Entity = Expense;
+}
+
+impl EntityAction for GeneratorAction {
+    type Entity = Generator;
 }
 
 impl Action for InvoiceAction {
@@ -128,336 +515,2209 @@ impl Action for InvoiceAction {
     }
 }
 
-/// Fakturoid client
-#[derive(Clone)]
-pub struct Fakturoid {
-    user: String,
-    password: String,
-    slug: String,
-    user_agent: Option<String>,
-    client: Client,
+impl Action for ExpenseAction {
+    fn url_part() -> &'static str {
+        "expenses"
+    }
+
+    fn query(&self) -> HashMap<String, String> {
+        [("event", self.to_string())]
+            .iter()
+            .map(|q| (q.0.to_string(), q.1.clone()))
+            .collect()
+    }
 }
 
-impl Fakturoid {
-    /// Creates new instance of client.
-    /// If user_agent is None "Rust API client (pepa@bukova.info) will be used.
-    pub fn new(user: &str, password: &str, slug: &str, user_agent: Option<&str>) -> Self {
-        Self {
-            user: user.to_string(),
-            password: password.to_string(),
-            slug: slug.to_string(),
-            user_agent: {
-                if let Some(ua) = user_agent {
-                    Some(ua.to_string())
-                } else {
-                    None
-                }
-            },
-            client: Client::new(),
-        }
+impl Action for GeneratorAction {
+    fn url_part() -> &'static str {
+        "recurring_generators"
     }
 
-    fn url_first(&self) -> String {
-        format!("https://app.fakturoid.cz/api/v2/accounts/{}/", self.slug)
+    fn query(&self) -> HashMap<String, String> {
+        HashMap::new()
     }
 
-    fn url_with_id(&self, entity_part: &str, id: i32) -> String {
-        format!("{}{}/{}.json", self.url_first(), entity_part, id)
+    fn endpoint(&self) -> String {
+        self.to_string()
     }
 
-    fn user_agent(&self) -> String {
-        if let Some(ua) = self.user_agent.as_ref() {
-            ua.clone()
+    fn method(&self) -> Method {
+        Method::PATCH
+    }
+}
+
+/// Response of request sent by client. Request is counted as in flight by
+/// `Fakturoid::shutdown()` until body is consumed and this is dropped.
+struct Sent {
+    response: Response,
+    /// URL of request, response of transport (e.g. `MockTransport`) may have no URL.
+    url: Url,
+    _in_flight: InFlight,
+}
+
+impl Sent {
+    /// Parses JSON body, see `Fakturoid::evaluate_response()`.
+    async fn evaluate<T: DeserializeOwned>(self) -> Result<T, FakturoidError> {
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self;
+        Fakturoid::evaluate_response(response).await
+    }
+
+    /// Checks that response is successful, see `Fakturoid::evaluate()`.
+    async fn check(self) -> Result<(), FakturoidError> {
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self;
+        Fakturoid::evaluate(response).await
+    }
+
+    /// Body of successful response.
+    async fn bytes(self) -> Result<Bytes, FakturoidError> {
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self;
+        if response.status().is_success() {
+            Ok(response.bytes().await?)
         } else {
-            "Rust API client (pepa@bukova.info)".to_string()
+            Err(Fakturoid::error_response(response).await)
         }
     }
+}
 
-    async fn paged_response<T>(
-        &self,
-        response: Response,
-    ) -> Result<PagedResponse<T>, FakturoidError>
-    where
-        T: Entity + DeserializeOwned,
-    {
-        if let Some(link) = response.headers().get("Link") {
-            let mut links = HashMap::<String, String>::new();
-            for lnk in link
-                .to_str()
-                .map_err(FakturoidError::from_std_err)?
-                .split(",")
-            {
-                let parts: Vec<_> = lnk.split(";").collect();
-                if parts.len() == 2 {
-                    let key = parts[1][6..parts[1].len() - 1].trim();
-                    let val = parts[0][1..parts[0].len() - 1].trim();
-                    links.insert(key.to_string(), val.replace("<", ""));
+/// State of stream returned by `Fakturoid::list_streamed()`.
+struct ItemStream<T> {
+    client: Fakturoid,
+    next: Option<RequestBuilder>,
+    response: Option<Sent>,
+    parser: ArrayParser,
+    items: VecDeque<T>,
+    /// URL of current page of API v3, next page is known when all its items are received.
+    numbered_page: Option<Url>,
+    page_items: usize,
+}
+
+impl<T: DeserializeOwned> ItemStream<T> {
+    /// Receives next chunk of current page or requests next page. Returns `false` when there is
+    /// nothing more to receive.
+    async fn receive(&mut self) -> Result<bool, FakturoidError> {
+        let sent = match self.response.as_mut() {
+            Some(sent) => sent,
+            None => {
+                let request = match self.next.take() {
+                    Some(request) => request,
+                    None => return Ok(false),
+                };
+                let sent = self.client.send(request).await?;
+                if !sent.response.status().is_success() {
+                    return Err(Fakturoid::error_response(sent.response).await);
+                }
+                let mut links = Fakturoid::parse_links(&sent.response)?;
+                self.numbered_page = if links.is_empty() && self.client.version == ApiVersion::V3 {
+                    Some(sent.url.clone())
+                } else {
+                    None
+                };
+                self.next = links
+                    .remove("next")
+                    .map(|url| self.client.request(Method::GET, &url));
+                self.parser = ArrayParser::new();
+                self.page_items = 0;
+                self.response.get_or_insert(sent)
+            }
+        };
+        match sent.response.chunk().await? {
+            Some(chunk) => {
+                let mut items = Vec::new();
+                self.parser
+                    .feed(&chunk, &mut items)
+                    .map_err(FakturoidError::from_std_err)?;
+                self.page_items += items.len();
+                self.items.extend(items);
+            }
+            None => {
+                self.parser.finish().map_err(FakturoidError::from_std_err)?;
+                self.response = None;
+                if let Some(url) = self.numbered_page.take() {
+                    self.next = Fakturoid::numbered_page_links(&url, self.page_items)
+                        .remove("next")
+                        .map(|url| self.client.request(Method::GET, &url));
                 }
             }
-            Ok(PagedResponse::new(
-                response.json::<Vec<T>>().await?,
-                self.clone(),
-                links,
-            ))
-        } else {
-            Ok(PagedResponse::new(
-                response.json::<Vec<T>>().await?,
-                self.clone(),
-                HashMap::<String, String>::new(),
-            ))
         }
+        Ok(true)
     }
+}
 
-    async fn get_url<T>(
-        &self,
-        url: &str,
-        filter: Option<HashMap<String, String>>,
-    ) -> Result<PagedResponse<T>, FakturoidError>
-    where
-        T: Entity + DeserializeOwned,
-    {
-        let resp = if let Some(flt) = filter {
-            self.client
-                .get(url)
-                .basic_auth(self.user.as_str(), Some(self.password.as_str()))
-                .header("User-Agent", self.user_agent())
-                .query(&flt)
-                .send()
-                .await?
-        } else {
+/// Version of fakturoid.cz API used by client.
+///
+/// API v3 accepts only OAuth authentication (see `Fakturoid::oauth_builder()`) and some fields
+/// of models were renamed there, e.g. `Invoice::document_type` replaces `proforma`,
+/// `partial_proforma` and `correction` flags and `Invoice::paid_on` replaces `paid_at`. Models
+/// contain fields of both versions, fields not used by selected version stay `None`.
+/// Lists of API v3 have no `Link` header, pages are requested by their number until page
+/// shorter than 40 items is received.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApiVersion {
+    V2,
+    V3,
+}
+
+impl ApiVersion {
+    fn path(self) -> &'static str {
+        match self {
+            ApiVersion::V2 => "v2",
+            ApiVersion::V3 => "v3",
+        }
+    }
+}
+
+/// URL of Fakturoid used unless `FakturoidBuilder::base_url()` is set.
+const DEFAULT_BASE_URL: &str = "https://app.fakturoid.cz/";
+/// Number of items on full page of list in API v3.
+const V3_PAGE_SIZE: usize = 40;
+
+/// Builder for `Fakturoid` client with non default settings.
+///
+/// # Example
+///
+/// ```no_run
+/// use fakturoid::client::Fakturoid;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+///     .user_agent("My app (developer@company.com)")
+///     .dump_http(std::io::stderr())
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FakturoidBuilder {
+    auth: Auth,
+    version: ApiVersion,
+    slug: String,
+    base_url: String,
+    client: Option<Client>,
+    transport: Option<Arc<dyn Transport>>,
+    user_agent: Option<String>,
+    dump: Option<HttpDump>,
+    client_builder: ClientBuilder,
+    subject_cache: Option<Arc<SubjectCache>>,
+    #[cfg(feature = "cache")]
+    response_cache: Option<Arc<ResponseCache>>,
+    rate_limit: Option<(u32, u32)>,
+    rate_limit_state: Option<PathBuf>,
+    retry: Option<RetryPolicy>,
+}
+
+impl FakturoidBuilder {
+    /// Credentials used instead of email and API key given to `Fakturoid::builder()`, see
+    /// [`auth`](../auth/index.html) module.
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    /// Version of API, default is v2 for clients authenticated by API key and v3 for clients
+    /// created by `Fakturoid::oauth_builder()`. API v3 can't be used with API key, `build()`
+    /// fails with `BasicAuthNotSupported` error then.
+    pub fn api_version(mut self, version: ApiVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// User agent sent with every request. It must contain contact email to developer of
+    /// application, e.g. `My app (developer@company.com)`, otherwise `build()` fails.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Sends requests through given client instead of client created by this builder, so
+    /// proxies, timeouts or TLS can be configured directly. Settings of connection pool,
+    /// keepalive and certificates made by this builder are ignored then.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let http = reqwest::Client::builder()
+    ///     .proxy(reqwest::Proxy::https("http://proxy.company.com:3128")?)
+    ///     .timeout(Duration::from_secs(30))
+    ///     .build()?;
+    /// let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+    ///     .http_client(http)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Sends requests through given transport instead of HTTP client, see
+    /// [`transport`](../transport/index.html) module.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// URL of Fakturoid instead of `https://app.fakturoid.cz/`, e.g. address of mock server in
+    /// tests. API paths are appended to it.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = if base_url.ends_with('/') {
+            base_url.to_string()
+        } else {
+            format!("{}/", base_url)
+        };
+        self
+    }
+
+    /// Dumps full requests and responses into given writer. Credentials are redacted, so dump
+    /// can be attached to bug report.
+    pub fn dump_http<W: Write + Send + 'static>(mut self, writer: W) -> Self {
+        self.dump = Some(HttpDump::Writer(Arc::new(Mutex::new(Box::new(writer)))));
+        self
+    }
+
+    /// Dumps full requests and responses on debug level of log target `fakturoid::http`.
+    /// Credentials are redacted.
+    #[cfg(feature = "log")]
+    pub fn dump_http_to_log(mut self) -> Self {
+        self.dump = Some(HttpDump::Log);
+        self
+    }
+
+    /// Disables verification of TLS certificates. It is needed behind proxies which intercept
+    /// TLS traffic with their own certificates.
+    ///
+    /// # Warning
+    ///
+    /// Any certificate will be trusted, including expired ones or certificates for different
+    /// host, so communication can be intercepted by anybody. Use only if there is no other way.
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.client_builder = self
+            .client_builder
+            .danger_accept_invalid_certs(accept_invalid_certs);
+        self
+    }
+
+    /// Maximum number of idle connections kept open per host. Default is unlimited.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.client_builder = self.client_builder.pool_max_idle_per_host(max);
+        self
+    }
+
+    /// Idle connections are closed after given timeout, `None` means that they are never
+    /// closed. Default is 90 seconds.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.client_builder = self.client_builder.pool_idle_timeout(timeout);
+        self
+    }
+
+    /// Interval of TCP keepalive probes, `None` disables them. Default is disabled.
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.client_builder = self.client_builder.tcp_keepalive(interval);
+        self
+    }
+
+    /// Caches details of up to `capacity` subjects for `ttl`, so repeated calls of
+    /// `detail::<Subject>()` with the same id don't consume rate limit. Cached subject is
+    /// dropped when it is updated or deleted through client.
+    pub fn subject_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.subject_cache = Some(Arc::new(SubjectCache::new(capacity, ttl)));
+        self
+    }
+
+    /// Persists GET responses in given directory and revalidates them with `ETag` and
+    /// `Last-Modified` headers, so unchanged data isn't downloaded again in next run of program.
+    /// Cached responses contain personal data of customers, directory should be protected
+    /// accordingly.
+    #[cfg(feature = "cache")]
+    pub fn disk_cache<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.response_cache = Some(Arc::new(ResponseCache::disk(dir.into())));
+        self
+    }
+
+    /// Keeps up to `capacity` GET responses in memory and revalidates them with `ETag` and
+    /// `Last-Modified` headers. Useful for integrations polling the same URLs, responses which
+    /// API reports as unchanged don't consume data transfer. Use `was_cached()` of
+    /// `PagedResponse` or `Fakturoid::detail_cached()` to find out whether data changed.
+    #[cfg(feature = "cache")]
+    pub fn memory_cache(mut self, capacity: usize) -> Self {
+        self.response_cache = Some(Arc::new(ResponseCache::memory(capacity)));
+        self
+    }
+
+    /// Keeps requests of client and all its clones within `per_minute` requests per minute,
+    /// requests over the limit wait. `reserved` requests of each minute can be used only by
+    /// interactive requests, so background synchronization can't starve requests the user is
+    /// waiting for. See `Fakturoid::with_priority()`. Limit of zero requests is rejected by
+    /// `build()` with `InvalidRateLimit` error.
+    pub fn rate_limit(mut self, per_minute: u32, reserved: u32) -> Self {
+        self.rate_limit = Some((per_minute, reserved));
+        self
+    }
+
+    /// Saves usage of `rate_limit()` in current minute into given file when client is shut
+    /// down and restores it in `build()`, so restarted service doesn't exceed the limit. Ignored
+    /// without `rate_limit()`.
+    pub fn rate_limit_state<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.rate_limit_state = Some(path.into());
+        self
+    }
+
+    /// Requests rejected with `429 Too Many Requests` are sent again according to given policy,
+    /// by default error with `Kind::TooManyRequests` is returned. Retries are counted by
+    /// `rate_limit()` as well.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::{Fakturoid, RetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+    ///     .retry_policy(RetryPolicy::new(5).initial_delay(Duration::from_secs(2)))
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Creates new instance of client. Fails with `InvalidUserAgent` error if user agent doesn't
+    /// contain contact email, with `InvalidRateLimit` error if rate limit is zero and with
+    /// `BasicAuthNotSupported` error if API v3 is used with API key.
+    pub fn build(self) -> Result<Fakturoid, FakturoidError> {
+        if let (ApiVersion::V3, Auth::Basic { .. }) = (self.version, &self.auth) {
+            return Err(FakturoidError::from_std_err(BasicAuthNotSupported));
+        }
+        if let Some(ua) = self.user_agent.as_ref() {
+            validate_user_agent(ua)?;
+        }
+        let queue = match self.rate_limit {
+            Some((0, _)) => return Err(FakturoidError::from_std_err(InvalidRateLimit)),
+            Some((per_minute, reserved)) => {
+                let queue = RequestQueue::new(per_minute, reserved, Duration::from_secs(60));
+                Some(Arc::new(match self.rate_limit_state {
+                    Some(path) => queue.persistent(path),
+                    None => queue,
+                }))
+            }
+            None => None,
+        };
+        Ok(Fakturoid {
+            auth: self.auth,
+            token: Arc::default(),
+            version: self.version,
+            slug: self.slug,
+            base_url: self.base_url,
+            user_agent: self.user_agent,
+            client: match self.client {
+                Some(client) => client,
+                None => self.client_builder.build()?,
+            },
+            transport: self.transport,
+            dump: self.dump,
+            subject_cache: self.subject_cache,
+            #[cfg(feature = "cache")]
+            response_cache: self.response_cache,
+            queue,
+            retry: self.retry,
+            priority: Priority::Interactive,
+            account_cache: Arc::default(),
+            shutdown: Arc::default(),
+        })
+    }
+}
+
+/// URL of page of list with given number, other query parameters are kept.
+fn page_url(url: &Url, page: u32) -> String {
+    let query: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "page")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    let mut url = url.clone();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("page", &page.to_string());
+    url.to_string()
+}
+
+/// Checks that user agent contains something which looks like email address.
+fn validate_user_agent(user_agent: &str) -> Result<(), FakturoidError> {
+    let has_email = user_agent
+        .split(|c: char| c.is_whitespace() || "()<>[],;".contains(c))
+        .any(|word| match word.find('@') {
+            Some(at) => at > 0 && word[at + 1..].contains('.') && !word.ends_with('.'),
+            None => false,
+        });
+    if has_email {
+        Ok(())
+    } else {
+        Err(FakturoidError::from_std_err(InvalidUserAgent::new(
+            user_agent,
+        )))
+    }
+}
+
+/// Image downloaded by `Fakturoid::download_avatar()`.
+#[derive(Clone, Debug)]
+pub struct Avatar {
+    /// Content type sent by server, e.g. `image/png`.
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// Entity returned by `Fakturoid::detail_cached()`, it dereferences to the entity.
+#[derive(Clone, Debug)]
+pub struct Cached<T> {
+    entity: T,
+    cached: bool,
+}
+
+impl<T> Cached<T> {
+    /// True if entity was served from response cache, because API reported it unchanged.
+    pub fn was_cached(&self) -> bool {
+        self.cached
+    }
+
+    pub fn into_inner(self) -> T {
+        self.entity
+    }
+}
+
+impl<T> Deref for Cached<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.entity
+    }
+}
+
+/// Result of health check made by `Fakturoid::ping()`.
+#[derive(Debug)]
+pub enum PingStatus {
+    /// Credentials and slug are valid, API is reachable.
+    Ok,
+    /// Email or API key is wrong.
+    BadCredentials,
+    /// Account with given slug doesn't exist or user has no access to it.
+    WrongSlug,
+    /// API server can't be reached.
+    NetworkDown,
+    /// Request failed for other reason.
+    Failed(FakturoidError),
+}
+
+impl PingStatus {
+    /// True if health check passed.
+    pub fn is_ok(&self) -> bool {
+        matches!(self, PingStatus::Ok)
+    }
+}
+
+/// Fakturoid client
+#[derive(Clone)]
+pub struct Fakturoid {
+    auth: Auth,
+    token: Arc<TokenStore>,
+    version: ApiVersion,
+    slug: String,
+    base_url: String,
+    user_agent: Option<String>,
+    client: Client,
+    transport: Option<Arc<dyn Transport>>,
+    dump: Option<HttpDump>,
+    subject_cache: Option<Arc<SubjectCache>>,
+    #[cfg(feature = "cache")]
+    response_cache: Option<Arc<ResponseCache>>,
+    queue: Option<Arc<RequestQueue>>,
+    retry: Option<RetryPolicy>,
+    priority: Priority,
+    account_cache: Arc<AccountCache>,
+    shutdown: Arc<ShutdownState>,
+}
+
+impl Fakturoid {
+    /// Creates new instance of client.
+    /// If user_agent is None "Rust API client (pepa@bukova.info) will be used.
+    /// User agent without contact email is logged as warning, use `builder()` to reject it.
+    pub fn new(user: &str, password: &str, slug: &str, user_agent: Option<&str>) -> Self {
+        #[cfg(feature = "log")]
+        {
+            if let Some(Err(err)) = user_agent.map(validate_user_agent) {
+                log::warn!("{}", err);
+            }
+        }
+        Self {
+            auth: Auth::basic(user, password),
+            token: Arc::default(),
+            version: ApiVersion::V2,
+            slug: slug.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            user_agent: {
+                if let Some(ua) = user_agent {
+                    Some(ua.to_string())
+                } else {
+                    None
+                }
+            },
+            client: Client::new(),
+            transport: None,
+            dump: None,
+            subject_cache: None,
+            #[cfg(feature = "cache")]
+            response_cache: None,
+            queue: None,
+            retry: None,
+            priority: Priority::Interactive,
+            account_cache: Arc::default(),
+            shutdown: Arc::default(),
+        }
+    }
+
+    /// Creates builder for client with non default settings.
+    pub fn builder(user: &str, password: &str, slug: &str) -> FakturoidBuilder {
+        Self::builder_with_auth(Auth::basic(user, password), ApiVersion::V2, slug)
+    }
+
+    /// Creates builder for client authenticated by OAuth 2.0 client credentials flow, see
+    /// [`auth`](../auth/index.html) module.
+    pub fn oauth_builder(client_id: &str, client_secret: &str, slug: &str) -> FakturoidBuilder {
+        Self::builder_with_auth(
+            Auth::client_credentials(client_id, client_secret),
+            ApiVersion::V3,
+            slug,
+        )
+    }
+
+    fn builder_with_auth(auth: Auth, version: ApiVersion, slug: &str) -> FakturoidBuilder {
+        FakturoidBuilder {
+            auth,
+            version,
+            slug: slug.to_string(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            client: None,
+            transport: None,
+            user_agent: None,
+            dump: None,
+            client_builder: Client::builder(),
+            subject_cache: None,
+            #[cfg(feature = "cache")]
+            response_cache: None,
+            rate_limit: None,
+            rate_limit_state: None,
+            retry: None,
+        }
+    }
+
+    /// Returns client which sends given user agent instead of configured one. It is cheap, the
+    /// connection pool and caches are shared with this client. Useful for platforms which act
+    /// on behalf of their customers and need to identify them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Account;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let account = client
+    ///         .with_user_agent("My platform for ACME (ops@acme.cz)")?
+    ///         .account()
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_user_agent(&self, user_agent: &str) -> Result<Fakturoid, FakturoidError> {
+        validate_user_agent(user_agent)?;
+        let mut client = self.clone();
+        client.user_agent = Some(user_agent.to_string());
+        Ok(client)
+    }
+
+    /// Returns client which sends requests with given priority, the rate limit set by
+    /// `FakturoidBuilder::rate_limit()` is shared with this client. Without rate limit priority
+    /// has no effect.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::{Fakturoid, Priority};
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+    ///         .rate_limit(200, 50)
+    ///         .build()?;
+    ///     let sync_client = client.with_priority(Priority::Batch);
+    ///     let invoices = sync_client.list::<Invoice>(None).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_priority(&self, priority: Priority) -> Fakturoid {
+        let mut client = self.clone();
+        client.priority = priority;
+        client
+    }
+
+    /// Returns client working with account with given slug. Connection pool, credentials, rate
+    /// limit and response cache are shared with this client, caches of account details and
+    /// subjects are separate for each account. Use `current_user()` to find slugs of accounts
+    /// accessible by credentials.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::builder("user@company.com", "apicode", "first")
+    ///         .rate_limit(200, 50)
+    ///         .build()?;
+    ///     let second = client.with_slug("second");
+    ///     let first_invoices = client.list::<Invoice>(None).await?;
+    ///     let second_invoices = second.list::<Invoice>(None).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_slug(&self, slug: &str) -> Fakturoid {
+        let mut client = self.clone();
+        client.slug = slug.to_string();
+        client.account_cache = Arc::default();
+        client.subject_cache = self
+            .subject_cache
+            .as_ref()
+            .map(|cache| Arc::new(cache.empty_copy()));
+        client
+    }
+
+    /// Slug of account used by client.
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    /// Version of API used by client.
+    pub fn api_version(&self) -> ApiVersion {
+        self.version
+    }
+
+    fn url_first(&self) -> String {
+        format!(
+            "{}api/{}/accounts/{}/",
+            self.base_url,
+            self.version.path(),
+            self.slug
+        )
+    }
+
+    fn url_with_id(&self, entity_part: &str, id: i32) -> String {
+        format!("{}{}/{}.json", self.url_first(), entity_part, id)
+    }
+
+    fn user_agent(&self) -> String {
+        if let Some(ua) = self.user_agent.as_ref() {
+            ua.clone()
+        } else {
+            "Rust API client (pepa@bukova.info)".to_string()
+        }
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        auth::apply_basic(&self.auth, self.client.request(method, url))
+            .header("User-Agent", self.user_agent())
+    }
+
+    /// Adds access token to request if client uses OAuth, token is returned so it can be
+    /// invalidated when API rejects it. Requests to other hosts than base URL (e.g. avatars on
+    /// CDN) get no token.
+    async fn authorize(&self, request: &mut Request) -> Result<Option<String>, FakturoidError> {
+        let (client_id, client_secret) = match &self.auth {
+            Auth::ClientCredentials {
+                client_id,
+                client_secret,
+            } => (client_id, client_secret),
+            Auth::Basic { .. } => return Ok(None),
+        };
+        if !request.url().as_str().starts_with(self.base_url.as_str()) {
+            return Ok(None);
+        }
+        let token = self
+            .token
+            .token(
+                &self.client,
+                self.transport.as_deref(),
+                &self.base_url,
+                &self.user_agent(),
+                client_id,
+                client_secret,
+            )
+            .await?;
+        let value = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(FakturoidError::from_std_err)?;
+        request.headers_mut().insert(AUTHORIZATION, value);
+        Ok(Some(token))
+    }
+
+    async fn execute(&self, request: Request) -> Result<Response, FakturoidError> {
+        #[cfg(feature = "log")]
+        log::debug!("request: {} {}", request.method(), request.url());
+        if let Some(dump) = self.dump.as_ref() {
+            dump.request(&request);
+        }
+        let response = match self.transport.as_ref() {
+            Some(transport) => transport.execute(request).await?,
+            None => self.client.execute(request).await?,
+        };
+        let response = if let Some(dump) = self.dump.as_ref() {
+            dump.response(response).await?
+        } else {
+            response
+        };
+        #[cfg(feature = "log")]
+        log::debug!("response: {} {}", response.status(), response.url());
+        Ok(response)
+    }
+
+    /// Executes request and repeats it while API rejects it with `429 Too Many Requests` and
+    /// retry policy allows it.
+    async fn execute_retrying(&self, mut request: Request) -> Result<Response, FakturoidError> {
+        let policy = match self.retry.as_ref() {
+            Some(policy) => policy,
+            None => return self.execute(request).await,
+        };
+        let mut retries = 0;
+        loop {
+            let next = request.try_clone();
+            let response = self.execute(request).await?;
+            match next {
+                Some(next)
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS
+                        && retries < policy.max_retries() =>
+                {
+                    let delay = policy.delay(retries, retry::retry_after(response.headers()));
+                    #[cfg(feature = "log")]
+                    log::debug!("rate limit exceeded, retrying in {:?}", delay);
+                    delay_for(delay).await;
+                    if let Some(queue) = self.queue.as_ref() {
+                        queue.acquire(self.priority).await;
+                    }
+                    retries += 1;
+                    request = next;
+                }
+                _ => return Ok(response),
+            }
+        }
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Sent, FakturoidError> {
+        let in_flight = self
+            .shutdown
+            .enter()
+            .ok_or_else(|| FakturoidError::from_std_err(ClientShutDown))?;
+        let mut request = request.build()?;
+        let url = request.url().clone();
+        if let Some(queue) = self.queue.as_ref() {
+            queue.acquire(self.priority).await;
+        }
+        #[cfg(feature = "cache")]
+        let (cache_url, cached) = self.prepare_cache(&mut request);
+        let token = self.authorize(&mut request).await?;
+        let retry = token.as_ref().and_then(|_| request.try_clone());
+        let mut response = self.execute_retrying(request).await?;
+        if let (Some(token), Some(mut retry)) = (token, retry) {
+            if response.status() == StatusCode::UNAUTHORIZED {
+                // token was revoked or expired sooner than announced
+                self.token.invalidate(&token).await;
+                self.authorize(&mut retry).await?;
+                response = self.execute_retrying(retry).await?;
+            }
+        }
+        #[cfg(feature = "cache")]
+        let response = if let Some(url) = cache_url {
+            self.cache_response(&url, cached, response).await?
+        } else {
+            response
+        };
+        Ok(Sent {
+            response,
+            url,
+            _in_flight: in_flight,
+        })
+    }
+
+    /// URL under which response of GET request is cached and cached response with conditional
+    /// headers added to request.
+    #[cfg(feature = "cache")]
+    fn prepare_cache(&self, request: &mut Request) -> (Option<String>, Option<CachedResponse>) {
+        if self.response_cache.is_none() || request.method() != Method::GET {
+            return (None, None);
+        }
+        let url = request.url().to_string();
+        let cached = self.response_cache.as_ref().and_then(|c| c.get(&url));
+        if let Some(entry) = cached.as_ref() {
+            entry.apply_conditions(request);
+        }
+        (Some(url), cached)
+    }
+
+    #[cfg(feature = "cache")]
+    async fn cache_response(
+        &self,
+        url: &str,
+        cached: Option<CachedResponse>,
+        response: Response,
+    ) -> Result<Response, FakturoidError> {
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                return Ok(entry.to_revalidated_response());
+            }
+        }
+        let (entry, response) = CachedResponse::from_response(url, response).await?;
+        if let (Some(entry), Some(cache)) = (entry, self.response_cache.as_ref()) {
+            cache.put(&entry);
+        }
+        Ok(response)
+    }
+
+    /// True if response was replayed from response cache, because API reported it unchanged.
+    fn was_cached(response: &Response) -> bool {
+        #[cfg(feature = "cache")]
+        {
+            response_cache::is_revalidated(response)
+        }
+        #[cfg(not(feature = "cache"))]
+        {
+            let _ = response;
+            false
+        }
+    }
+
+    fn response_meta(response: &Response) -> ResponseMeta {
+        ResponseMeta::new(
+            response.status(),
+            response.headers().clone(),
+            Self::was_cached(response),
+        )
+    }
+
+    async fn paged_response<T>(&self, sent: Sent) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: DeserializeOwned,
+    {
+        let Sent {
+            response,
+            url,
+            _in_flight,
+        } = sent;
+        let mut links = Self::parse_links(&response)?;
+        let meta = Self::response_meta(&response);
+        let collection = response.json::<Vec<T>>().await?;
+        if links.is_empty() && self.version == ApiVersion::V3 {
+            links = Self::numbered_page_links(&url, collection.len());
+        }
+        Ok(PagedResponse::new(collection, self.clone(), links, meta))
+    }
+
+    /// Links to other pages of list of API v3, which has no `Link` header. Next page is
+    /// expected unless this page is shorter than full page.
+    fn numbered_page_links(url: &Url, items: usize) -> HashMap<String, String> {
+        let page = url
+            .query_pairs()
+            .find(|(key, _)| key == "page")
+            .and_then(|(_, value)| value.parse::<u32>().ok())
+            .unwrap_or(1);
+        let mut links = HashMap::new();
+        if items >= V3_PAGE_SIZE {
+            links.insert("next".to_string(), page_url(url, page + 1));
+        }
+        if page > 1 {
+            links.insert("prev".to_string(), page_url(url, page - 1));
+            links.insert("first".to_string(), page_url(url, 1));
+        }
+        links
+    }
+
+    fn parse_links(response: &Response) -> Result<HashMap<String, String>, FakturoidError> {
+        let mut links = HashMap::<String, String>::new();
+        if let Some(link) = response.headers().get("Link") {
+            for lnk in link
+                .to_str()
+                .map_err(FakturoidError::from_std_err)?
+                .split(",")
+            {
+                let parts: Vec<_> = lnk.split(";").collect();
+                if parts.len() == 2 {
+                    let key = parts[1][6..parts[1].len() - 1].trim();
+                    let val = parts[0][1..parts[0].len() - 1].trim();
+                    links.insert(key.to_string(), val.replace("<", ""));
+                }
+            }
+        }
+        Ok(links)
+    }
+
+    async fn get_url<T>(
+        &self,
+        url: &str,
+        filter: Option<HashMap<String, String>>,
+    ) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: DeserializeOwned,
+    {
+        let req = self.request(Method::GET, url);
+        let resp = if let Some(flt) = filter {
+            self.send(req.query(&flt)).await?
+        } else {
+            self.send(req).await?
+        };
+
+        self.paged_response(resp).await
+    }
+
+    #[cfg(feature = "performance")]
+    pub(crate) async fn get_raw(
+        &self,
+        url: &str,
+        filter: Option<HashMap<String, String>>,
+    ) -> Result<RawPage, FakturoidError> {
+        let mut request = self.request(Method::GET, url);
+        if let Some(flt) = filter {
+            request = request.query(&flt);
+        }
+        let Sent {
+            response,
+            url: page_url,
+            _in_flight,
+        } = self.send(request).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_response(response).await);
+        }
+        let mut links = Self::parse_links(&response)?;
+        let body = response.bytes().await?.to_vec();
+        if links.is_empty() && self.version == ApiVersion::V3 {
+            let items = serde_json::from_slice::<Vec<serde::de::IgnoredAny>>(&body)
+                .map(|items| items.len())
+                .unwrap_or_default();
+            links = Self::numbered_page_links(&page_url, items);
+        }
+        Ok(RawPage::new(body, links, self.clone()))
+    }
+
+    async fn error_response(response: Response) -> FakturoidError {
+        if let Err(e) = response.error_for_status_ref() {
+            if response.status() == 422 {
+                match response.json::<DataErrors>().await {
+                    Ok(data) => FakturoidError::from_data(data, e),
+                    Err(err) => FakturoidError::from_std_err(err),
+                }
+            } else {
+                e.into()
+            }
+        } else {
+            FakturoidError::from_std_err(UnknownError::new("evaluate_response<T>()"))
+        }
+    }
+
+    /// Parses JSON body of successful response, unsuccessful response is turned into error
+    /// (with errors of fields for `422 Unprocessable Entity`).
+    pub async fn evaluate_response<T>(response: Response) -> Result<T, FakturoidError>
+    where
+        T: DeserializeOwned,
+    {
+        if response.status().is_success() {
+            Ok(response.json::<T>().await?)
+        } else {
+            Err(Self::error_response(response).await)
+        }
+    }
+
+    /// Checks that response is successful, unsuccessful response is turned into error.
+    pub async fn evaluate(response: Response) -> Result<(), FakturoidError> {
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(Self::error_response(response).await)
+        }
+    }
+    fn cached_subjects<T: Entity>(&self) -> Option<&SubjectCache> {
+        if T::url_part() == Subject::url_part() {
+            self.subject_cache.as_deref()
+        } else {
+            None
+        }
+    }
+
+    async fn detail_private<T>(&self, id: Option<i32>) -> Result<T, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        if let (Some(id), Some(cache)) = (id, self.cached_subjects::<T>()) {
+            let value = match cache.get(id) {
+                Some(value) => value,
+                None => {
+                    let value: Value = self
+                        .send(self.request(Method::GET, &self.url_with_id(T::url_part(), id)))
+                        .await?
+                        .evaluate()
+                        .await?;
+                    cache.insert(id, value.clone());
+                    value
+                }
+            };
+            return serde_json::from_value(value).map_err(FakturoidError::from_std_err);
+        }
+
+        let url = if let Some(id) = id {
+            self.url_with_id(T::url_part(), id)
+        } else {
+            format!("{}{}.json", self.url_first(), T::url_part())
+        };
+        self.send(self.request(Method::GET, &url))
+            .await?
+            .evaluate()
+            .await
+    }
+
+    /// Detail of entity with given id.
+    pub async fn detail<T>(&self, id: i32) -> Result<T, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.detail_private(Some(id)).await
+    }
+
+    /// Detail of entity with given id together with metadata of response, e.g. remaining rate
+    /// limit or id of request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Subject;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let (subject, meta) = client.detail_with_meta::<Subject>(16).await?;
+    ///     if meta.rate_limit_remaining() == Some(0) {
+    ///         println!("Rate limit reset in {:?}", meta.rate_limit_reset());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn detail_with_meta<T>(&self, id: i32) -> Result<(T, ResponseMeta), FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self
+            .send(self.request(Method::GET, &self.url_with_id(T::url_part(), id)))
+            .await?;
+        let meta = Self::response_meta(&response);
+        Ok((Self::evaluate_response(response).await?, meta))
+    }
+
+    /// Detail of entity with given id together with information whether it was served from
+    /// response cache, so pollers can skip processing of unchanged entity. Without response
+    /// cache (see `FakturoidBuilder::memory_cache()`) entity is never cached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let invoice = client.detail_cached::<Invoice>(27).await?;
+    ///     if !invoice.was_cached() {
+    ///         println!("Invoice changed, status {:?}", invoice.status);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn detail_cached<T>(&self, id: i32) -> Result<Cached<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self
+            .send(self.request(Method::GET, &self.url_with_id(T::url_part(), id)))
+            .await?;
+        let cached = Self::was_cached(&response);
+        Ok(Cached {
+            entity: Self::evaluate_response(response).await?,
+            cached,
+        })
+    }
+
+    /// PDF of invoice with given id. `None` is returned if PDF is not generated yet, it should be
+    /// requested again after a while.
+    pub async fn invoice_pdf(&self, id: i32) -> Result<Option<Vec<u8>>, FakturoidError> {
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self
+            .send(self.request(
+                Method::GET,
+                &format!(
+                    "{}{}/{}/download.pdf",
+                    self.url_first(),
+                    Invoice::url_part(),
+                    id
+                ),
+            ))
+            .await?;
+        if response.status() == StatusCode::NO_CONTENT {
+            Ok(None)
+        } else if response.status().is_success() {
+            Ok(Some(response.bytes().await?.to_vec()))
+        } else {
+            Err(Self::error_response(response).await)
+        }
+    }
+
+    /// Downloads content of invoice or expense attachment. Download URL outside of base URL of
+    /// client is rejected, so credentials are not sent to other hosts.
+    pub async fn download_attachment(
+        &self,
+        attachment: &RemoteAttachment,
+    ) -> Result<Bytes, FakturoidError> {
+        let url = self.own_url(&attachment.download_url)?;
+        self.send(self.request(Method::GET, url))
+            .await?
+            .bytes()
+            .await
+    }
+
+    /// Downloads content of attachment as stream of chunks, so large attachments don't have to
+    /// be held in memory. Stream ends after first error. Download URL outside of base URL of
+    /// client is rejected like in [`download_attachment()`](#method.download_attachment).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Expense;
+    /// use futures::TryStreamExt;
+    /// use std::fs::File;
+    /// use std::io::Write;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let expense = client.detail::<Expense>(5).await?;
+    ///     if let Some(attachment) = expense.attachment() {
+    ///         let mut file = File::create(&attachment.file_name)?;
+    ///         let mut chunks = Box::pin(client.download_attachment_stream(attachment));
+    ///         while let Some(chunk) = chunks.try_next().await? {
+    ///             file.write_all(&chunk)?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn download_attachment_stream(
+        &self,
+        attachment: &RemoteAttachment,
+    ) -> impl Stream<Item = Result<Bytes, FakturoidError>> {
+        let request = self
+            .own_url(&attachment.download_url)
+            .map(|url| self.request(Method::GET, url));
+        let state = (self.clone(), Some(request), None::<Sent>);
+        stream::unfold(Some(state), |state| async move {
+            let (client, request, sent) = state?;
+            let mut sent = match (request, sent) {
+                (_, Some(sent)) => sent,
+                (Some(Err(err)), None) => return Some((Err(err), None)),
+                (Some(Ok(request)), None) => match client.send(request).await {
+                    Ok(sent) if sent.response.status().is_success() => sent,
+                    Ok(sent) => {
+                        return Some((Err(Self::error_response(sent.response).await), None))
+                    }
+                    Err(err) => return Some((Err(err), None)),
+                },
+                (None, None) => return None,
+            };
+            match sent.response.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some((client, None, Some(sent))))),
+                Ok(None) => None,
+                Err(err) => Some((Err(err.into()), None)),
+            }
+        })
+    }
+
+    /// Downloads avatar (logo) of subject. `None` is returned if subject has no avatar.
+    /// Credentials are sent only if avatar is hosted by fakturoid.cz.
+    pub async fn download_avatar(
+        &self,
+        subject: &Subject,
+    ) -> Result<Option<Avatar>, FakturoidError> {
+        let url = match subject.avatar_url.as_deref() {
+            Some(url) if !url.is_empty() => url,
+            _ => return Ok(None),
+        };
+        let request = if url.starts_with(self.base_url.as_str()) {
+            self.request(Method::GET, url)
+        } else {
             self.client
-                .get(url)
-                .basic_auth(self.user.as_str(), Some(self.password.as_str()))
+                .request(Method::GET, url)
                 .header("User-Agent", self.user_agent())
-                .send()
-                .await?
         };
+        let Sent {
+            response,
+            _in_flight,
+            ..
+        } = self.send(request).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_response(response).await);
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|ct| ct.to_str().ok())
+            .map(|ct| ct.to_string());
+        Ok(Some(Avatar {
+            content_type,
+            data: response.bytes().await?.to_vec(),
+        }))
+    }
+
+    /// Stops accepting new requests and waits at most `timeout` until requests in flight are
+    /// finished. Applies to all clones of client, their new requests fail with `ClientShutDown`
+    /// error. Fails with `ShutdownTimeout` error if some requests are still in flight after
+    /// timeout. Request is in flight until its response is read, e.g. until stream of
+    /// `download_attachment_stream()` ends or is dropped. Usage of rate limit is saved if
+    /// `FakturoidBuilder::rate_limit_state()` is set. Disk cache and HTTP dump are written
+    /// continuously and client keeps no synchronization cursor (caller stores time of last
+    /// synchronization, see [`sync`](../sync/index.html) module), so nothing else has to be
+    /// flushed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use std::time::Duration;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let account = client.account().await?;
+    ///     client.shutdown(Duration::from_secs(30)).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn shutdown(&self, timeout: Duration) -> Result<(), FakturoidError> {
+        let in_flight = self.shutdown.close(timeout).await;
+        if let Some(queue) = self.queue.as_ref() {
+            queue.save()?;
+        }
+        match in_flight {
+            0 => Ok(()),
+            in_flight => Err(FakturoidError::from_std_err(ShutdownTimeout::new(
+                in_flight,
+            ))),
+        }
+    }
+
+    /// Whether `shutdown()` was called on this client or any of its clones.
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown.is_closed()
+    }
+
+    /// Drops all subjects cached by client. See `FakturoidBuilder::subject_cache()`.
+    pub fn clear_subject_cache(&self) {
+        if let Some(cache) = self.subject_cache.as_ref() {
+            cache.clear();
+        }
+    }
+
+    /// Account details.
+    pub async fn account(&self) -> Result<Account, FakturoidError> {
+        self.detail_private(None).await
+    }
+
+    /// Changes account settings, only filled fields of settings are sent. Updated account
+    /// details will be returned in case of success and replace details cached by
+    /// `cached_account()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::AccountSettings;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut settings = AccountSettings::default();
+    ///     settings.due = Some(30);
+    ///     settings.unit_name = Some("pcs".to_string());
+    ///     let account = client.update_account(&settings).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn update_account(
+        &self,
+        settings: &AccountSettings,
+    ) -> Result<Account, FakturoidError> {
+        let url = format!("{}{}.json", self.url_first(), Account::url_part());
+        let account: Account = self
+            .send(self.request(Method::PATCH, &url).json(settings))
+            .await?
+            .evaluate()
+            .await?;
+        self.account_cache.insert(Arc::new(account.clone()));
+        Ok(account)
+    }
+
+    /// Bank accounts configured in account settings.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::currency::Currency;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let eur_account = client
+    ///         .bank_accounts()
+    ///         .await?
+    ///         .into_iter()
+    ///         .find(|a| a.currency == Some(Currency::EUR));
+    ///     let mut invoice = Invoice::default();
+    ///     invoice.currency = Some(Currency::EUR);
+    ///     invoice.bank_account_id = eur_account.and_then(|a| a.id);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn bank_accounts(&self) -> Result<Vec<BankAccount>, FakturoidError> {
+        self.list::<BankAccount>(None)
+            .await?
+            .collect_all(None)
+            .await
+    }
+
+    /// User authenticated by credentials of client, including accounts accessible by the
+    /// credentials. Slug of client isn't used, so client with any slug can discover slugs of
+    /// accessible accounts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let user = client.current_user().await?;
+    ///     for slug in user.slugs() {
+    ///         let account = Fakturoid::new("user@company.com", "apicode", slug, None)
+    ///             .account()
+    ///             .await?;
+    ///         println!("{}: {}", slug, account.name);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn current_user(&self) -> Result<User, FakturoidError> {
+        let url = format!("{}api/{}/user.json", self.base_url, self.version.path());
+        self.send(self.request(Method::GET, &url))
+            .await?
+            .evaluate()
+            .await
+    }
+
+    /// Users with access to account.
+    pub async fn account_users(&self) -> Result<Vec<User>, FakturoidError> {
+        self.list::<User>(None).await?.collect_all(None).await
+    }
+
+    /// Number formats (numbering series) of invoices.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let format = client
+    ///         .number_formats()
+    ///         .await?
+    ///         .into_iter()
+    ///         .find(|f| f.format.as_deref() == Some("EXP-#yyyy#-#dddd#"));
+    ///     let mut invoice = Invoice::default();
+    ///     invoice.number_format_id = format.and_then(|f| f.id);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn number_formats(&self) -> Result<Vec<NumberFormat>, FakturoidError> {
+        self.list::<NumberFormat>(None)
+            .await?
+            .collect_all(None)
+            .await
+    }
+
+    /// Checks that `number_format_id` of invoice refers to existing number format, so invoice
+    /// isn't rejected by API. Fails with `UnknownNumberFormat` error otherwise. Invoice without
+    /// number format is numbered by default format and it isn't checked.
+    pub async fn validate_number_format(&self, invoice: &Invoice) -> Result<(), FakturoidError> {
+        let id = match invoice.number_format_id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+        if self
+            .number_formats()
+            .await?
+            .iter()
+            .any(|f| f.id == Some(id))
+        {
+            Ok(())
+        } else {
+            Err(FakturoidError::from_std_err(UnknownNumberFormat::new(id)))
+        }
+    }
+
+    /// Account details downloaded once and cached for ten minutes. Cache is shared by clones of
+    /// client.
+    pub async fn cached_account(&self) -> Result<Arc<Account>, FakturoidError> {
+        if let Some(account) = self.account_cache.get() {
+            return Ok(account);
+        }
+        let account = Arc::new(self.account().await?);
+        self.account_cache.insert(account.clone());
+        Ok(account)
+    }
+
+    /// Drops account details cached by `cached_account()`, e.g. after account settings were
+    /// changed.
+    pub fn clear_account_cache(&self) {
+        self.account_cache.clear();
+    }
+
+    /// Fills empty fields of invoice with defaults from account settings, see
+    /// `Invoice::with_account_defaults()`. Account details are cached.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut invoice = Invoice::default();
+    ///     invoice.subject_id = Some(16);
+    ///     let invoice = client.with_account_defaults(invoice).await?;
+    ///     let invoice = client.create(invoice).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn with_account_defaults(&self, invoice: Invoice) -> Result<Invoice, FakturoidError> {
+        let account = self.cached_account().await?;
+        Ok(invoice.with_account_defaults(&account))
+    }
+
+    /// Checks configuration of client with lightweight authenticated request and classifies
+    /// result. Useful for verifying configuration at application startup.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::client::{Fakturoid, PingStatus};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     match client.ping().await {
+    ///         PingStatus::Ok => println!("Configuration is OK"),
+    ///         PingStatus::BadCredentials => eprintln!("Check email and API key"),
+    ///         status => eprintln!("Fakturoid is not available: {:?}", status),
+    ///     }
+    /// }
+    /// ```
+    pub async fn ping(&self) -> PingStatus {
+        match self.account().await {
+            Ok(_) => PingStatus::Ok,
+            Err(err) => match err.kind() {
+                Kind::Unauthorized => PingStatus::BadCredentials,
+                Kind::Forbidden | Kind::EntityDoesNotExists => PingStatus::WrongSlug,
+                _ => {
+                    if err
+                        .request_err()
+                        .map(|e| e.is_connect() || e.is_timeout())
+                        .unwrap_or(false)
+                    {
+                        PingStatus::NetworkDown
+                    } else {
+                        PingStatus::Failed(err)
+                    }
+                }
+            },
+        }
+    }
 
-        self.paged_response(resp).await
+    /// Updates entity with given id. Updated entity will be returned in case of success.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Invoice;
+    /// use tokio::prelude::*;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut invoice = Invoice::default();
+    ///     invoice.note = Some("Some note".to_string());
+    ///     let invoice = client.update(1234, invoice).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn update<T>(&self, id: i32, entity: T) -> Result<T, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        if let Some(cache) = self.cached_subjects::<T>() {
+            cache.remove(id);
+        }
+        self.send(
+            self.request(Method::PATCH, &self.url_with_id(T::url_part(), id))
+                .json(&entity),
+        )
+        .await?
+        .evaluate()
+        .await
     }
 
-    async fn error_response(response: Response) -> FakturoidError {
-        if let Err(e) = response.error_for_status_ref() {
-            if response.status() == 422 {
-                match response.json::<DataErrors>().await {
-                    Ok(data) => FakturoidError::from_data(data, e),
-                    Err(err) => FakturoidError::from_std_err(err),
-                }
-            } else {
-                e.into()
+    /// Fetches entity with given id, changes it by given closure and sends only fields which were
+    /// changed. Fields set to `None` by closure are sent as `null`, so they are cleared. Unlike
+    /// [`update()`](#method.update) with complete entity, fields changed meanwhile by someone
+    /// else are not overwritten. If closure changes nothing, no update is sent. Updated entity
+    /// is returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Invoice;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let invoice = client
+    ///         .update_fields(1234, |invoice: &mut Invoice| {
+    ///             invoice.note = Some("Some note".to_string());
+    ///             invoice.private_note = None;
+    ///         })
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn update_fields<T, F>(&self, id: i32, change: F) -> Result<T, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+        F: FnOnce(&mut T),
+    {
+        let mut entity = self.detail::<T>(id).await?;
+        let before = serde_json::to_value(&entity).map_err(FakturoidError::from_std_err)?;
+        change(&mut entity);
+        let after = serde_json::to_value(&entity).map_err(FakturoidError::from_std_err)?;
+        let patch = Self::changed_fields(&before, &after);
+        if patch.is_empty() {
+            return Ok(entity);
+        }
+        if let Some(cache) = self.cached_subjects::<T>() {
+            cache.remove(id);
+        }
+        self.send(
+            self.request(Method::PATCH, &self.url_with_id(T::url_part(), id))
+                .json(&patch),
+        )
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// Top level fields of `after` which differ from `before`, removed fields are `null`.
+    fn changed_fields(before: &Value, after: &Value) -> serde_json::Map<String, Value> {
+        let empty = serde_json::Map::new();
+        let before = before.as_object().unwrap_or(&empty);
+        let after = after.as_object().unwrap_or(&empty);
+        let mut changed: serde_json::Map<String, Value> = after
+            .iter()
+            .filter(|(key, value)| before.get(key.as_str()) != Some(value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        for key in before.keys() {
+            if !after.contains_key(key.as_str()) {
+                changed.insert(key.clone(), Value::Null);
             }
-        } else {
-            FakturoidError::from_std_err(UnknownError::new("evaluate_response<T>()"))
         }
+        changed
     }
 
-    async fn evaluate_response<T>(response: Response) -> Result<T, FakturoidError>
+    /// Deletes entity with given id.
+    pub async fn delete<T>(&self, id: i32) -> Result<(), FakturoidError>
     where
-        T: Entity + DeserializeOwned,
+        T: Entity,
     {
-        if response.status().is_success() {
-            Ok(response.json::<T>().await?)
-        } else {
-            Err(Self::error_response(response).await)
+        if let Some(cache) = self.cached_subjects::<T>() {
+            cache.remove(id);
         }
+        self.send(self.request(Method::DELETE, &self.url_with_id(T::url_part(), id)))
+            .await?
+            .check()
+            .await
     }
 
-    async fn evaluate(response: Response) -> Result<(), FakturoidError> {
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(Self::error_response(response).await)
-        }
+    /// Creates new entity. Only mandatory fields may be filled.New entity will be returned
+    /// in case of success.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Subject;
+    /// use tokio::prelude::*;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut subject = Subject::default();
+    ///     subject.name = Some("Some company".to_string());
+    ///     let subject = client.create(subject).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create<T>(&self, entity: T) -> Result<T, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        self.send(
+            self.request(
+                Method::POST,
+                &format!("{}{}.json", self.url_first(), T::url_part()),
+            )
+            .json(&entity),
+        )
+        .await?
+        .evaluate()
+        .await
     }
-    async fn detail_private<T>(&self, id: Option<i32>) -> Result<T, FakturoidError>
+
+    /// Creates all given entities, at most `concurrency` requests are sent at once. Requests
+    /// respect rate limit set by `FakturoidBuilder::rate_limit()` and retry policy. Result of
+    /// each entity is returned in the same order as entities were given, failure of one entity
+    /// doesn't stop creating others.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Subject;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+    ///         .rate_limit(200, 50)
+    ///         .build()?;
+    ///     let subjects = vec![
+    ///         Subject::builder().name("ACME s.r.o.").build()?,
+    ///         Subject::builder().name("Globex a.s.").build()?,
+    ///     ];
+    ///     for result in client.create_many(subjects, 4).await {
+    ///         match result {
+    ///             Ok(subject) => println!("created {:?}", subject.id),
+    ///             Err(err) => println!("failed: {}", err),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_many<T>(
+        &self,
+        entities: Vec<T>,
+        concurrency: usize,
+    ) -> Vec<Result<T, FakturoidError>>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        stream::iter(entities)
+            .map(|entity| self.create(entity))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// List of entities. If there is more than 20 entities first 20 will be returned as
+    /// PagedResponse object. Next pages will be accessible through methods of PagedResponse.
+    /// List can be filtered with optional given filter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Invoice;
+    /// use tokio::prelude::*;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let invoices = client.list::<Invoice>(None).await?;
+    ///     let note = invoices.data()[0].note.clone();
+    ///     let invoices = invoices.next_page().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn list<T>(&self, filter: Option<Filter>) -> Result<PagedResponse<T>, FakturoidError>
     where
         T: Entity + DeserializeOwned,
     {
-        let url = if let Some(id) = id {
-            self.url_with_id(T::url_part(), id)
+        let filter = if let Some(flt) = filter {
+            if !flt.is_empty() {
+                Some(T::filter_builder().build(flt))
+            } else {
+                None
+            }
         } else {
-            format!("{}{}.json", self.url_first(), T::url_part())
+            None
         };
-        Self::evaluate_response(
-            self.client
-                .get(&url)
-                .basic_auth(self.user.as_str(), Some(self.password.as_str()))
-                .header("User-Agent", self.user_agent())
-                .send()
-                .await?,
+        self.get_url(
+            format!("{}{}.json", self.url_first(), T::url_part()).as_str(),
+            filter,
         )
         .await
     }
 
-    /// Detail of entity with given id.
-    pub async fn detail<T>(&self, id: i32) -> Result<T, FakturoidError>
+    /// Lists entities matching typed filter, entity is given by type of filter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::filters::SubjectFilters;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let subjects = client
+    ///         .list_filtered(SubjectFilters::new().custom_id("CUST-1"))
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn list_filtered<F>(
+        &self,
+        filter: F,
+    ) -> Result<PagedResponse<F::Entity>, FakturoidError>
+    where
+        F: EntityFilter,
+        F::Entity: DeserializeOwned,
+    {
+        self.list::<F::Entity>(Some(filter.into())).await
+    }
+
+    /// List of entities at custom URL, e.g. nested endpoint not covered by this library. Path
+    /// is relative to URL of account (absolute URLs must point to base URL of client, others
+    /// fail with `ForeignUrl` error) and filter is built by filter builder of entity. Next pages
+    /// are accessible through methods of PagedResponse.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::entity;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// pub struct Note {
+    ///     pub id: i32,
+    ///     pub text: String,
+    /// }
+    ///
+    /// entity!(Note, "notes");
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let notes = client.list_url::<Note>("subjects/16/notes.json", None).await?;
+    ///     let notes = notes.next_page().await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn list_url<T>(
+        &self,
+        path: &str,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<T>, FakturoidError>
     where
         T: Entity + DeserializeOwned,
     {
-        self.detail_private(Some(id)).await
+        let filter = filter
+            .filter(|flt| !flt.is_empty())
+            .map(|flt| T::filter_builder().build(flt));
+        self.get_url(&self.url_of(path)?, filter).await
     }
 
-    /// Account details.
-    pub async fn account(&self) -> Result<Account, FakturoidError> {
-        self.detail_private(None).await
+    /// Activity feed of account, newest events first. Filter accepts `since` and `subject_id`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     for event in client.events(None).await?.iter() {
+    ///         println!("{:?}: {:?}", event.created_at, event.text);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn events(
+        &self,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<Event>, FakturoidError> {
+        self.list::<Event>(filter).await
     }
 
-    /// Updates entity with given id. Updated entity will be returned in case of success.
+    /// Activity feed containing only payments of invoices.
+    pub async fn events_paid(
+        &self,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<Event>, FakturoidError> {
+        self.get_url(
+            &format!("{}{}/paid.json", self.url_first(), Event::url_part()),
+            filter
+                .filter(|f| !f.is_empty())
+                .map(|f| Event::filter_builder().build(f)),
+        )
+        .await
+    }
+
+    /// Todo list of account, filter accepts `since`.
+    pub async fn todos(
+        &self,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<Todo>, FakturoidError> {
+        self.list::<Todo>(filter).await
+    }
+
+    /// Marks todo as completed or not completed again, updated todo is returned.
+    pub async fn toggle_todo_completion(&self, id: i32) -> Result<Todo, FakturoidError> {
+        self.send(self.request(
+            Method::POST,
+            &format!(
+                "{}{}/{}/toggle_completion.json",
+                self.url_first(),
+                Todo::url_part(),
+                id
+            ),
+        ))
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// List of all entities matching filter, all pages are downloaded. See
+    /// [`PagedResponse::collect_all()`](struct.PagedResponse.html#method.collect_all) for
+    /// meaning of `max_pages`.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// extern crate tokio;
-    /// use fakturoid::models::Invoice;
-    /// use tokio::prelude::*;
     /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::filters::InvoiceFilters;
+    /// use fakturoid::models::{Invoice, InvoiceState};
     ///
+    /// # extern crate tokio;
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
-    ///     let mut invoice = Invoice::default();
-    ///     invoice.note = Some("Some note".to_string());
-    ///     let invoice = client.update(1234, invoice).await?;
+    ///     let filter = InvoiceFilters::new().status(InvoiceState::Overdue);
+    ///     let overdue = client
+    ///         .list_all::<Invoice>(Some(filter.into()), Some(50))
+    ///         .await?;
+    ///     println!("{} overdue invoices", overdue.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn list_all<T>(
+        &self,
+        filter: Option<Filter>,
+        max_pages: Option<usize>,
+    ) -> Result<Vec<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        self.list::<T>(filter).await?.collect_all(max_pages).await
+    }
+
+    /// Lists only recurring generators or only templates, use `list::<Generator>()` for all
+    /// generators. Filter accepts `since`, `updated_since` and `subject_id`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::GeneratorKind;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let generators = client
+    ///         .list_generators(GeneratorKind::Recurring, None)
+    ///         .await?;
+    ///     for generator in generators.iter() {
+    ///         println!("{:?} {:?}", generator.name, generator.next_occurrence_on);
+    ///     }
     ///     Ok(())
     /// }
     /// ```
-    pub async fn update<T>(&self, id: i32, entity: T) -> Result<T, FakturoidError>
-    where
-        T: Entity + Serialize + DeserializeOwned,
-    {
-        Self::evaluate_response(
-            self.client
-                .patch(&self.url_with_id(T::url_part(), id))
-                .basic_auth(self.user.as_str(), Some(self.password.as_str()))
-                .header("User-Agent", self.user_agent())
-                .json(&entity)
-                .send()
-                .await?,
+    pub async fn list_generators(
+        &self,
+        kind: GeneratorKind,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<Generator>, FakturoidError> {
+        let filter = filter
+            .filter(|flt| !flt.is_empty())
+            .map(|flt| Generator::filter_builder().build(flt));
+        self.get_url(
+            &format!(
+                "{}{}/{}.json",
+                self.url_first(),
+                Generator::url_part(),
+                kind
+            ),
+            filter,
         )
         .await
     }
 
-    /// Deletes entity with given id.
-    pub async fn delete<T>(&self, id: i32) -> Result<(), FakturoidError>
+    /// Downloads page of entities without parsing it, items can be parsed into lightweight views
+    /// borrowing from body, see [`views`](../views/index.html) module. Available with
+    /// `performance` feature.
+    #[cfg(feature = "performance")]
+    pub async fn list_raw<T>(&self, filter: Option<Filter>) -> Result<RawPage, FakturoidError>
     where
         T: Entity,
     {
-        Self::evaluate(
-            self.client
-                .delete(&self.url_with_id(T::url_part(), id))
-                .basic_auth(self.user.as_str(), Some(self.password.as_str()))
-                .header("User-Agent", self.user_agent())
-                .send()
-                .await?,
+        self.get_raw(
+            &format!("{}{}.json", self.url_first(), T::url_part()),
+            filter
+                .filter(|f| !f.is_empty())
+                .map(|f| T::filter_builder().build(f)),
         )
         .await
     }
 
-    /// Creates new entity. Only mandatory fields may be filled.New entity will be returned
-    /// in case of success.
+    /// Stream of all entities matching filter. Pages are downloaded one after another and their
+    /// items are deserialized while the body is being received, so only few items are held in
+    /// memory at a time. Suitable for exports of tens of thousands of records. Stream ends after
+    /// first error.
     ///
     /// # Example
     ///
     /// ```no_run
     /// extern crate tokio;
-    /// use fakturoid::models::Subject;
-    /// use tokio::prelude::*;
+    /// use fakturoid::models::Invoice;
     /// use fakturoid::client::Fakturoid;
+    /// use futures::TryStreamExt;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
-    ///     let mut subject = Subject::default();
-    ///     subject.name = Some("Some company".to_string());
-    ///     let subject = client.create(subject).await?;
+    ///     let mut invoices = Box::pin(client.list_streamed::<Invoice>(None));
+    ///     while let Some(invoice) = invoices.try_next().await? {
+    ///         println!("{}", invoice);
+    ///     }
     ///     Ok(())
     /// }
     /// ```
-    pub async fn create<T>(&self, entity: T) -> Result<T, FakturoidError>
+    pub fn list_streamed<T>(
+        &self,
+        filter: Option<Filter>,
+    ) -> impl Stream<Item = Result<T, FakturoidError>>
     where
-        T: Entity + Serialize + DeserializeOwned,
+        T: Entity + DeserializeOwned,
     {
-        Self::evaluate_response(
-            self.client
-                .post(&format!("{}{}.json", self.url_first(), T::url_part()))
-                .basic_auth(self.user.as_str(), Some(self.password.as_str()))
-                .header("User-Agent", self.user_agent())
-                .json(&entity)
-                .send()
-                .await?,
-        )
-        .await
+        let mut request = self.request(
+            Method::GET,
+            &format!("{}{}.json", self.url_first(), T::url_part()),
+        );
+        if let Some(flt) = filter.filter(|f| !f.is_empty()) {
+            request = request.query(&T::filter_builder().build(flt));
+        }
+        let state = ItemStream {
+            client: self.clone(),
+            next: Some(request),
+            response: None,
+            parser: ArrayParser::new(),
+            items: VecDeque::new(),
+            numbered_page: None,
+            page_items: 0,
+        };
+        stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+            loop {
+                if let Some(item) = state.items.pop_front() {
+                    return Some((Ok(item), Some(state)));
+                }
+                match state.receive().await {
+                    Ok(true) => continue,
+                    Ok(false) => return None,
+                    Err(err) => return Some((Err(err), None)),
+                }
+            }
+        })
     }
 
-    /// List of entities. If there is more than 20 entities first 20 will be returned as
+    /// Fulltext search in entities. If there is more than 20 entities first 20 will be returned as
     /// PagedResponse object. Next pages will be accessible through methods of PagedResponse.
-    /// List can be filtered with optional given filter.
+    /// Search can be narrowed by filter, e.g. to paid invoices, and filter can select page of
+    /// results.
     ///
     /// # Example
     ///
     /// ```no_run
     /// extern crate tokio;
-    /// use fakturoid::models::Invoice;
+    /// use fakturoid::models::{Invoice, InvoiceState};
+    /// use fakturoid::filters::InvoiceFilters;
     /// use tokio::prelude::*;
     /// use fakturoid::client::Fakturoid;
     ///
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
-    ///     let invoices = client.list::<Invoice>(None).await?;
+    ///     let invoices = client.fulltext::<Invoice>("some hard work", None).await?;
     ///     let note = invoices.data()[0].note.clone();
     ///     let invoices = invoices.next_page().await?;
+    ///
+    ///     let filter = InvoiceFilters::new().status(InvoiceState::Paid).page(3);
+    ///     let paid = client.fulltext::<Invoice>("ACME", Some(filter.into())).await?;
     ///     Ok(())
     /// }
     /// ```
-    pub async fn list<T>(&self, filter: Option<Filter>) -> Result<PagedResponse<T>, FakturoidError>
+    pub async fn fulltext<T>(
+        &self,
+        search: &str,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<T>, FakturoidError>
     where
         T: Entity + DeserializeOwned,
     {
-        let filter = if let Some(flt) = filter {
-            if !flt.is_empty() {
-                Some(T::filter_builder().build(flt))
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-        self.get_url(
-            format!("{}{}.json", self.url_first(), T::url_part()).as_str(),
-            filter,
-        )
-        .await
+        self.search::<T>(search, filter, &[]).await
     }
 
-    /// Fulltext search in entities. If there is more than 20 entities first 20 will be returned as
-    /// PagedResponse object. Next pages will be accessible through methods of PagedResponse.
+    /// Fulltext search in entities which have all given tags, e.g. customers tagged `vip`
+    /// matching `novak`. Both conditions are evaluated by API. Results are paged the same way as
+    /// in [`fulltext()`](#method.fulltext).
     ///
     /// # Example
     ///
     /// ```no_run
-    /// extern crate tokio;
-    /// use fakturoid::models::Invoice;
-    /// use tokio::prelude::*;
     /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Subject;
     ///
+    /// # extern crate tokio;
     /// #[tokio::main]
     /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
     ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
-    ///     let invoices = client.fulltext::<Invoice>("some hard work").await?;
-    ///     let note = invoices.data()[0].note.clone();
-    ///     let invoices = invoices.next_page().await?;
+    ///     let subjects = client.fulltext_tagged::<Subject>("novak", &["vip"]).await?;
     ///     Ok(())
     /// }
     /// ```
-    pub async fn fulltext<T>(&self, search: &str) -> Result<PagedResponse<T>, FakturoidError>
+    pub async fn fulltext_tagged<T>(
+        &self,
+        search: &str,
+        tags: &[&str],
+    ) -> Result<PagedResponse<T>, FakturoidError>
     where
         T: Entity + DeserializeOwned,
     {
-        let query_map: HashMap<String, String> = [("query".to_string(), search.to_string())]
-            .iter()
-            .map(|q| (q.0.clone(), q.1.clone()))
-            .collect();
+        self.search::<T>(search, None, tags).await
+    }
+
+    async fn search<T>(
+        &self,
+        search: &str,
+        filter: Option<Filter>,
+        tags: &[&str],
+    ) -> Result<PagedResponse<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned,
+    {
+        let mut query_map = filter
+            .filter(|f| !f.is_empty())
+            .map(|f| T::filter_builder().build(f))
+            .unwrap_or_default();
+        query_map.insert("query".to_string(), search.to_string());
+        if !tags.is_empty() {
+            query_map.insert("tags".to_string(), tags.join(","));
+        }
         self.get_url(
             format!("{}{}/search.json", self.url_first(), T::url_part()).as_str(),
             Some(query_map),
@@ -466,28 +2726,475 @@ impl Fakturoid {
     }
 
     /// Fires action on entity with given id.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::{ApiVersion, Fakturoid};
+    /// use fakturoid::models::{GeneratorAction, GeneratorActivateData};
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::oauth_builder("client_id", "client_secret", "slug")
+    ///         .api_version(ApiVersion::V3)
+    ///         .build()?;
+    ///     client
+    ///         .action(15, GeneratorAction::Deactivate, None::<GeneratorActivateData>)
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
     pub async fn action<T: Action, D: Serialize>(
         &self,
         id: i32,
         action: T,
         data: Option<D>,
     ) -> Result<(), FakturoidError> {
+        self.fire(id, action, data).await?.check().await
+    }
+
+    /// Fires action on entity with given id and returns updated entity, e.g. paid invoice with
+    /// new status. Entity is parsed from response of action if API returns it, otherwise it is
+    /// downloaded by another request.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::{InvoiceAction, InvoicePayData};
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let invoice = client
+    ///         .action_updated(27, InvoiceAction::Pay, Some(InvoicePayData::default()))
+    ///         .await?;
+    ///     println!("{:?} {:?}", invoice.status, invoice.paid_at);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn action_updated<T: EntityAction, D: Serialize>(
+        &self,
+        id: i32,
+        action: T,
+        data: Option<D>,
+    ) -> Result<T::Entity, FakturoidError> {
+        let body = self.fire(id, action, data).await?.bytes().await?;
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Object(map)) if !map.is_empty() => {
+                return serde_json::from_value(Value::Object(map))
+                    .map_err(FakturoidError::from_std_err);
+            }
+            _ => {}
+        }
+        self.send(self.request(Method::GET, &self.url_with_id(T::url_part(), id)))
+            .await?
+            .evaluate()
+            .await
+    }
+
+    async fn fire<T: Action, D: Serialize>(
+        &self,
+        id: i32,
+        action: T,
+        data: Option<D>,
+    ) -> Result<Sent, FakturoidError> {
         let req = self
-            .client
-            .post(&format!(
-                "{}{}/{}/fire.json",
-                self.url_first(),
-                T::url_part(),
-                id
-            ))
-            .basic_auth(self.user.as_str(), Some(self.password.as_str()))
-            .header("User-Agent", self.user_agent())
+            .request(
+                action.method(),
+                &format!(
+                    "{}{}/{}/{}.json",
+                    self.url_first(),
+                    T::url_part(),
+                    id,
+                    action.endpoint()
+                ),
+            )
             .query(&action.query());
         let req = if let Some(d) = data {
             req.query(&d)
         } else {
             req
         };
-        Self::evaluate(req.send().await?).await
+        self.send(req).await
+    }
+
+    /// Sends request to endpoint not covered by this library and parses JSON response, so new
+    /// endpoints of API can be used before they are added. Path is relative to URL of account
+    /// (absolute URLs must point to base URL of client, others fail with `ForeignUrl` error) and
+    /// may contain query string, optional data are sent as JSON body. Request is authorized, rate limited and retried like other requests of
+    /// client and API errors are returned as `FakturoidError`. Empty response is parsed as
+    /// JSON `null`, so `()` can be used as type of result.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use reqwest::Method;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize)]
+    /// struct NewNote {
+    ///     text: String,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Note {
+    ///     id: i32,
+    ///     text: String,
+    /// }
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let note: Note = client
+    ///         .raw_request(
+    ///             Method::POST,
+    ///             "subjects/16/notes.json",
+    ///             Some(NewNote { text: "Call back".to_string() }),
+    ///         )
+    ///         .await?;
+    ///     let path = format!("subjects/16/notes/{}.json", note.id);
+    ///     client
+    ///         .raw_request::<(), ()>(Method::DELETE, &path, None)
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn raw_request<T, D>(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<D>,
+    ) -> Result<T, FakturoidError>
+    where
+        T: DeserializeOwned,
+        D: Serialize,
+    {
+        let body = self.raw_send(method, path, data).await?.bytes().await?;
+        let body: &[u8] = if body.iter().all(u8::is_ascii_whitespace) {
+            b"null"
+        } else {
+            &body
+        };
+        serde_json::from_slice(body).map_err(FakturoidError::from_std_err)
+    }
+
+    /// Sends request like `raw_request()` and returns response without evaluating it, e.g. for
+    /// endpoints returning files. Response can be evaluated by `evaluate_response()` or
+    /// `evaluate()`. `shutdown()` doesn't wait for body of returned response.
+    pub async fn raw_response<D: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<D>,
+    ) -> Result<Response, FakturoidError> {
+        Ok(self.raw_send(method, path, data).await?.response)
+    }
+
+    async fn raw_send<D: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        data: Option<D>,
+    ) -> Result<Sent, FakturoidError> {
+        let request = self.request(method, &self.url_of(path)?);
+        let request = if let Some(d) = data {
+            request.json(&d)
+        } else {
+            request
+        };
+        self.send(request).await
+    }
+
+    /// URL of path relative to URL of account. Absolute URLs are returned as they are if they
+    /// point to base URL of client, otherwise they are rejected, so credentials are not sent to
+    /// other hosts.
+    fn url_of(&self, path: &str) -> Result<String, FakturoidError> {
+        if path.starts_with(self.base_url.as_str()) {
+            Ok(path.to_string())
+        } else if path.contains("://") {
+            Err(FakturoidError::from_std_err(ForeignUrl::new(path)))
+        } else {
+            Ok(format!(
+                "{}{}",
+                self.url_first(),
+                path.trim_start_matches('/')
+            ))
+        }
+    }
+
+    /// Absolute URL received from API, it is rejected unless it points to base URL of client.
+    fn own_url<'a>(&self, url: &'a str) -> Result<&'a str, FakturoidError> {
+        if url.starts_with(self.base_url.as_str()) {
+            Ok(url)
+        } else {
+            Err(FakturoidError::from_std_err(ForeignUrl::new(url)))
+        }
+    }
+
+    fn payments_url(&self, invoice_id: i32) -> String {
+        format!(
+            "{}{}/{}/payments",
+            self.url_first(),
+            Invoice::url_part(),
+            invoice_id
+        )
+    }
+
+    /// Records payment of invoice with given id, created payment is returned. Available in API
+    /// v3, see `FakturoidBuilder::api_version()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::InvoicePayment;
+    /// use rust_decimal::Decimal;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::oauth_builder("client_id", "client_secret", "slug").build()?;
+    ///     let payment = client
+    ///         .create_payment(27, InvoicePayment::new(Decimal::new(50000, 2)))
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_payment(
+        &self,
+        invoice_id: i32,
+        payment: InvoicePayment,
+    ) -> Result<InvoicePayment, FakturoidError> {
+        self.send(
+            self.request(
+                Method::POST,
+                &format!("{}.json", self.payments_url(invoice_id)),
+            )
+            .json(&payment),
+        )
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// Removes payment from invoice. Available in API v3.
+    pub async fn delete_payment(
+        &self,
+        invoice_id: i32,
+        payment_id: i32,
+    ) -> Result<(), FakturoidError> {
+        self.send(self.request(
+            Method::DELETE,
+            &format!("{}/{}.json", self.payments_url(invoice_id), payment_id),
+        ))
+        .await?
+        .check()
+        .await
+    }
+
+    /// Payments of invoice, they are part of invoice detail. Available in API v3.
+    pub async fn list_payments(
+        &self,
+        invoice_id: i32,
+    ) -> Result<Vec<InvoicePayment>, FakturoidError> {
+        Ok(self
+            .detail::<Invoice>(invoice_id)
+            .await?
+            .payments
+            .unwrap_or_default())
+    }
+
+    fn expense_payments_url(&self, expense_id: i32) -> String {
+        format!(
+            "{}{}/{}/payments",
+            self.url_first(),
+            Expense::url_part(),
+            expense_id
+        )
+    }
+
+    /// Records payment of expense with given id, created payment is returned. Available in API
+    /// v3, see `FakturoidBuilder::api_version()`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::ExpensePayment;
+    /// use rust_decimal::Decimal;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::oauth_builder("client_id", "client_secret", "slug").build()?;
+    ///     let payment = client
+    ///         .create_expense_payment(9, ExpensePayment::new(Decimal::new(120000, 2)))
+    ///         .await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_expense_payment(
+        &self,
+        expense_id: i32,
+        payment: ExpensePayment,
+    ) -> Result<ExpensePayment, FakturoidError> {
+        self.send(
+            self.request(
+                Method::POST,
+                &format!("{}.json", self.expense_payments_url(expense_id)),
+            )
+            .json(&payment),
+        )
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// Removes payment from expense. Available in API v3.
+    pub async fn delete_expense_payment(
+        &self,
+        expense_id: i32,
+        payment_id: i32,
+    ) -> Result<(), FakturoidError> {
+        self.send(self.request(
+            Method::DELETE,
+            &format!(
+                "{}/{}.json",
+                self.expense_payments_url(expense_id),
+                payment_id
+            ),
+        ))
+        .await?
+        .check()
+        .await
+    }
+
+    /// Payments of expense, they are part of expense detail. Available in API v3.
+    pub async fn list_expense_payments(
+        &self,
+        expense_id: i32,
+    ) -> Result<Vec<ExpensePayment>, FakturoidError> {
+        Ok(self
+            .detail::<Expense>(expense_id)
+            .await?
+            .payments
+            .unwrap_or_default())
+    }
+
+    fn inventory_moves_url(&self, item_id: i32) -> String {
+        format!(
+            "{}{}/{}/{}",
+            self.url_first(),
+            InventoryItem::url_part(),
+            item_id,
+            "inventory_moves"
+        )
+    }
+
+    /// Moves of inventory item, they are nested under item, so `InventoryMove` isn't an
+    /// `Entity`. Period filters (`since`, `until`, `updated_since`, `updated_until`) are passed
+    /// to API.
+    pub async fn list_inventory_moves(
+        &self,
+        item_id: i32,
+        filter: Option<Filter>,
+    ) -> Result<PagedResponse<InventoryMove>, FakturoidError> {
+        let filter = filter
+            .filter(|flt| !flt.is_empty())
+            .map(|flt| InventoryMoveFilter.build(flt));
+        self.get_url(
+            &format!("{}.json", self.inventory_moves_url(item_id)),
+            filter,
+        )
+        .await
+    }
+
+    /// Moves given quantity of inventory item into or out of stock, created move is returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::{InventoryDirection, InventoryMove};
+    /// use rust_decimal::Decimal;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::oauth_builder("client_id", "client_secret", "slug").build()?;
+    ///     let received = InventoryMove::new(InventoryDirection::In, Decimal::from(25));
+    ///     let received = client.create_inventory_move(12, received).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_inventory_move(
+        &self,
+        item_id: i32,
+        inventory_move: InventoryMove,
+    ) -> Result<InventoryMove, FakturoidError> {
+        self.send(
+            self.request(
+                Method::POST,
+                &format!("{}.json", self.inventory_moves_url(item_id)),
+            )
+            .json(&inventory_move),
+        )
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// Detail of inventory move.
+    pub async fn inventory_move(
+        &self,
+        item_id: i32,
+        move_id: i32,
+    ) -> Result<InventoryMove, FakturoidError> {
+        self.send(self.request(
+            Method::GET,
+            &format!("{}/{}.json", self.inventory_moves_url(item_id), move_id),
+        ))
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// Updates inventory move, updated move is returned.
+    pub async fn update_inventory_move(
+        &self,
+        item_id: i32,
+        move_id: i32,
+        inventory_move: InventoryMove,
+    ) -> Result<InventoryMove, FakturoidError> {
+        self.send(
+            self.request(
+                Method::PATCH,
+                &format!("{}/{}.json", self.inventory_moves_url(item_id), move_id),
+            )
+            .json(&inventory_move),
+        )
+        .await?
+        .evaluate()
+        .await
+    }
+
+    /// Deletes inventory move, quantity of item is recomputed by API.
+    pub async fn delete_inventory_move(
+        &self,
+        item_id: i32,
+        move_id: i32,
+    ) -> Result<(), FakturoidError> {
+        self.send(self.request(
+            Method::DELETE,
+            &format!("{}/{}.json", self.inventory_moves_url(item_id), move_id),
+        ))
+        .await?
+        .check()
+        .await
     }
 }
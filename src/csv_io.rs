@@ -0,0 +1,386 @@
+//! Export and import of subjects and invoices in CSV format, so records can be moved between
+//! fakturoid.cz and other invoicing tools or spreadsheets. Available with `csv` feature.
+//!
+//! Files have header row, columns are matched by name on import and their order doesn't
+//! matter. Missing or empty columns are `None`. Dates are in `YYYY-MM-DD` format and tags are
+//! separated by semicolon.
+//!
+//! Subjects have one row each. Invoices are flattened into one row per line: columns of invoice
+//! are repeated on every row and line is described by `line_name`, `line_quantity`,
+//! `line_unit_name`, `line_unit_price` and `line_vat_rate` columns (invoice without lines has
+//! one row with empty line columns). On import, rows which follow each other and have the same
+//! `custom_id` (or `number` if `custom_id` is empty) are lines of one invoice.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::csv_io::CsvRecords;
+//! use fakturoid::models::Invoice;
+//! use futures::TryStreamExt;
+//! use std::fs::File;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let invoices: Vec<Invoice> = client
+//!         .list::<Invoice>(None)
+//!         .await?
+//!         .into_stream()
+//!         .try_collect()
+//!         .await?;
+//!     invoices.to_csv(File::create("invoices.csv")?)?;
+//!
+//!     let imported = Vec::<Invoice>::from_csv(File::open("invoices.csv")?)?;
+//!     assert_eq!(imported.len(), invoices.len());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::currency::Currency;
+use crate::dates::{self, Date};
+use crate::error::FakturoidError;
+use crate::models::{Invoice, InvoiceLanguage, InvoiceLine, PaymentMethod, Subject, SubjectType};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// Records which can be written to and read from CSV file, see [module](index.html)
+/// documentation for columns.
+pub trait CsvRecords: Sized {
+    /// Writes records with header row.
+    fn to_csv<W: Write>(&self, writer: W) -> Result<(), FakturoidError>;
+
+    /// Reads records, error contains line of first invalid row.
+    fn from_csv<R: Read>(reader: R) -> Result<Self, FakturoidError>;
+}
+
+impl CsvRecords for Vec<Subject> {
+    fn to_csv<W: Write>(&self, writer: W) -> Result<(), FakturoidError> {
+        write_rows(writer, self.iter().map(SubjectRow::from))
+    }
+
+    fn from_csv<R: Read>(reader: R) -> Result<Self, FakturoidError> {
+        Ok(read_rows::<SubjectRow, R>(reader)?
+            .into_iter()
+            .map(SubjectRow::into_subject)
+            .collect())
+    }
+}
+
+impl CsvRecords for Vec<Invoice> {
+    fn to_csv<W: Write>(&self, writer: W) -> Result<(), FakturoidError> {
+        write_rows(writer, self.iter().flat_map(InvoiceRow::from_invoice))
+    }
+
+    fn from_csv<R: Read>(reader: R) -> Result<Self, FakturoidError> {
+        let mut invoices: Vec<(String, Invoice)> = Vec::new();
+        for row in read_rows::<InvoiceRow, R>(reader)? {
+            let key = row.key();
+            let line = row.line();
+            match invoices.last_mut() {
+                Some((last_key, invoice)) if !key.is_empty() && *last_key == key => {
+                    invoice.lines_mut().extend(line);
+                }
+                _ => {
+                    let mut invoice = row.into_invoice();
+                    invoice.lines = line.map(|line| vec![line]);
+                    invoices.push((key, invoice));
+                }
+            }
+        }
+        Ok(invoices.into_iter().map(|(_, invoice)| invoice).collect())
+    }
+}
+
+fn write_rows<W, T, I>(writer: W, rows: I) -> Result<(), FakturoidError>
+where
+    W: Write,
+    T: Serialize,
+    I: Iterator<Item = T>,
+{
+    let mut writer = csv::Writer::from_writer(writer);
+    for row in rows {
+        writer
+            .serialize(row)
+            .map_err(FakturoidError::from_std_err)?;
+    }
+    writer.flush().map_err(FakturoidError::from_std_err)
+}
+
+fn read_rows<T: DeserializeOwned, R: Read>(reader: R) -> Result<Vec<T>, FakturoidError> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+        .deserialize::<T>()
+        .map(|row| row.map_err(FakturoidError::from_std_err))
+        .collect()
+}
+
+fn join_tags(tags: &Option<Vec<String>>) -> Option<String> {
+    tags.as_ref()
+        .filter(|tags| !tags.is_empty())
+        .map(|tags| tags.join(";"))
+}
+
+fn split_tags(tags: Option<String>) -> Option<Vec<String>> {
+    tags.map(|tags| {
+        tags.split(';')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect()
+    })
+}
+
+/// One row of subjects file.
+#[derive(Debug, Serialize, Deserialize)]
+struct SubjectRow {
+    #[serde(default)]
+    id: Option<i32>,
+    #[serde(default)]
+    custom_id: Option<String>,
+    #[serde(rename = "type", default)]
+    sub_type: Option<SubjectType>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    full_name: Option<String>,
+    #[serde(default)]
+    street: Option<String>,
+    #[serde(default)]
+    street2: Option<String>,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    zip: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    registration_no: Option<String>,
+    #[serde(default)]
+    vat_no: Option<String>,
+    #[serde(default)]
+    local_vat_no: Option<String>,
+    #[serde(default)]
+    bank_account: Option<String>,
+    #[serde(default)]
+    iban: Option<String>,
+    #[serde(default)]
+    variable_symbol: Option<String>,
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(default)]
+    email_copy: Option<String>,
+    #[serde(default)]
+    phone: Option<String>,
+    #[serde(default)]
+    web: Option<String>,
+    #[serde(default)]
+    private_note: Option<String>,
+}
+
+impl From<&Subject> for SubjectRow {
+    fn from(subject: &Subject) -> Self {
+        Self {
+            id: subject.id,
+            custom_id: subject.custom_id.clone(),
+            sub_type: subject.sub_type.clone(),
+            name: subject.name.clone(),
+            full_name: subject.full_name.clone(),
+            street: subject.street.clone(),
+            street2: subject.street2.clone(),
+            city: subject.city.clone(),
+            zip: subject.zip.clone(),
+            country: subject.country.clone(),
+            registration_no: subject.registration_no.clone(),
+            vat_no: subject.vat_no.clone(),
+            local_vat_no: subject.local_vat_no.clone(),
+            bank_account: subject.bank_account.clone(),
+            iban: subject.iban.clone(),
+            variable_symbol: subject.variable_symbol.clone(),
+            email: subject.email.clone(),
+            email_copy: subject.email_copy.clone(),
+            phone: subject.phone.clone(),
+            web: subject.web.clone(),
+            private_note: subject.private_note.clone(),
+        }
+    }
+}
+
+impl SubjectRow {
+    fn into_subject(self) -> Subject {
+        Subject {
+            id: self.id,
+            custom_id: self.custom_id,
+            sub_type: self.sub_type,
+            name: self.name,
+            full_name: self.full_name,
+            street: self.street,
+            street2: self.street2,
+            city: self.city,
+            zip: self.zip,
+            country: self.country,
+            registration_no: self.registration_no,
+            vat_no: self.vat_no,
+            local_vat_no: self.local_vat_no,
+            bank_account: self.bank_account,
+            iban: self.iban,
+            variable_symbol: self.variable_symbol,
+            email: self.email,
+            email_copy: self.email_copy,
+            phone: self.phone,
+            web: self.web,
+            private_note: self.private_note,
+            ..Subject::default()
+        }
+    }
+}
+
+/// One row of invoices file, invoice with one of its lines.
+#[derive(Debug, Serialize, Deserialize)]
+struct InvoiceRow {
+    #[serde(default)]
+    id: Option<i32>,
+    #[serde(default)]
+    custom_id: Option<String>,
+    #[serde(default)]
+    number: Option<String>,
+    #[serde(default)]
+    subject_id: Option<i32>,
+    #[serde(default)]
+    subject_custom_id: Option<String>,
+    #[serde(default)]
+    client_name: Option<String>,
+    #[serde(default)]
+    client_registration_no: Option<String>,
+    #[serde(default)]
+    client_vat_no: Option<String>,
+    #[serde(default)]
+    order_number: Option<String>,
+    #[serde(default)]
+    variable_symbol: Option<String>,
+    #[serde(default, with = "dates::option_date")]
+    issued_on: Option<Date>,
+    #[serde(default, with = "dates::option_date")]
+    taxable_fulfillment_due: Option<Date>,
+    #[serde(default)]
+    due: Option<i32>,
+    #[serde(default, with = "dates::option_date")]
+    due_on: Option<Date>,
+    #[serde(default)]
+    payment_method: Option<PaymentMethod>,
+    #[serde(default)]
+    currency: Option<Currency>,
+    #[serde(default)]
+    exchange_rate: Option<Decimal>,
+    #[serde(default)]
+    language: Option<InvoiceLanguage>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    private_note: Option<String>,
+    #[serde(default)]
+    tags: Option<String>,
+    #[serde(default)]
+    total: Option<Decimal>,
+    #[serde(default)]
+    line_name: Option<String>,
+    #[serde(default)]
+    line_quantity: Option<Decimal>,
+    #[serde(default)]
+    line_unit_name: Option<String>,
+    #[serde(default)]
+    line_unit_price: Option<Decimal>,
+    #[serde(default)]
+    line_vat_rate: Option<Decimal>,
+}
+
+impl InvoiceRow {
+    /// Rows of invoice, one for each line.
+    fn from_invoice(invoice: &Invoice) -> Vec<Self> {
+        let row = |line: Option<&InvoiceLine>| Self {
+            id: invoice.id,
+            custom_id: invoice.custom_id.clone(),
+            number: invoice.number.clone(),
+            subject_id: invoice.subject_id,
+            subject_custom_id: invoice.subject_custom_id.clone(),
+            client_name: invoice.client_name.clone(),
+            client_registration_no: invoice.client_registration_no.clone(),
+            client_vat_no: invoice.client_vat_no.clone(),
+            order_number: invoice.order_number.clone(),
+            variable_symbol: invoice.variable_symbol.clone(),
+            issued_on: invoice.issued_on,
+            taxable_fulfillment_due: invoice.taxable_fulfillment_due,
+            due: invoice.due,
+            due_on: invoice.due_on,
+            payment_method: invoice.payment_method.clone(),
+            currency: invoice.currency,
+            exchange_rate: invoice.exchange_rate,
+            language: invoice.language,
+            note: invoice.note.clone(),
+            private_note: invoice.private_note.clone(),
+            tags: join_tags(&invoice.tags),
+            total: invoice.total,
+            line_name: line.map(|l| l.name.clone()),
+            line_quantity: line.map(|l| l.quantity),
+            line_unit_name: line.and_then(|l| l.unit_name.clone()),
+            line_unit_price: line.map(|l| l.unit_price),
+            line_vat_rate: line.map(|l| l.vat_rate),
+        };
+        match invoice.lines.as_ref().filter(|lines| !lines.is_empty()) {
+            Some(lines) => lines.iter().map(|line| row(Some(line))).collect(),
+            None => vec![row(None)],
+        }
+    }
+
+    /// Key which joins rows of one invoice.
+    fn key(&self) -> String {
+        self.custom_id
+            .as_deref()
+            .filter(|id| !id.is_empty())
+            .or(self.number.as_deref())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Line of row, `None` if row has no line name.
+    fn line(&self) -> Option<InvoiceLine> {
+        let name = self.line_name.as_deref().filter(|name| !name.is_empty())?;
+        Some(InvoiceLine::new(
+            name,
+            self.line_quantity.unwrap_or(Decimal::ONE),
+            self.line_unit_name.as_deref(),
+            self.line_unit_price.unwrap_or_default(),
+            self.line_vat_rate.unwrap_or_default(),
+        ))
+    }
+
+    fn into_invoice(self) -> Invoice {
+        let mut invoice = Invoice::default();
+        invoice.id = self.id;
+        invoice.custom_id = self.custom_id;
+        invoice.number = self.number;
+        invoice.subject_id = self.subject_id;
+        invoice.subject_custom_id = self.subject_custom_id;
+        invoice.client_name = self.client_name;
+        invoice.client_registration_no = self.client_registration_no;
+        invoice.client_vat_no = self.client_vat_no;
+        invoice.order_number = self.order_number;
+        invoice.variable_symbol = self.variable_symbol;
+        invoice.issued_on = self.issued_on;
+        invoice.taxable_fulfillment_due = self.taxable_fulfillment_due;
+        invoice.due = self.due;
+        invoice.due_on = self.due_on;
+        invoice.payment_method = self.payment_method;
+        invoice.currency = self.currency;
+        invoice.exchange_rate = self.exchange_rate;
+        invoice.language = self.language;
+        invoice.note = self.note;
+        invoice.private_note = self.private_note;
+        invoice.tags = split_tags(self.tags);
+        invoice.total = self.total;
+        invoice
+    }
+}
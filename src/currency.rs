@@ -0,0 +1,199 @@
+//! Currency of documents, payments and accounts and amounts of money in it.
+//!
+//! [`Currency`](struct.Currency.html) is ISO 4217 code of three ASCII letters. Codes are
+//! normalized to upper case and malformed codes are rejected when parsed, so invalid currency
+//! is caught before it is sent to API.
+//!
+//! [`Money`](struct.Money.html) is amount together with its currency. Amounts in different
+//! currencies are never added together, `checked_add()` and `checked_sub()` return `None` for
+//! them. Invoices and expenses provide their amounts as money, e.g. `Invoice::total_money()`.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::currency::{Currency, Money};
+//! use fakturoid::models::Invoice;
+//! use rust_decimal::Decimal;
+//!
+//! let mut invoice = Invoice::default();
+//! invoice.currency = Some("eur".parse().unwrap());
+//! assert_eq!(invoice.currency, Some(Currency::EUR));
+//! assert!("euro".parse::<Currency>().is_err());
+//!
+//! let price = Money::new(Decimal::new(1250, 1), Currency::EUR);
+//! let total = price.checked_add(&(price * Decimal::from(2))).unwrap();
+//! assert_eq!(total.to_string(), "375.00 EUR");
+//! assert_eq!(total.checked_add(&Money::zero(Currency::CZK)), None);
+//! ```
+
+use crate::error::InvalidCurrency;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Formatter;
+use std::ops::{Mul, Neg};
+use std::str::FromStr;
+
+/// ISO 4217 code of currency.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const CZK: Currency = Currency(*b"CZK");
+    pub const EUR: Currency = Currency(*b"EUR");
+    pub const USD: Currency = Currency(*b"USD");
+    pub const GBP: Currency = Currency(*b"GBP");
+    pub const PLN: Currency = Currency(*b"PLN");
+    pub const HUF: Currency = Currency(*b"HUF");
+    pub const CHF: Currency = Currency(*b"CHF");
+
+    /// Currency with given code, white spaces around code are ignored and code is converted to
+    /// upper case.
+    pub fn new(code: &str) -> Result<Self, InvalidCurrency> {
+        let trimmed = code.trim();
+        match trimmed.as_bytes() {
+            [a, b, c] if trimmed.bytes().all(|b| b.is_ascii_alphabetic()) => Ok(Currency([
+                a.to_ascii_uppercase(),
+                b.to_ascii_uppercase(),
+                c.to_ascii_uppercase(),
+            ])),
+            _ => Err(InvalidCurrency::new(code)),
+        }
+    }
+
+    /// Three letter code in upper case.
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("Currency({})", self.as_str()))
+    }
+}
+
+impl FromStr for Currency {
+    type Err = InvalidCurrency;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = InvalidCurrency;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(&value)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.as_str().to_string()
+    }
+}
+
+impl PartialEq<str> for Currency {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str().eq_ignore_ascii_case(other.trim())
+    }
+}
+
+impl PartialEq<&str> for Currency {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+/// Amount of money in given currency.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(Decimal::new(0, 0), currency)
+    }
+
+    /// Sum of amounts, `None` if currencies differ.
+    pub fn checked_add(&self, other: &Money) -> Option<Money> {
+        self.with_same_currency(other, |a, b| a.checked_add(b))
+    }
+
+    /// Difference of amounts, `None` if currencies differ.
+    pub fn checked_sub(&self, other: &Money) -> Option<Money> {
+        self.with_same_currency(other, |a, b| a.checked_sub(b))
+    }
+
+    /// Sum of all amounts in given currency, `None` if any amount is in other currency.
+    pub fn sum<'a, I>(currency: Currency, items: I) -> Option<Money>
+    where
+        I: IntoIterator<Item = &'a Money>,
+    {
+        items
+            .into_iter()
+            .try_fold(Money::zero(currency), |sum, item| sum.checked_add(item))
+    }
+
+    /// Amount rounded to cents (half away from zero).
+    pub fn round(&self) -> Money {
+        Money::new(
+            self.amount
+                .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+            self.currency,
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    fn with_same_currency<F>(&self, other: &Money, op: F) -> Option<Money>
+    where
+        F: FnOnce(Decimal, Decimal) -> Option<Decimal>,
+    {
+        if self.currency != other.currency {
+            return None;
+        }
+        op(self.amount, other.amount).map(|amount| Money::new(amount, self.currency))
+    }
+}
+
+/// Amount with two decimal places followed by code of currency, e.g. `1250.00 CZK`.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("{:.2} {}", self.round().amount, self.currency))
+    }
+}
+
+impl Mul<Decimal> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: Decimal) -> Self::Output {
+        Money::new(self.amount * rhs, self.currency)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Self::Output {
+        Money::new(-self.amount, self.currency)
+    }
+}
@@ -0,0 +1,256 @@
+//! Date and date time types used in models and filters. Types from `chrono` crate are used by
+//! default, with `time` feature types from `time` crate are used instead.
+//!
+//! Date times are always (de)serialized in RFC 3339 format and dates in `YYYY-MM-DD` format
+//! as API expects.
+
+#[cfg(not(feature = "time"))]
+/// Date without time.
+pub type Date = chrono::NaiveDate;
+#[cfg(not(feature = "time"))]
+/// Date with time and time zone offset.
+pub type DateTime = chrono::DateTime<chrono::Local>;
+
+#[cfg(feature = "time")]
+/// Date without time.
+pub type Date = time::Date;
+#[cfg(feature = "time")]
+/// Date with time and time zone offset.
+pub type DateTime = time::OffsetDateTime;
+
+/// Formats date time in RFC 3339 format.
+#[cfg(not(feature = "time"))]
+pub(crate) fn format_datetime(dt: &DateTime) -> String {
+    dt.to_rfc3339()
+}
+
+/// Formats date time in RFC 3339 format.
+#[cfg(feature = "time")]
+pub(crate) fn format_datetime(dt: &DateTime) -> String {
+    dt.format(&time::format_description::well_known::Rfc3339)
+        .expect("date time can't be formatted")
+}
+
+/// Returns year, month and day of date.
+#[cfg(not(feature = "time"))]
+pub(crate) fn ymd(date: &Date) -> (i32, u32, u32) {
+    use chrono::Datelike;
+    (date.year(), date.month(), date.day())
+}
+
+/// Returns year, month and day of date.
+#[cfg(feature = "time")]
+pub(crate) fn ymd(date: &Date) -> (i32, u32, u32) {
+    (
+        date.year(),
+        u8::from(date.month()) as u32,
+        date.day() as u32,
+    )
+}
+
+/// Creates date from year, month and day, `None` if such date doesn't exist.
+#[cfg(not(feature = "time"))]
+pub(crate) fn from_ymd(year: i32, month: u32, day: u32) -> Option<Date> {
+    Date::from_ymd_opt(year, month, day)
+}
+
+/// Creates date from year, month and day, `None` if such date doesn't exist.
+#[cfg(feature = "time")]
+pub(crate) fn from_ymd(year: i32, month: u32, day: u32) -> Option<Date> {
+    let month = std::convert::TryFrom::try_from(month as u8).ok()?;
+    Date::from_calendar_date(year, month, day as u8).ok()
+}
+
+/// Moves date by given number of days.
+#[cfg(not(feature = "time"))]
+pub(crate) fn add_days(date: &Date, days: i64) -> Date {
+    *date + chrono::Duration::days(days)
+}
+
+/// Moves date by given number of days.
+#[cfg(feature = "time")]
+pub(crate) fn add_days(date: &Date, days: i64) -> Date {
+    *date + time::Duration::days(days)
+}
+
+/// Number of days from `from` to `to`, negative if `to` is earlier.
+#[cfg(not(feature = "time"))]
+pub(crate) fn days_between(from: &Date, to: &Date) -> i64 {
+    (*to - *from).num_days()
+}
+
+/// Number of days from `from` to `to`, negative if `to` is earlier.
+#[cfg(feature = "time")]
+pub(crate) fn days_between(from: &Date, to: &Date) -> i64 {
+    (*to - *from).whole_days()
+}
+
+/// Date time in local time zone of `chrono`, used by computations in local time.
+#[cfg(all(feature = "scheduler", not(feature = "time")))]
+pub(crate) fn to_local(dt: &DateTime) -> Option<chrono::DateTime<chrono::Local>> {
+    Some(*dt)
+}
+
+/// Date time in local time zone of `chrono`, used by computations in local time.
+#[cfg(all(feature = "scheduler", feature = "time"))]
+pub(crate) fn to_local(dt: &DateTime) -> Option<chrono::DateTime<chrono::Local>> {
+    use chrono::TimeZone;
+    chrono::Local
+        .timestamp_opt(dt.unix_timestamp(), dt.nanosecond())
+        .single()
+}
+
+/// Date time from local time of `chrono`, `None` if it can't be represented.
+#[cfg(all(feature = "scheduler", not(feature = "time")))]
+pub(crate) fn from_local(dt: chrono::DateTime<chrono::Local>) -> Option<DateTime> {
+    Some(dt)
+}
+
+/// Date time from local time of `chrono`, `None` if it can't be represented.
+#[cfg(all(feature = "scheduler", feature = "time"))]
+pub(crate) fn from_local(dt: chrono::DateTime<chrono::Local>) -> Option<DateTime> {
+    let offset = time::UtcOffset::from_whole_seconds(dt.offset().local_minus_utc()).ok()?;
+    time::OffsetDateTime::from_unix_timestamp(dt.timestamp())
+        .ok()?
+        .replace_nanosecond(dt.timestamp_subsec_nanos())
+        .ok()
+        .map(|utc| utc.to_offset(offset))
+}
+
+/// Current date in local time zone (in UTC with `time` feature).
+#[cfg(not(feature = "time"))]
+pub(crate) fn today() -> Date {
+    chrono::Local::now().naive_local().date()
+}
+
+/// Current date in local time zone (in UTC with `time` feature).
+#[cfg(feature = "time")]
+pub(crate) fn today() -> Date {
+    time::OffsetDateTime::now_utc().date()
+}
+
+/// Last day of month of given date.
+pub(crate) fn end_of_month(date: &Date) -> Date {
+    let (year, month, _) = ymd(date);
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let first = from_ymd(next_year, next_month, 1).expect("first day of month always exists");
+    add_days(&first, -1)
+}
+
+/// Date in month `months` after month of given date with given day. Day is clamped to length
+/// of month, so day 31 is the last day of any month.
+pub(crate) fn add_months(date: &Date, months: u32, day: u32) -> Date {
+    let (year, month, _) = ymd(date);
+    let index = month - 1 + months;
+    let year = year + (index / 12) as i32;
+    let month = index % 12 + 1;
+    let first = from_ymd(year, month, 1).expect("first day of month always exists");
+    let (_, _, last) = ymd(&end_of_month(&first));
+    from_ymd(year, month, day.min(last).max(1)).expect("day is clamped to month")
+}
+
+#[cfg(feature = "time")]
+const DATE_FORMAT: &[time::format_description::FormatItem<'static>] =
+    time::macros::format_description!("[year]-[month]-[day]");
+
+pub(crate) mod datetime {
+    use super::DateTime;
+    use serde::{Deserializer, Serializer};
+
+    #[cfg(not(feature = "time"))]
+    pub fn serialize<S: Serializer>(dt: &DateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(dt, serializer)
+    }
+
+    #[cfg(not(feature = "time"))]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    #[cfg(feature = "time")]
+    pub fn serialize<S: Serializer>(dt: &DateTime, serializer: S) -> Result<S::Ok, S::Error> {
+        time::serde::rfc3339::serialize(dt, serializer)
+    }
+
+    #[cfg(feature = "time")]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime, D::Error> {
+        time::serde::rfc3339::deserialize(deserializer)
+    }
+}
+
+pub(crate) mod option_datetime {
+    use super::DateTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "super::datetime")] DateTime);
+
+    pub fn serialize<S: Serializer>(
+        dt: &Option<DateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match dt {
+            Some(dt) => super::datetime::serialize(dt, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|w| w.0))
+    }
+}
+
+pub(crate) mod date {
+    use super::Date;
+    use serde::{Deserializer, Serializer};
+
+    #[cfg(not(feature = "time"))]
+    pub fn serialize<S: Serializer>(date: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(date, serializer)
+    }
+
+    #[cfg(not(feature = "time"))]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    #[cfg(feature = "time")]
+    pub fn serialize<S: Serializer>(date: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        serializer.serialize_str(&date.format(super::DATE_FORMAT).map_err(S::Error::custom)?)
+    }
+
+    #[cfg(feature = "time")]
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+        use serde::de::Error;
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        Date::parse(&s, super::DATE_FORMAT).map_err(D::Error::custom)
+    }
+}
+
+pub(crate) mod option_date {
+    use super::Date;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(with = "super::date")] Date);
+
+    pub fn serialize<S: Serializer>(date: &Option<Date>, serializer: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => super::date::serialize(date, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Date>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|w| w.0))
+    }
+}
@@ -0,0 +1,317 @@
+//! Demo data for development accounts, available with `devtools` feature.
+//!
+//! [`DemoGenerator`](struct.DemoGenerator.html) generates realistic fake subjects, invoices and
+//! expenses. `Fakturoid::populate_demo_data()` creates them in account and brings invoices into
+//! various states (open, sent, paid, overdue, cancelled) and returns ids of created records,
+//! `Fakturoid::cleanup_demo_data()` deletes exactly these records. Created records are also
+//! tagged with [`DEMO_TAG`](constant.DEMO_TAG.html), so they can be told apart in web
+//! application.
+//!
+//! **Use it only with testing account**, records are really created in account of client.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::devtools::DemoOptions;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("dev@company.com", "apicode", "sandbox-slug", None);
+//!     let demo = client.populate_demo_data(&DemoOptions::default()).await?;
+//!     println!("{} invoices created", demo.invoice_ids.len());
+//!     // ... exercise integration ...
+//!     client.cleanup_demo_data(&demo).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::{Entity, Fakturoid};
+use crate::dates::{self, Date};
+use crate::error::{FakturoidError, Kind};
+use crate::models::{
+    Expense, ExpenseLine, Invoice, InvoiceAction, InvoiceLine, PaymentMethod, Subject,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Tag of all records created by `Fakturoid::populate_demo_data()`.
+pub const DEMO_TAG: &str = "demo-data";
+
+const COMPANY_PREFIXES: &[&str] = &[
+    "Alfa",
+    "Beta",
+    "Delta",
+    "Horizont",
+    "Kvant",
+    "Modrá hora",
+    "Nova",
+    "Orbit",
+    "Sever",
+    "Zenit",
+];
+const COMPANY_FIELDS: &[&str] = &[
+    "stavby",
+    "software",
+    "logistika",
+    "design",
+    "servis",
+    "obchod",
+    "energie",
+    "media",
+];
+const COMPANY_FORMS: &[&str] = &["s.r.o.", "a.s.", "v.o.s."];
+const CITIES: &[(&str, &str)] = &[
+    ("Praha", "11000"),
+    ("Brno", "60200"),
+    ("Ostrava", "70200"),
+    ("Plzeň", "30100"),
+    ("Olomouc", "77900"),
+    ("Liberec", "46001"),
+];
+const STREETS: &[&str] = &[
+    "Masarykova",
+    "Husova",
+    "Nádražní",
+    "Palackého",
+    "Komenského",
+    "Školní",
+];
+const SERVICES: &[(&str, &str, i64)] = &[
+    ("Vývoj webové aplikace", "hod", 1200),
+    ("Konzultace", "hod", 1500),
+    ("Grafický návrh loga", "ks", 8000),
+    ("Správa serveru", "měs", 3500),
+    ("Překlad dokumentace", "NS", 450),
+    ("Školení zaměstnanců", "den", 12000),
+];
+const EXPENSES: &[(&str, i64, i32)] = &[
+    ("Nájem kanceláře", 15000, 21),
+    ("Internetové připojení", 990, 21),
+    ("Kancelářské potřeby", 1850, 21),
+    ("Odborná literatura", 1200, 10),
+    ("Pohonné hmoty", 2600, 21),
+    ("Občerstvení na poradu", 640, 12),
+];
+
+/// Deterministic generator of fake records. The same seed generates the same records.
+pub struct DemoGenerator {
+    state: u64,
+    counter: u32,
+}
+
+impl DemoGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed ^ 0x5DEE_CE66_D1CE_4E5B,
+            counter: 0,
+        }
+    }
+
+    /// Next pseudo-random number lower than `bound`.
+    fn next(&mut self, bound: usize) -> usize {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let value = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        ((value >> 33) % bound.max(1) as u64) as usize
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.next(items.len())]
+    }
+
+    /// Registration number with valid check digit.
+    fn registration_no(&mut self) -> String {
+        let digits: Vec<u32> = (0..7).map(|_| self.next(10) as u32).collect();
+        let sum: u32 = digits
+            .iter()
+            .enumerate()
+            .map(|(i, d)| d * (8 - i as u32))
+            .sum();
+        let check = (11 - sum % 11) % 10;
+        digits
+            .iter()
+            .chain(std::iter::once(&check))
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    /// Fake Czech company.
+    pub fn subject(&mut self) -> Subject {
+        self.counter += 1;
+        let name = format!(
+            "{} {} {}",
+            self.pick(COMPANY_PREFIXES),
+            self.pick(COMPANY_FIELDS),
+            self.pick(COMPANY_FORMS)
+        );
+        let (city, zip) = *self.pick(CITIES);
+        let registration_no = self.registration_no();
+        let street = format!("{} {}", self.pick(STREETS), self.next(200) + 1);
+        Subject {
+            custom_id: Some(format!("demo-{}", self.counter)),
+            name: Some(name),
+            email: Some(format!("fakturace{}@example.com", self.counter)),
+            street: Some(street),
+            city: Some(city.to_string()),
+            zip: Some(zip.to_string()),
+            country: Some("CZ".to_string()),
+            vat_no: Some(format!("CZ{}", registration_no)),
+            registration_no: Some(registration_no),
+            ..Subject::default()
+        }
+    }
+
+    /// Fake invoice for subject with one to three lines issued within last 60 days.
+    pub fn invoice(&mut self, subject_id: i32) -> Invoice {
+        let issued_on = dates::add_days(&dates::today(), -(self.next(60) as i64));
+        let lines = (0..=self.next(3))
+            .map(|_| {
+                let (name, unit, price) = *self.pick(SERVICES);
+                let quantity = Decimal::from(self.next(10) as i64 + 1);
+                InvoiceLine::new(name, quantity, Some(unit), Decimal::from(price), 21)
+            })
+            .collect();
+        let mut invoice = Invoice::default();
+        invoice.subject_id = Some(subject_id);
+        invoice.issued_on = Some(issued_on);
+        invoice.due = Some(14);
+        invoice.payment_method = Some(PaymentMethod::Bank);
+        invoice.tags = Some(vec![DEMO_TAG.to_string()]);
+        invoice.lines = Some(lines);
+        invoice
+    }
+
+    /// Fake expense from supplier issued within last 60 days.
+    pub fn expense(&mut self, subject_id: i32) -> Expense {
+        self.counter += 1;
+        let issued_on: Date = dates::add_days(&dates::today(), -(self.next(60) as i64));
+        let (name, price, vat_rate) = *self.pick(EXPENSES);
+        let mut expense = Expense::default();
+        expense.subject_id = Some(subject_id);
+        expense.original_number = Some(format!("DEMO-{:05}", self.counter));
+        expense.issued_on = Some(issued_on);
+        expense.due_on = Some(dates::add_days(&issued_on, 14));
+        expense.payment_method = Some(PaymentMethod::Bank);
+        expense.tags = Some(vec![DEMO_TAG.to_string()]);
+        expense.lines = Some(vec![ExpenseLine::new(
+            name,
+            Decimal::ONE,
+            None,
+            Decimal::from(price),
+            vat_rate,
+        )]);
+        expense
+    }
+}
+
+/// How much demo data is created.
+#[derive(Clone, Debug)]
+pub struct DemoOptions {
+    pub subjects: usize,
+    pub invoices_per_subject: usize,
+    pub expenses: usize,
+    /// Seed of generator, the same seed generates the same data.
+    pub seed: u64,
+}
+
+impl Default for DemoOptions {
+    fn default() -> Self {
+        Self {
+            subjects: 5,
+            invoices_per_subject: 4,
+            expenses: 10,
+            seed: 42,
+        }
+    }
+}
+
+/// Ids of records created by `Fakturoid::populate_demo_data()`. It can be serialized, so demo
+/// data can be cleaned up by another run of program.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoData {
+    pub subject_ids: Vec<i32>,
+    pub invoice_ids: Vec<i32>,
+    pub expense_ids: Vec<i32>,
+}
+
+/// Actions which bring n-th invoice of subject into different state: open, sent, paid, overdue
+/// (sent invoice issued long ago) and cancelled.
+fn demo_actions(index: usize) -> Vec<InvoiceAction> {
+    match index % 5 {
+        0 => vec![],
+        1 | 3 => vec![InvoiceAction::MarkAsSent],
+        2 => vec![InvoiceAction::MarkAsSent, InvoiceAction::Pay],
+        _ => vec![InvoiceAction::Cancel],
+    }
+}
+
+impl Fakturoid {
+    /// Creates demo subjects, invoices in various states and expenses, see
+    /// [`devtools`](../devtools/index.html) module. Subjects are tagged by `custom_id` with
+    /// `demo-` prefix, invoices and expenses by [`DEMO_TAG`](../devtools/constant.DEMO_TAG.html).
+    pub async fn populate_demo_data(
+        &self,
+        options: &DemoOptions,
+    ) -> Result<DemoData, FakturoidError> {
+        let mut generator = DemoGenerator::new(options.seed);
+        let mut data = DemoData::default();
+        for _ in 0..options.subjects {
+            if let Some(id) = self.create(generator.subject()).await?.id {
+                data.subject_ids.push(id);
+            }
+        }
+        for subject_id in data.subject_ids.clone() {
+            for index in 0..options.invoices_per_subject {
+                let mut invoice = generator.invoice(subject_id);
+                if index % 5 == 3 {
+                    // issued long ago and unpaid, so it becomes overdue
+                    invoice.issued_on = Some(dates::add_days(&dates::today(), -45));
+                }
+                let id = match self.create(invoice).await?.id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                for action in demo_actions(index) {
+                    self.action::<_, ()>(id, action, None).await?;
+                }
+                data.invoice_ids.push(id);
+            }
+        }
+        for subject_id in data.subject_ids.iter().cycle().take(options.expenses) {
+            if let Some(id) = self.create(generator.expense(*subject_id)).await?.id {
+                data.expense_ids.push(id);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Deletes demo invoices, expenses and subjects created by `populate_demo_data()`. Only
+    /// records with ids in `data` are deleted, records which are already deleted are skipped.
+    /// Number of deleted records is returned.
+    pub async fn cleanup_demo_data(&self, data: &DemoData) -> Result<usize, FakturoidError> {
+        let mut deleted = 0;
+        for id in &data.invoice_ids {
+            deleted += self.delete_demo::<Invoice>(*id).await?;
+        }
+        for id in &data.expense_ids {
+            deleted += self.delete_demo::<Expense>(*id).await?;
+        }
+        for id in &data.subject_ids {
+            deleted += self.delete_demo::<Subject>(*id).await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes demo record, returns number of deleted records.
+    async fn delete_demo<T: Entity>(&self, id: i32) -> Result<usize, FakturoidError> {
+        match self.delete::<T>(id).await {
+            Ok(()) => Ok(1),
+            Err(err) if *err.kind() == Kind::EntityDoesNotExists => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Dumping of complete HTTP traffic for debugging purposes.
+
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::{Request, Response, ResponseBuilderExt};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Target where HTTP traffic is dumped.
+#[derive(Clone)]
+pub(crate) enum HttpDump {
+    Writer(Arc<Mutex<Box<dyn Write + Send>>>),
+    #[cfg(feature = "log")]
+    Log,
+}
+
+impl HttpDump {
+    pub(crate) fn request(&self, request: &Request) {
+        let mut dump = format!("> {} {}\n", request.method(), request.url());
+        push_headers(&mut dump, "> ", request.headers());
+        if let Some(body) = request.body().and_then(|b| b.as_bytes()) {
+            push_body(&mut dump, "> ", body);
+        }
+        self.write(dump);
+    }
+
+    /// Dumps response. Body of response must be read, so new response with the same content
+    /// and URL is returned.
+    pub(crate) async fn response(&self, response: Response) -> Result<Response, reqwest::Error> {
+        let status = response.status();
+        let version = response.version();
+        let url = response.url().clone();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+
+        let mut dump = format!("< {:?} {}\n", version, status);
+        push_headers(&mut dump, "< ", &headers);
+        push_body(&mut dump, "< ", &body);
+        self.write(dump);
+
+        let mut response = http::Response::builder()
+            .status(status)
+            .version(version)
+            .url(url)
+            .body(body)
+            .expect("valid response parts");
+        *response.headers_mut() = headers;
+        Ok(response.into())
+    }
+
+    fn write(&self, dump: String) {
+        match self {
+            HttpDump::Writer(writer) => {
+                if let Ok(mut writer) = writer.lock() {
+                    let _ = writer.write_all(dump.as_bytes());
+                    let _ = writer.flush();
+                }
+            }
+            #[cfg(feature = "log")]
+            HttpDump::Log => log::debug!(target: "fakturoid::http", "{}", dump),
+        }
+    }
+}
+
+fn push_headers(dump: &mut String, prefix: &str, headers: &HeaderMap) {
+    for (name, value) in headers {
+        let value = if name == AUTHORIZATION {
+            "[redacted]"
+        } else {
+            value.to_str().unwrap_or("[binary]")
+        };
+        dump.push_str(&format!("{}{}: {}\n", prefix, name, value));
+    }
+}
+
+fn push_body(dump: &mut String, prefix: &str, body: &[u8]) {
+    dump.push_str(prefix.trim_end());
+    dump.push('\n');
+    for line in String::from_utf8_lossy(body).lines() {
+        dump.push_str(&format!("{}{}\n", prefix, line));
+    }
+}
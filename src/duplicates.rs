@@ -0,0 +1,427 @@
+//! Detection of duplicate subjects, typically created by messy imports, their merging and
+//! prevention of creating new ones. Prevention of duplicate invoices for one order and upserts
+//! of records synchronized from other systems by `custom_id` are here too.
+
+use crate::client::{Entity, Fakturoid};
+use crate::error::{FakturoidError, MissingField};
+use crate::filters::{EntityFilter, InvoiceFilters, SubjectFilters};
+use crate::models::{Invoice, Subject};
+use futures::future;
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Why subjects are considered to be duplicates.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DuplicateReason {
+    /// Subjects have the same registration number.
+    RegistrationNo(String),
+    /// Subjects have the same email (case insensitive).
+    Email(String),
+    /// Subjects have the same name after normalization (case, punctuation, white spaces).
+    Name(String),
+}
+
+/// Group of subjects which are likely duplicates of each other.
+#[derive(Clone, Debug)]
+pub struct DuplicateGroup {
+    pub reason: DuplicateReason,
+    pub subjects: Vec<Subject>,
+}
+
+/// Result of upsert, entity was either created or existing entity with the same `custom_id` was
+/// updated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Upserted<T> {
+    Created(T),
+    Updated(T),
+}
+
+impl<T> Upserted<T> {
+    pub fn was_created(&self) -> bool {
+        matches!(self, Upserted::Created(_))
+    }
+
+    /// Created or updated entity.
+    pub fn into_inner(self) -> T {
+        match self {
+            Upserted::Created(entity) | Upserted::Updated(entity) => entity,
+        }
+    }
+}
+
+/// Plan of merging duplicate subjects into one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergePlan {
+    /// Id of subject which is kept.
+    pub keep: i32,
+    /// Ids of subjects which invoices are moved to kept subject and which are deleted.
+    pub remove: Vec<i32>,
+}
+
+impl DuplicateGroup {
+    /// Plan which keeps the oldest subject of group. `None` is returned if there are less than
+    /// two subjects with id in group.
+    pub fn merge_plan(&self) -> Option<MergePlan> {
+        let mut ids: Vec<i32> = self.subjects.iter().filter_map(|s| s.id).collect();
+        if ids.len() < 2 {
+            return None;
+        }
+        ids.sort_unstable();
+        let keep = ids.remove(0);
+        Some(MergePlan { keep, remove: ids })
+    }
+}
+
+/// Finds groups of likely duplicate subjects: subjects with the same registration number, email
+/// or normalized name. One subject can be in more groups.
+pub fn find_duplicates(subjects: &[Subject]) -> Vec<DuplicateGroup> {
+    let mut groups = group_by(subjects, |s| {
+        s.registration_no
+            .as_ref()
+            .map(|r| r.trim().to_string())
+            .filter(|r| !r.is_empty())
+            .map(DuplicateReason::RegistrationNo)
+    });
+    groups.append(&mut group_by(subjects, |s| {
+        s.email
+            .as_ref()
+            .map(|e| e.trim().to_lowercase())
+            .filter(|e| !e.is_empty())
+            .map(DuplicateReason::Email)
+    }));
+    groups.append(&mut group_by(subjects, |s| {
+        s.name
+            .as_ref()
+            .map(|n| normalize_name(n))
+            .filter(|n| !n.is_empty())
+            .map(DuplicateReason::Name)
+    }));
+    groups
+}
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn group_by<F>(subjects: &[Subject], key: F) -> Vec<DuplicateGroup>
+where
+    F: Fn(&Subject) -> Option<DuplicateReason>,
+{
+    let mut order = Vec::new();
+    let mut groups: HashMap<DuplicateReason, Vec<Subject>> = HashMap::new();
+    for subject in subjects {
+        if let Some(reason) = key(subject) {
+            groups
+                .entry(reason.clone())
+                .or_insert_with(|| {
+                    order.push(reason);
+                    Vec::new()
+                })
+                .push(subject.clone());
+        }
+    }
+    order
+        .into_iter()
+        .filter_map(|reason| {
+            groups
+                .remove(&reason)
+                .map(|subjects| DuplicateGroup { reason, subjects })
+        })
+        .filter(|g| g.subjects.len() > 1)
+        .collect()
+}
+
+impl Fakturoid {
+    /// Downloads all subjects and finds likely duplicates. See [`find_duplicates`].
+    ///
+    /// [`find_duplicates`]: ../duplicates/fn.find_duplicates.html
+    pub async fn find_duplicate_subjects(&self) -> Result<Vec<DuplicateGroup>, FakturoidError> {
+        let subjects: Vec<Subject> = self
+            .list::<Subject>(None)
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?;
+        Ok(find_duplicates(&subjects))
+    }
+
+    /// Executes merge plan: invoices of removed subjects are moved to kept subject and removed
+    /// subjects are deleted. Invoices which can't be changed (e.g. locked ones) cause error and
+    /// their subject is not deleted.
+    pub async fn merge_subjects(&self, plan: &MergePlan) -> Result<(), FakturoidError> {
+        for id in plan.remove.iter() {
+            let invoices: Vec<Invoice> = self
+                .list_filtered(InvoiceFilters::new().subject_id(*id))
+                .await?
+                .into_stream()
+                .try_collect()
+                .await?;
+            for invoice_id in invoices.iter().filter_map(|i| i.id) {
+                let mut update = Invoice::default();
+                update.subject_id = Some(plan.keep);
+                self.update(invoice_id, update).await?;
+            }
+            self.delete::<Subject>(*id).await?;
+        }
+        Ok(())
+    }
+
+    /// Finds existing subject which is the same as given one: subject with the same `custom_id`,
+    /// `registration_no` or `email` (in this order).
+    pub async fn find_existing_subject(
+        &self,
+        subject: &Subject,
+    ) -> Result<Option<Subject>, FakturoidError> {
+        if let Some(custom_id) = subject.custom_id.as_ref() {
+            let found = self.find_subject_by_custom_id(custom_id).await?;
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+        if let Some(registration_no) = subject.registration_no.as_ref().map(|r| r.trim()) {
+            if !registration_no.is_empty() {
+                let found = self
+                    .search_first::<Subject, _>(registration_no, |s| {
+                        s.registration_no.as_ref().map(|r| r.trim()) == Some(registration_no)
+                    })
+                    .await?;
+                if found.is_some() {
+                    return Ok(found);
+                }
+            }
+        }
+        if let Some(email) = subject.email.as_ref().map(|e| e.trim().to_lowercase()) {
+            if !email.is_empty() {
+                return self
+                    .search_first::<Subject, _>(&email, |s| {
+                        s.email.as_ref().map(|e| e.trim().to_lowercase()) == Some(email.clone())
+                    })
+                    .await;
+            }
+        }
+        Ok(None)
+    }
+
+    /// Creates subject only if there is no existing subject with the same `custom_id`,
+    /// `registration_no` or `email`. Existing subject is returned otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Subject;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut subject = Subject::default();
+    ///     subject.name = Some("Some company".to_string());
+    ///     subject.registration_no = Some("28897501".to_string());
+    ///     let subject = client.create_subject_unless_exists(subject).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_subject_unless_exists(
+        &self,
+        subject: Subject,
+    ) -> Result<Subject, FakturoidError> {
+        match self.find_existing_subject(&subject).await? {
+            Some(existing) => Ok(existing),
+            None => self.create(subject).await,
+        }
+    }
+
+    /// Finds existing invoice created for order: invoice with the same `custom_id` as given one
+    /// or invoice with given `order_number`.
+    pub async fn find_invoice_for_order(
+        &self,
+        order_number: &str,
+        invoice: &Invoice,
+    ) -> Result<Option<Invoice>, FakturoidError> {
+        if let Some(custom_id) = invoice.custom_id.as_ref() {
+            let found = self.find_invoice_by_custom_id(custom_id).await?;
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+        let order_number = order_number.trim();
+        if order_number.is_empty() {
+            return Ok(None);
+        }
+        self.search_first::<Invoice, _>(order_number, |i| {
+            i.order_number.as_ref().map(|o| o.trim()) == Some(order_number)
+        })
+        .await
+    }
+
+    /// Creates invoice for order unless there already is one (see
+    /// [`find_invoice_for_order()`](#method.find_invoice_for_order)), in which case existing
+    /// invoice is returned. `order_number` of created invoice is set to given one, so order
+    /// processing can be safely run again, e.g. when webhook is delivered twice.
+    ///
+    /// Setting `custom_id` (e.g. to order id) is recommended, lookup by it is exact while order
+    /// number is looked up by fulltext search.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Invoice;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut invoice = Invoice::default();
+    ///     invoice.subject_id = Some(16);
+    ///     invoice.custom_id = Some("eshop-10045".to_string());
+    ///     let invoice = client.create_invoice_for_order("10045", invoice).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn create_invoice_for_order(
+        &self,
+        order_number: &str,
+        mut invoice: Invoice,
+    ) -> Result<Invoice, FakturoidError> {
+        match self.find_invoice_for_order(order_number, &invoice).await? {
+            Some(existing) => Ok(existing),
+            None => {
+                invoice.order_number = Some(order_number.to_string());
+                self.create(invoice).await
+            }
+        }
+    }
+
+    /// Subject with given `custom_id`.
+    pub async fn find_subject_by_custom_id(
+        &self,
+        custom_id: &str,
+    ) -> Result<Option<Subject>, FakturoidError> {
+        self.find_by_custom_id(SubjectFilters::new().custom_id(custom_id), custom_id, |s| {
+            s.custom_id.as_deref()
+        })
+        .await
+    }
+
+    /// Invoice with given `custom_id`.
+    pub async fn find_invoice_by_custom_id(
+        &self,
+        custom_id: &str,
+    ) -> Result<Option<Invoice>, FakturoidError> {
+        self.find_by_custom_id(InvoiceFilters::new().custom_id(custom_id), custom_id, |i| {
+            i.custom_id.as_deref()
+        })
+        .await
+    }
+
+    /// Creates subject or updates existing subject with the same `custom_id`, so record
+    /// synchronized from other system (e.g. ERP) can be sent repeatedly without creating
+    /// duplicates. Fields which are `None` are not sent, so they are kept unchanged by update.
+    /// Subject without `custom_id` is rejected with
+    /// [`MissingField`](../error/struct.MissingField.html) error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// extern crate tokio;
+    /// use fakturoid::models::Subject;
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let subject = Subject::builder()
+    ///         .name("Some company")
+    ///         .custom_id("erp-1024")
+    ///         .build()?;
+    ///     let upserted = client.upsert_subject(subject).await?;
+    ///     if upserted.was_created() {
+    ///         println!("New subject {:?}", upserted.into_inner().id);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn upsert_subject(
+        &self,
+        subject: Subject,
+    ) -> Result<Upserted<Subject>, FakturoidError> {
+        let custom_id = Self::required_custom_id(subject.custom_id.as_ref())?;
+        let existing = self.find_subject_by_custom_id(&custom_id).await?;
+        self.upsert(existing.and_then(|s| s.id), subject).await
+    }
+
+    /// Creates invoice or updates existing invoice with the same `custom_id`, see
+    /// [`upsert_subject()`](#method.upsert_subject).
+    pub async fn upsert_invoice(
+        &self,
+        invoice: Invoice,
+    ) -> Result<Upserted<Invoice>, FakturoidError> {
+        let custom_id = Self::required_custom_id(invoice.custom_id.as_ref())?;
+        let existing = self.find_invoice_by_custom_id(&custom_id).await?;
+        self.upsert(existing.and_then(|i| i.id), invoice).await
+    }
+
+    fn required_custom_id(custom_id: Option<&String>) -> Result<String, FakturoidError> {
+        custom_id
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string())
+            .ok_or_else(|| FakturoidError::from_std_err(MissingField::new("custom_id")))
+    }
+
+    async fn upsert<T>(&self, id: Option<i32>, entity: T) -> Result<Upserted<T>, FakturoidError>
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        match id {
+            Some(id) => self.update(id, entity).await.map(Upserted::Updated),
+            None => self.create(entity).await.map(Upserted::Created),
+        }
+    }
+
+    async fn find_by_custom_id<F, G>(
+        &self,
+        filter: F,
+        custom_id: &str,
+        get: G,
+    ) -> Result<Option<F::Entity>, FakturoidError>
+    where
+        F: EntityFilter,
+        F::Entity: DeserializeOwned,
+        G: Fn(&F::Entity) -> Option<&str>,
+    {
+        Ok(self
+            .list_filtered(filter)
+            .await?
+            .into_data()
+            .into_iter()
+            .find(|entity| get(entity) == Some(custom_id)))
+    }
+
+    async fn search_first<T, P>(
+        &self,
+        query: &str,
+        predicate: P,
+    ) -> Result<Option<T>, FakturoidError>
+    where
+        T: Entity + DeserializeOwned + Send + 'static,
+        P: Fn(&T) -> bool,
+    {
+        // pages after the first match are not downloaded
+        let mut found = Box::pin(
+            self.fulltext::<T>(query, None)
+                .await?
+                .into_stream()
+                .try_filter(|item| future::ready(predicate(item))),
+        );
+        found.try_next().await
+    }
+}
@@ -1,9 +1,14 @@
+#[cfg(feature = "qr")]
+use crate::currency::Currency;
+use crate::gateways::PaymentGateway;
 use reqwest::Error;
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error as StdError;
 use std::fmt;
 use std::fmt::Formatter;
+use std::io;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, PartialEq)]
 pub enum Kind {
@@ -34,11 +39,612 @@ impl fmt::Display for UnknownError {
 
 impl StdError for UnknownError {}
 
+/// PDF of invoice was not generated in time.
+#[derive(Debug)]
+pub struct PdfNotReady(i32);
+
+impl PdfNotReady {
+    pub(crate) fn new(invoice_id: i32) -> Self {
+        Self(invoice_id)
+    }
+
+    /// Id of invoice which PDF is not ready.
+    pub fn invoice_id(&self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for PdfNotReady {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("PDF of invoice {} is not ready", self.0))
+    }
+}
+
+impl StdError for PdfNotReady {}
+
+/// User agent doesn't contain contact email, which fakturoid.cz requires.
+#[derive(Debug)]
+pub struct InvalidUserAgent(String);
+
+impl InvalidUserAgent {
+    pub(crate) fn new(user_agent: &str) -> Self {
+        Self(user_agent.to_string())
+    }
+
+    /// Rejected user agent.
+    pub fn user_agent(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InvalidUserAgent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "User agent \"{}\" doesn't contain contact email",
+            self.0
+        ))
+    }
+}
+
+impl StdError for InvalidUserAgent {}
+
+/// Payment terms are negative or longer than
+/// [`MAX_DUE_DAYS`](../terms/constant.MAX_DUE_DAYS.html).
+#[derive(Debug)]
+pub struct InvalidPaymentTerms(i64);
+
+impl InvalidPaymentTerms {
+    pub(crate) fn new(days: i64) -> Self {
+        Self(days)
+    }
+
+    /// Rejected number of days.
+    pub fn days(&self) -> i64 {
+        self.0
+    }
+}
+
+impl fmt::Display for InvalidPaymentTerms {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "Payment terms of {} days are out of range 0 - {}",
+            self.0,
+            crate::terms::MAX_DUE_DAYS
+        ))
+    }
+}
+
+impl StdError for InvalidPaymentTerms {}
+
+/// Currency code is not three letter ISO 4217 code.
+#[derive(Debug, PartialEq)]
+pub struct InvalidCurrency(String);
+
+impl InvalidCurrency {
+    pub(crate) fn new(code: &str) -> Self {
+        Self(code.to_string())
+    }
+
+    /// Rejected code.
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for InvalidCurrency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("\"{}\" is not ISO 4217 currency code", self.0))
+    }
+}
+
+impl StdError for InvalidCurrency {}
+
+/// Czech registration number (IČO) is not eight digits with valid check digit, see
+/// [`ares`](../ares/index.html) module.
+#[cfg(feature = "ares")]
+#[derive(Debug, PartialEq)]
+pub struct InvalidRegistrationNo(String);
+
+#[cfg(feature = "ares")]
+impl InvalidRegistrationNo {
+    pub(crate) fn new(registration_no: &str) -> Self {
+        Self(registration_no.to_string())
+    }
+
+    /// Rejected registration number.
+    pub fn registration_no(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "ares")]
+impl fmt::Display for InvalidRegistrationNo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "\"{}\" is not valid registration number",
+            self.0
+        ))
+    }
+}
+
+#[cfg(feature = "ares")]
+impl StdError for InvalidRegistrationNo {}
+
+/// Payment QR code can't be generated for invoice, see [`qr`](../qr/index.html) module.
+#[cfg(feature = "qr")]
+#[derive(Debug, PartialEq)]
+pub enum PaymentQrError {
+    /// Invoice lacks field needed for payment, e.g. `iban` or `currency`.
+    MissingField(&'static str),
+    /// Bank account of invoice is not valid Czech account number or IBAN.
+    InvalidAccount(String),
+    /// Format doesn't support currency of invoice, EPC QR code is for payments in euro only.
+    UnsupportedCurrency(Currency),
+    /// Invoice is paid, remaining amount is zero.
+    NothingToPay,
+    /// Variable symbol is not 1 to 10 digits, QR Platba can't carry it.
+    InvalidVariableSymbol(String),
+}
+
+#[cfg(feature = "qr")]
+impl fmt::Display for PaymentQrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentQrError::MissingField(field) => f.write_fmt(format_args!(
+                "Field {} needed for payment is missing",
+                field
+            )),
+            PaymentQrError::InvalidAccount(account) => {
+                f.write_fmt(format_args!("Bank account {} is not valid", account))
+            }
+            PaymentQrError::UnsupportedCurrency(currency) => f.write_fmt(format_args!(
+                "Payments in {} are not supported by QR code format",
+                currency
+            )),
+            PaymentQrError::NothingToPay => f.write_str("Invoice is paid, there is nothing to pay"),
+            PaymentQrError::InvalidVariableSymbol(symbol) => f.write_fmt(format_args!(
+                "Variable symbol {} is not number of at most 10 digits",
+                symbol
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "qr")]
+impl StdError for PaymentQrError {}
+
+/// Request was not sent, because client was shut down by `Fakturoid::shutdown()`.
+#[derive(Debug)]
+pub struct ClientShutDown;
+
+impl fmt::Display for ClientShutDown {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Client is shut down, no new requests are accepted")
+    }
+}
+
+impl StdError for ClientShutDown {}
+
+/// Requests were still in flight when timeout of `Fakturoid::shutdown()` elapsed.
+#[derive(Debug)]
+pub struct ShutdownTimeout(usize);
+
+impl ShutdownTimeout {
+    pub(crate) fn new(in_flight: usize) -> Self {
+        Self(in_flight)
+    }
+
+    /// Number of unfinished requests.
+    pub fn in_flight(&self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for ShutdownTimeout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "Shutdown timed out with {} requests in flight",
+            self.0
+        ))
+    }
+}
+
+impl StdError for ShutdownTimeout {}
+
+/// List has more pages than limit given to `Fakturoid::list_all()`.
+#[derive(Debug)]
+pub struct PageLimitExceeded(usize);
+
+impl PageLimitExceeded {
+    pub(crate) fn new(max_pages: usize) -> Self {
+        Self(max_pages)
+    }
+
+    /// Maximum number of pages which were allowed.
+    pub fn max_pages(&self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for PageLimitExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("List has more than {} pages", self.0))
+    }
+}
+
+impl StdError for PageLimitExceeded {}
+
+/// Record built by builder lacks field required by API.
+#[derive(Debug, PartialEq)]
+pub struct MissingField(&'static str);
+
+impl MissingField {
+    pub(crate) fn new(field: &'static str) -> Self {
+        Self(field)
+    }
+
+    /// Name of missing field.
+    pub fn field(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for MissingField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("Required field {} is missing", self.0))
+    }
+}
+
+impl StdError for MissingField {}
+
+/// Invoice refers to number format which doesn't exist in account.
+#[derive(Debug, PartialEq)]
+pub struct UnknownNumberFormat(i32);
+
+impl UnknownNumberFormat {
+    pub(crate) fn new(number_format_id: i32) -> Self {
+        Self(number_format_id)
+    }
+
+    /// Id of unknown number format.
+    pub fn number_format_id(&self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for UnknownNumberFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("Number format {} does not exist", self.0))
+    }
+}
+
+impl StdError for UnknownNumberFormat {}
+
+/// Webhook delivery doesn't carry `Authorization` header configured for webhook.
+#[derive(Debug)]
+pub struct InvalidWebhookAuthorization;
+
+impl fmt::Display for InvalidWebhookAuthorization {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Webhook delivery has missing or wrong Authorization header")
+    }
+}
+
+impl StdError for InvalidWebhookAuthorization {}
+
+/// Rate limit of client allows no requests, see `FakturoidBuilder::rate_limit()`.
+#[derive(Debug)]
+pub struct InvalidRateLimit;
+
+impl fmt::Display for InvalidRateLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Rate limit must allow at least one request per minute")
+    }
+}
+
+impl StdError for InvalidRateLimit {}
+
+/// API v3 was selected for client authenticated by API key, but v3 accepts only OAuth, see
+/// `Fakturoid::oauth_builder()`.
+#[derive(Debug)]
+pub struct BasicAuthNotSupported;
+
+impl fmt::Display for BasicAuthNotSupported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("API v3 doesn't accept authentication by API key, OAuth must be used")
+    }
+}
+
+impl StdError for BasicAuthNotSupported {}
+
+/// Absolute URL of raw request or attachment points outside of Fakturoid, credentials are
+/// never sent there.
+#[derive(Debug, PartialEq)]
+pub struct ForeignUrl(String);
+
+impl ForeignUrl {
+    pub(crate) fn new(url: &str) -> Self {
+        Self(url.to_string())
+    }
+
+    /// Rejected URL.
+    pub fn url(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ForeignUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("URL {} is outside of Fakturoid API", self.0))
+    }
+}
+
+impl StdError for ForeignUrl {}
+
+/// Payment gateway can't be used on invoice, because it is disabled in account settings or it
+/// doesn't support currency of invoice.
+#[derive(Debug)]
+pub struct InvalidPaymentGateway {
+    gateway: PaymentGateway,
+    currency: Option<String>,
+}
+
+impl InvalidPaymentGateway {
+    pub(crate) fn new(gateway: PaymentGateway, currency: Option<&str>) -> Self {
+        Self {
+            gateway,
+            currency: currency.map(|c| c.to_string()),
+        }
+    }
+
+    pub fn gateway(&self) -> PaymentGateway {
+        self.gateway
+    }
+
+    /// Currency not supported by gateway, `None` if gateway is disabled in account settings.
+    pub fn unsupported_currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+}
+
+impl fmt::Display for InvalidPaymentGateway {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.currency.as_ref() {
+            Some(currency) => f.write_fmt(format_args!(
+                "{} doesn't support payments in {}",
+                self.gateway, currency
+            )),
+            None => f.write_fmt(format_args!(
+                "{} is not enabled in account settings",
+                self.gateway
+            )),
+        }
+    }
+}
+
+impl StdError for InvalidPaymentGateway {}
+
+/// File can't be used as attachment of document.
+#[derive(Debug)]
+pub struct AttachmentError {
+    path: PathBuf,
+    source: io::Error,
+}
+
+impl AttachmentError {
+    pub(crate) fn new(path: &Path, source: io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    /// Path of file which was being attached.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Underlying I/O error.
+    pub fn io_error(&self) -> &io::Error {
+        &self.source
+    }
+}
+
+impl fmt::Display for AttachmentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "Can't attach file {}: {}",
+            self.path.display(),
+            self.source
+        ))
+    }
+}
+
+impl StdError for AttachmentError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Schedule expression of job can't be parsed.
+#[cfg(feature = "scheduler")]
+#[derive(Debug)]
+pub struct InvalidSchedule(String);
+
+#[cfg(feature = "scheduler")]
+impl InvalidSchedule {
+    pub(crate) fn new(expr: &str) -> Self {
+        Self(expr.to_string())
+    }
+}
+
+#[cfg(feature = "scheduler")]
+impl fmt::Display for InvalidSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("Invalid schedule expression \"{}\"", self.0))
+    }
+}
+
+#[cfg(feature = "scheduler")]
+impl StdError for InvalidSchedule {}
+
 #[derive(Deserialize)]
 pub(crate) struct DataErrors {
     errors: HashMap<String, Vec<String>>,
 }
 
+/// Validation errors returned by API (status 422) arranged into tree by field path, so they can be
+/// attached to form inputs. Key `lines[2].unit_price` (or `lines.2.unit_price`) is stored under
+/// field `lines`, item `2` and field `unit_price`.
+///
+/// Serialized into JSON as nested objects with `messages`, `fields` and `items` keys, empty
+/// ones are omitted.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct FieldErrors {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    messages: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, FieldErrors>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    items: BTreeMap<usize, FieldErrors>,
+}
+
+enum PathSegment<'a> {
+    Field(&'a str),
+    Item(usize),
+}
+
+fn parse_path(path: &str) -> Vec<PathSegment<'_>> {
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|p| !p.is_empty()) {
+        let mut rest = part;
+        if let Some(bracket) = rest.find('[') {
+            if bracket > 0 {
+                segments.push(PathSegment::Field(&rest[..bracket]));
+            }
+            rest = &rest[bracket..];
+            while let Some(end) = rest.find(']') {
+                match rest[1..end].parse() {
+                    Ok(index) => segments.push(PathSegment::Item(index)),
+                    Err(_) => segments.push(PathSegment::Field(&rest[1..end])),
+                }
+                rest = rest[end + 1..].trim_start_matches('.');
+                if !rest.starts_with('[') {
+                    break;
+                }
+            }
+            if !rest.is_empty() {
+                segments.push(PathSegment::Field(rest));
+            }
+        } else {
+            match rest.parse() {
+                Ok(index) => segments.push(PathSegment::Item(index)),
+                Err(_) => segments.push(PathSegment::Field(rest)),
+            }
+        }
+    }
+    segments
+}
+
+impl FieldErrors {
+    /// Builds tree from errors as returned by
+    /// [`FakturoidError::data_errors()`](struct.FakturoidError.html#method.data_errors).
+    pub fn from_data_errors(errors: &HashMap<String, Vec<String>>) -> Self {
+        let mut tree = Self::default();
+        for (path, messages) in errors {
+            tree.insert(path, messages.iter().cloned());
+        }
+        tree
+    }
+
+    fn insert<I: IntoIterator<Item = String>>(&mut self, path: &str, messages: I) {
+        let mut node = self;
+        for segment in parse_path(path) {
+            node = match segment {
+                PathSegment::Field(name) => node.fields.entry(name.to_string()).or_default(),
+                PathSegment::Item(index) => node.items.entry(index).or_default(),
+            };
+        }
+        node.messages.extend(messages);
+    }
+
+    /// Messages attached directly to this node.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// Errors of nested field.
+    pub fn field(&self, name: &str) -> Option<&FieldErrors> {
+        self.fields.get(name)
+    }
+
+    /// Errors of item with given index of nested list (e.g. invoice line).
+    pub fn item(&self, index: usize) -> Option<&FieldErrors> {
+        self.items.get(&index)
+    }
+
+    /// Errors on given path, e.g. `lines[2].unit_price`.
+    pub fn get(&self, path: &str) -> Option<&FieldErrors> {
+        let mut node = self;
+        for segment in parse_path(path) {
+            node = match segment {
+                PathSegment::Field(name) => node.field(name)?,
+                PathSegment::Item(index) => node.item(index)?,
+            };
+        }
+        Some(node)
+    }
+
+    /// Messages on given path, empty slice if there are none.
+    pub fn messages_at(&self, path: &str) -> &[String] {
+        self.get(path).map(|e| e.messages()).unwrap_or(&[])
+    }
+
+    /// Returns `true` if there are no messages in whole tree.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+            && self.fields.values().all(|e| e.is_empty())
+            && self.items.values().all(|e| e.is_empty())
+    }
+
+    /// Flattens tree back into map keyed by normalized path (`lines[2].unit_price`).
+    pub fn to_flat(&self) -> BTreeMap<String, Vec<String>> {
+        let mut flat = BTreeMap::new();
+        self.flatten_into(String::new(), &mut flat);
+        flat
+    }
+
+    fn flatten_into(&self, path: String, flat: &mut BTreeMap<String, Vec<String>>) {
+        if !self.messages.is_empty() {
+            flat.insert(path.clone(), self.messages.clone());
+        }
+        for (name, errors) in &self.fields {
+            let field_path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{}.{}", path, name)
+            };
+            errors.flatten_into(field_path, flat);
+        }
+        for (index, errors) in &self.items {
+            errors.flatten_into(format!("{}[{}]", path, index), flat);
+        }
+    }
+
+    /// Returns copy of tree with messages translated by given function. Function gets
+    /// normalized path of field and original message, e.g. for localization of messages.
+    pub fn localize<F>(&self, translate: F) -> Self
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        let mut localized = Self::default();
+        for (path, messages) in self.to_flat() {
+            localized.insert(&path, messages.iter().map(|m| translate(&path, m)));
+        }
+        localized
+    }
+}
+
 /// If something goes wrong this error wil bew returned.
 #[derive(Debug)]
 pub struct FakturoidError {
@@ -54,6 +660,11 @@ impl FakturoidError {
         self.inner_request
     }
 
+    /// Reference to underlying error from reqwest library if there is any.
+    pub fn request_err(&self) -> Option<&Error> {
+        self.inner_request.as_ref()
+    }
+
     /// Transforms this object into std::error::Error.
     pub fn into_std_err(self) -> Box<dyn StdError> {
         assert!(
@@ -84,6 +695,12 @@ impl FakturoidError {
         self.data_errors.as_ref()
     }
 
+    /// If fakturoid.cz API returns JSON with errors (status 422) method returns these errors
+    /// arranged into tree by field path, see [`FieldErrors`](struct.FieldErrors.html).
+    pub fn field_errors(&self) -> Option<FieldErrors> {
+        self.data_errors.as_ref().map(FieldErrors::from_data_errors)
+    }
+
     pub(crate) fn from_std_err<E>(err: E) -> Self
     where
         E: StdError + 'static,
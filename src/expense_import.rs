@@ -0,0 +1,249 @@
+//! Import of historic expenses (received invoices) from CSV file. Available with `csv` feature.
+//!
+//! CSV file must have header row, columns are matched by name and their order doesn't matter.
+//! Recognized columns are `original_number` (number of document assigned by supplier, required),
+//! `supplier_name`, `supplier_street`, `supplier_city`, `supplier_zip`, `supplier_country`,
+//! `supplier_registration_no`, `supplier_vat_no`, `variable_symbol`, `issued_on`,
+//! `taxable_fulfillment_due`, `due_on`, `paid_on`, `currency`, `description`, `amount` (price
+//! without VAT, required), `vat_rate` and `tags` (separated by semicolon). Dates are in
+//! `YYYY-MM-DD` format. Other columns are ignored.
+//!
+//! Every row is imported as expense with one line. Expense is identified by its supplier
+//! (`supplier_registration_no`, or `supplier_name` if registration number is missing) and
+//! `original_number`, expenses which already exist in account are skipped. Documents of
+//! different suppliers may have the same number.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::migration::Importer;
+//! use std::fs::File;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let importer = Importer::new(client).resume_file("expenses.resume");
+//!     let report = importer
+//!         .import_expenses_csv(File::open("expenses.csv")?)
+//!         .await?;
+//!     println!("{} created, {} skipped", report.created(), report.skipped());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::currency::Currency;
+use crate::dates::{self, Date};
+use crate::error::FakturoidError;
+use crate::migration::{ImportItem, ImportOutcome, ImportReport, ImportResult, Importer};
+use crate::models::{Expense, ExpenseLine};
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+
+/// One row of CSV file.
+#[derive(Debug, Deserialize)]
+struct ExpenseRow {
+    original_number: String,
+    #[serde(default)]
+    supplier_name: Option<String>,
+    #[serde(default)]
+    supplier_street: Option<String>,
+    #[serde(default)]
+    supplier_city: Option<String>,
+    #[serde(default)]
+    supplier_zip: Option<String>,
+    #[serde(default)]
+    supplier_country: Option<String>,
+    #[serde(default)]
+    supplier_registration_no: Option<String>,
+    #[serde(default)]
+    supplier_vat_no: Option<String>,
+    #[serde(default)]
+    variable_symbol: Option<String>,
+    #[serde(default, with = "dates::option_date")]
+    issued_on: Option<Date>,
+    #[serde(default, with = "dates::option_date")]
+    taxable_fulfillment_due: Option<Date>,
+    #[serde(default, with = "dates::option_date")]
+    due_on: Option<Date>,
+    #[serde(default, with = "dates::option_date")]
+    paid_on: Option<Date>,
+    #[serde(default)]
+    currency: Option<Currency>,
+    #[serde(default)]
+    description: Option<String>,
+    amount: Decimal,
+    #[serde(default)]
+    vat_rate: Option<Decimal>,
+    #[serde(default)]
+    tags: Option<String>,
+}
+
+impl ExpenseRow {
+    fn into_expense(self) -> Expense {
+        let line_name = self
+            .description
+            .clone()
+            .unwrap_or_else(|| self.original_number.clone());
+        let mut expense = Expense::default();
+        expense.original_number = Some(self.original_number);
+        expense.supplier_name = self.supplier_name;
+        expense.supplier_street = self.supplier_street;
+        expense.supplier_city = self.supplier_city;
+        expense.supplier_zip = self.supplier_zip;
+        expense.supplier_country = self.supplier_country;
+        expense.supplier_registration_no = self.supplier_registration_no;
+        expense.supplier_vat_no = self.supplier_vat_no;
+        expense.variable_symbol = self.variable_symbol;
+        expense.issued_on = self.issued_on;
+        expense.taxable_fulfillment_due = self.taxable_fulfillment_due;
+        expense.due_on = self.due_on;
+        expense.paid_on = self.paid_on;
+        expense.currency = self.currency;
+        expense.description = self.description;
+        expense.tags = self.tags.map(|tags| {
+            tags.split(';')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        });
+        expense.lines = Some(vec![ExpenseLine::new(
+            &line_name,
+            Decimal::ONE,
+            None,
+            self.amount,
+            self.vat_rate.unwrap_or_default(),
+        )]);
+        expense
+    }
+}
+
+/// Parses expenses from CSV file, see [module](index.html) documentation for columns. Error
+/// contains line of first invalid row.
+pub fn parse_expenses_csv<R: Read>(reader: R) -> Result<Vec<Expense>, FakturoidError> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader)
+        .deserialize::<ExpenseRow>()
+        .map(|row| {
+            row.map(ExpenseRow::into_expense)
+                .map_err(FakturoidError::from_std_err)
+        })
+        .collect()
+}
+
+impl Importer {
+    /// Imports expenses from CSV file, see [module](../expense_import/index.html)
+    /// documentation. Expenses are keyed by supplier and `original_number`, those which already
+    /// exist in account are reported as skipped.
+    pub async fn import_expenses_csv<R: Read>(
+        &self,
+        reader: R,
+    ) -> Result<ImportReport, FakturoidError> {
+        let expenses = parse_expenses_csv(reader)?;
+        let listed: Vec<Expense> = self
+            .client()
+            .list::<Expense>(None)
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?;
+        let mut existing = ExistingExpenses::default();
+        for expense in &listed {
+            existing.insert(expense);
+        }
+
+        let mut duplicates = Vec::new();
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+        for expense in expenses {
+            let key = expense_key(&expense);
+            if let Some(id) = existing.find(&expense) {
+                duplicates.push(ImportResult {
+                    key,
+                    outcome: ImportOutcome::Skipped(id),
+                });
+            } else if !seen.insert(key.clone()) {
+                duplicates.push(ImportResult {
+                    key,
+                    outcome: ImportOutcome::Failed {
+                        message: "Duplicate supplier and original_number in CSV file".to_string(),
+                        attempts: 0,
+                    },
+                });
+            } else {
+                items.push(ImportItem::expense(&key, expense));
+            }
+        }
+        let mut report = self.run(items).await?;
+        duplicates.append(&mut report.results);
+        report.results = duplicates;
+        Ok(report)
+    }
+}
+
+/// Expenses in account indexed by supplier and number of document.
+#[derive(Default)]
+struct ExistingExpenses {
+    /// Ids by supplier's registration number and number of document.
+    by_registration_no: HashMap<(String, String), i32>,
+    /// Ids and supplier's registration numbers by lowercase supplier's name and number of
+    /// document.
+    by_name: HashMap<(String, String), (Option<String>, i32)>,
+}
+
+impl ExistingExpenses {
+    fn insert(&mut self, expense: &Expense) {
+        let id = match expense.id {
+            Some(id) => id,
+            None => return,
+        };
+        let number = trimmed(&expense.original_number).unwrap_or_default();
+        let registration_no = trimmed(&expense.supplier_registration_no);
+        if let Some(registration_no) = registration_no {
+            self.by_registration_no
+                .insert((registration_no.to_string(), number.to_string()), id);
+        }
+        let name = trimmed(&expense.supplier_name).unwrap_or_default();
+        self.by_name.insert(
+            (name.to_lowercase(), number.to_string()),
+            (registration_no.map(str::to_string), id),
+        );
+    }
+
+    /// Id of the same expense: with the same number from supplier with the same registration
+    /// number, or with the same name if registration number of one of them is unknown.
+    fn find(&self, expense: &Expense) -> Option<i32> {
+        let number = trimmed(&expense.original_number).unwrap_or_default();
+        let registration_no = trimmed(&expense.supplier_registration_no);
+        if let Some(registration_no) = registration_no {
+            let key = (registration_no.to_string(), number.to_string());
+            if let Some(id) = self.by_registration_no.get(&key) {
+                return Some(*id);
+            }
+        }
+        let name = trimmed(&expense.supplier_name).unwrap_or_default();
+        match self.by_name.get(&(name.to_lowercase(), number.to_string())) {
+            Some((Some(_), _)) if registration_no.is_some() => None,
+            Some((_, id)) => Some(*id),
+            None => None,
+        }
+    }
+}
+
+/// Key of expense in import report and resume file, e.g. `12345678/FV-001`.
+fn expense_key(expense: &Expense) -> String {
+    let supplier = trimmed(&expense.supplier_registration_no)
+        .or_else(|| trimmed(&expense.supplier_name))
+        .unwrap_or_default();
+    let number = trimmed(&expense.original_number).unwrap_or_default();
+    format!("{}/{}", supplier, number)
+}
+
+fn trimmed(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
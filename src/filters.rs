@@ -1,6 +1,34 @@
+//! Filters of lists. Each entity which can be filtered has its own filter which offers only
+//! parameters accepted by API for that entity, e.g. `InvoiceFilters` or `SubjectFilters`.
+//! Typed filters are used by `Fakturoid::list_filtered()` or converted into `Filter` by
+//! `into()`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::filters::InvoiceFilters;
+//! use fakturoid::models::InvoiceState;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let invoices = client
+//!         .list_filtered(InvoiceFilters::new().status(InvoiceState::Overdue).subject_id(5))
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::Entity;
+use crate::dates::{self, DateTime};
+use crate::models::{
+    Event, Expense, ExpenseState, Generator, InventoryItem, Invoice, InvoiceDocumentType,
+    InvoiceState, Subject, Todo,
+};
 use std::collections::HashMap;
-use chrono::{DateTime, Local};
-use crate::models::InvoiceState;
+use std::fmt::{self, Formatter};
 
 /// Filter builder trait for implement concrete filtering.
 pub trait FilterBuilder {
@@ -8,13 +36,20 @@ pub trait FilterBuilder {
     fn build(&self, filter: Filter) -> HashMap<String, String>;
 }
 
-/// Common filter struct.
-#[derive(Default, Clone)]
+/// Common filter struct. Parameters which aren't accepted by entity are silently dropped when
+/// list is requested, typed filters of entities (e.g. `InvoiceFilters`) should be used instead.
+/// `Filter::default()` remains the way to build filters of custom entities, which have no typed
+/// filter.
+#[derive(Default, Clone, Debug)]
 pub struct Filter {
     query_map: HashMap<String, String>,
 }
 
 impl Filter {
+    #[deprecated(
+        since = "0.1.4",
+        note = "use typed filter of entity, e.g. `InvoiceFilters`, `SubjectFilters` or `ExpenseFilters`"
+    )]
     pub fn new() -> Self {
         Self::default()
     }
@@ -25,15 +60,17 @@ impl Filter {
         self
     }
 
-    pub fn since(mut self, since: DateTime<Local>) -> Self {
+    pub fn since(mut self, since: DateTime) -> Self {
         self.query_map
-            .insert("since".to_string(), since.to_rfc3339());
+            .insert("since".to_string(), dates::format_datetime(&since));
         self
     }
 
-    pub fn updated_since(mut self, upd_since: DateTime<Local>) -> Self {
-        self.query_map
-            .insert("updated_since".to_string(), upd_since.to_rfc3339());
+    pub fn updated_since(mut self, upd_since: DateTime) -> Self {
+        self.query_map.insert(
+            "updated_since".to_string(),
+            dates::format_datetime(&upd_since),
+        );
         self
     }
 
@@ -43,15 +80,17 @@ impl Filter {
         self
     }
 
-    pub fn until(mut self, until: DateTime<Local>) -> Self {
+    pub fn until(mut self, until: DateTime) -> Self {
         self.query_map
-            .insert("until".to_string(), until.to_rfc3339());
+            .insert("until".to_string(), dates::format_datetime(&until));
         self
     }
 
-    pub fn updated_until(mut self, upd_until: DateTime<Local>) -> Self {
-        self.query_map
-            .insert("updated_until".to_string(), upd_until.to_rfc3339());
+    pub fn updated_until(mut self, upd_until: DateTime) -> Self {
+        self.query_map.insert(
+            "updated_until".to_string(),
+            dates::format_datetime(&upd_until),
+        );
         self
     }
 
@@ -67,20 +106,104 @@ impl Filter {
         self
     }
 
+    /// Status of expenses, use `status()` for invoices.
+    pub fn expense_status(mut self, status: ExpenseState) -> Self {
+        self.query_map
+            .insert("status".to_string(), status.to_string());
+        self
+    }
+
     pub fn subject_id(mut self, id: i32) -> Self {
         self.query_map
             .insert("subject_id".to_string(), format!("{}", id));
         self
     }
 
+    /// Stock keeping unit of inventory items.
+    pub fn sku(mut self, sku: &str) -> Self {
+        self.query_map.insert("sku".to_string(), sku.to_string());
+        self
+    }
+
+    /// Article number (EAN, ISBN) of inventory items.
+    pub fn article_number(mut self, article_number: &str) -> Self {
+        self.query_map
+            .insert("article_number".to_string(), article_number.to_string());
+        self
+    }
+
+    /// Type of invoices, API v3 only. Type of invoice document can be given too, see
+    /// [`DocumentType`](enum.DocumentType.html).
+    pub fn document_type(mut self, document_type: impl Into<DocumentType>) -> Self {
+        self.query_map.insert(
+            "document_type".to_string(),
+            document_type.into().to_string(),
+        );
+        self
+    }
+
     pub fn is_empty(&self) -> bool {
         self.query_map.is_empty()
     }
+
+    /// Transforms filter into query parameters. Useful for custom filter builders.
+    pub fn into_query(self) -> HashMap<String, String> {
+        self.query_map
+    }
+}
+
+/// Type of invoices requested by `InvoiceFilters::document_type()`, filter is supported by API
+/// v3 only. It's coarser than [`InvoiceDocumentType`](../models/enum.InvoiceDocumentType.html)
+/// of invoice, which is converted to it, e.g. both partial proforma and proforma are
+/// `Proforma`. Unknown types of invoice documents are converted to `Regular`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DocumentType {
+    /// Regular invoices, including final invoices.
+    Regular,
+    /// Proformas, including partial proformas.
+    Proforma,
+    /// Corrective tax documents.
+    Correction,
+    /// Tax documents for received payments.
+    TaxDocument,
+}
+
+impl From<InvoiceDocumentType> for DocumentType {
+    fn from(document_type: InvoiceDocumentType) -> Self {
+        match document_type {
+            InvoiceDocumentType::Invoice
+            | InvoiceDocumentType::FinalInvoice
+            | InvoiceDocumentType::Other => DocumentType::Regular,
+            InvoiceDocumentType::Proforma | InvoiceDocumentType::PartialProforma => {
+                DocumentType::Proforma
+            }
+            InvoiceDocumentType::Correction => DocumentType::Correction,
+            InvoiceDocumentType::TaxDocument => DocumentType::TaxDocument,
+        }
+    }
 }
 
-pub(crate) struct NoneFilter;
+impl fmt::Display for DocumentType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DocumentType::Regular => "regular",
+            DocumentType::Proforma => "proforma",
+            DocumentType::Correction => "correction",
+            DocumentType::TaxDocument => "tax_document",
+        })
+    }
+}
+
+/// Filter builder which ignores all filter parameters.
+pub struct NoneFilter;
+/// Filter builder which passes all filter parameters to API.
+pub struct AllFilter;
 pub(crate) struct SubjectFilter;
-pub(crate) struct InvoiceFilter;
+pub(crate) struct EventFilter;
+pub(crate) struct GeneratorFilter;
+pub(crate) struct TodoFilter;
+pub(crate) struct InventoryItemFilter;
+pub(crate) struct InventoryMoveFilter;
 
 impl FilterBuilder for NoneFilter {
     fn build(&self, _filter: Filter) -> HashMap<String, String> {
@@ -99,14 +222,236 @@ impl FilterBuilder for SubjectFilter {
                     && *f.0 != "updated_until"
                     && *f.0 != "number"
                     && *f.0 != "status"
+                    && *f.0 != "document_type"
             })
             .map(|f| (f.0.clone(), f.1.clone()))
             .collect()
     }
 }
 
-impl FilterBuilder for InvoiceFilter {
+impl FilterBuilder for AllFilter {
     fn build(&self, filter: Filter) -> HashMap<String, String> {
         filter.query_map
     }
 }
+
+impl FilterBuilder for EventFilter {
+    fn build(&self, filter: Filter) -> HashMap<String, String> {
+        filter
+            .query_map
+            .into_iter()
+            .filter(|(key, _)| key == "page" || key == "since" || key == "subject_id")
+            .collect()
+    }
+}
+
+impl FilterBuilder for GeneratorFilter {
+    fn build(&self, filter: Filter) -> HashMap<String, String> {
+        filter
+            .query_map
+            .into_iter()
+            .filter(|(key, _)| {
+                key == "page" || key == "since" || key == "updated_since" || key == "subject_id"
+            })
+            .collect()
+    }
+}
+
+/// Parameters accepted by both inventory endpoints.
+fn is_period_param(key: &str) -> bool {
+    key == "page"
+        || key == "since"
+        || key == "until"
+        || key == "updated_since"
+        || key == "updated_until"
+}
+
+impl FilterBuilder for InventoryItemFilter {
+    fn build(&self, filter: Filter) -> HashMap<String, String> {
+        filter
+            .query_map
+            .into_iter()
+            .filter(|(key, _)| is_period_param(key) || key == "sku" || key == "article_number")
+            .collect()
+    }
+}
+
+impl FilterBuilder for InventoryMoveFilter {
+    fn build(&self, filter: Filter) -> HashMap<String, String> {
+        filter
+            .query_map
+            .into_iter()
+            .filter(|(key, _)| is_period_param(key))
+            .collect()
+    }
+}
+
+impl FilterBuilder for TodoFilter {
+    fn build(&self, filter: Filter) -> HashMap<String, String> {
+        filter
+            .query_map
+            .into_iter()
+            .filter(|(key, _)| key == "page" || key == "since")
+            .collect()
+    }
+}
+
+/// Typed filter of entity, see `Fakturoid::list_filtered()`.
+pub trait EntityFilter: Into<Filter> {
+    /// Entity listed with this filter.
+    type Entity: Entity;
+}
+
+/// Generates methods of typed filter which pass parameter to `Filter`.
+macro_rules! filter_params {
+    ($($(#[$meta:meta])* $name:ident($arg:ident: $ty:ty);)*) => {
+        $(
+            $(#[$meta])*
+            pub fn $name(mut self, $arg: $ty) -> Self {
+                self.filter = self.filter.$name($arg);
+                self
+            }
+        )*
+    };
+}
+
+/// Generates typed filter of entity.
+macro_rules! entity_filter {
+    ($(#[$meta:meta])* $filter:ident, $entity:ty) => {
+        $(#[$meta])*
+        #[derive(Default, Clone, Debug)]
+        pub struct $filter {
+            filter: Filter,
+        }
+
+        impl $filter {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            filter_params! {
+                page(page: i32);
+            }
+        }
+
+        impl From<$filter> for Filter {
+            fn from(filter: $filter) -> Filter {
+                filter.filter
+            }
+        }
+
+        impl EntityFilter for $filter {
+            type Entity = $entity;
+        }
+    };
+}
+
+entity_filter!(
+    /// Filter of invoices.
+    InvoiceFilters,
+    Invoice
+);
+entity_filter!(
+    /// Filter of subjects.
+    SubjectFilters,
+    Subject
+);
+entity_filter!(
+    /// Filter of expenses.
+    ExpenseFilters,
+    Expense
+);
+entity_filter!(
+    /// Filter of recurring generators.
+    GeneratorFilters,
+    Generator
+);
+entity_filter!(
+    /// Filter of events.
+    EventFilters,
+    Event
+);
+entity_filter!(
+    /// Filter of todos.
+    TodoFilters,
+    Todo
+);
+entity_filter!(
+    /// Filter of inventory items.
+    InventoryItemFilters,
+    InventoryItem
+);
+
+impl InvoiceFilters {
+    filter_params! {
+        since(since: DateTime);
+        until(until: DateTime);
+        updated_since(updated_since: DateTime);
+        updated_until(updated_until: DateTime);
+        number(number: &str);
+        status(status: InvoiceState);
+        subject_id(subject_id: i32);
+        custom_id(custom_id: &str);
+        /// Only proformas, regular invoices, corrections or tax documents, API v3 only.
+        document_type(document_type: impl Into<DocumentType>);
+    }
+}
+
+impl SubjectFilters {
+    filter_params! {
+        since(since: DateTime);
+        updated_since(updated_since: DateTime);
+        custom_id(custom_id: &str);
+    }
+}
+
+impl ExpenseFilters {
+    filter_params! {
+        since(since: DateTime);
+        until(until: DateTime);
+        updated_since(updated_since: DateTime);
+        updated_until(updated_until: DateTime);
+        number(number: &str);
+        subject_id(subject_id: i32);
+        custom_id(custom_id: &str);
+    }
+
+    pub fn status(mut self, status: ExpenseState) -> Self {
+        self.filter = self.filter.expense_status(status);
+        self
+    }
+}
+
+impl GeneratorFilters {
+    filter_params! {
+        since(since: DateTime);
+        updated_since(updated_since: DateTime);
+        subject_id(subject_id: i32);
+    }
+}
+
+impl EventFilters {
+    filter_params! {
+        since(since: DateTime);
+        subject_id(subject_id: i32);
+    }
+}
+
+impl TodoFilters {
+    filter_params! {
+        since(since: DateTime);
+    }
+}
+
+impl InventoryItemFilters {
+    filter_params! {
+        since(since: DateTime);
+        until(until: DateTime);
+        updated_since(updated_since: DateTime);
+        updated_until(updated_until: DateTime);
+        /// Stock keeping unit.
+        sku(sku: &str);
+        /// Article number (EAN, ISBN).
+        article_number(article_number: &str);
+    }
+}
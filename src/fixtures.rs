@@ -0,0 +1,159 @@
+//! Real-world JSON documents as returned by fakturoid.cz API and helpers which check that models
+//! survive deserialization and serialization without losing any data.
+//!
+//! Fixtures can be used for testing custom code working with models, helpers can be used with
+//! any JSON document to detect changes in API shape which models don't cover.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::fixtures::{assert_round_trip, INVOICE};
+//! use fakturoid::models::Invoice;
+//!
+//! assert_round_trip::<Invoice>(INVOICE);
+//! ```
+
+use chrono::DateTime;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+/// Account detail (`account.json`).
+pub const ACCOUNT: &str = include_str!("../fixtures/account.json");
+/// Subject detail (`subjects/{id}.json`).
+pub const SUBJECT: &str = include_str!("../fixtures/subject.json");
+/// Invoice detail (`invoices/{id}.json`).
+pub const INVOICE: &str = include_str!("../fixtures/invoice.json");
+/// Invoice with prices including VAT, several VAT rates and rounded total
+/// (`invoices/{id}.json`).
+pub const INVOICE_ROUNDED: &str = include_str!("../fixtures/invoice_rounded.json");
+/// Expense detail (`expenses/{id}.json`).
+pub const EXPENSE: &str = include_str!("../fixtures/expense.json");
+/// Recurring generator detail (`generators/{id}.json`).
+pub const GENERATOR: &str = include_str!("../fixtures/generator.json");
+/// Current user with accessible accounts (`user.json`).
+pub const USER: &str = include_str!("../fixtures/user.json");
+
+/// Deserializes given JSON into model `T`, serializes it back and returns list of differences
+/// between original and serialized document. Empty list means that model is able to hold all
+/// data from the document.
+///
+/// Values are compared by their meaning, not by representation: decimal numbers can be strings
+/// or numbers, date times can be in different time zones and `null` values can be omitted.
+/// Received attachments are never sent back to API, so they can be omitted too.
+pub fn round_trip_differences<T>(json: &str) -> Vec<String>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let original: Value = match serde_json::from_str(json) {
+        Ok(val) => val,
+        Err(err) => return vec![format!("invalid JSON: {}", err)],
+    };
+    let model: T = match serde_json::from_value(original.clone()) {
+        Ok(model) => model,
+        Err(err) => return vec![format!("deserialization failed: {}", err)],
+    };
+    let serialized = match serde_json::to_value(&model) {
+        Ok(val) => val,
+        Err(err) => return vec![format!("serialization failed: {}", err)],
+    };
+
+    let mut differences = Vec::new();
+    compare("$", &original, &serialized, &mut differences);
+    differences
+}
+
+/// Panics with list of differences if given JSON doesn't survive round trip through model `T`.
+/// See [`round_trip_differences`](fn.round_trip_differences.html).
+pub fn assert_round_trip<T>(json: &str)
+where
+    T: Serialize + DeserializeOwned,
+{
+    let differences = round_trip_differences::<T>(json);
+    assert!(
+        differences.is_empty(),
+        "Round trip of {} is not lossless:\n{}",
+        std::any::type_name::<T>(),
+        differences.join("\n")
+    );
+}
+
+/// Fields which models deserialize but serialize only when they contain new data.
+const WRITE_ONLY_FIELDS: &[&str] = &["attachment", "attachments"];
+
+fn compare(path: &str, original: &Value, serialized: &Value, differences: &mut Vec<String>) {
+    match (original, serialized) {
+        (Value::Object(orig), Value::Object(ser)) => compare_maps(path, orig, ser, differences),
+        (Value::Array(orig), Value::Array(ser)) => {
+            if orig.len() != ser.len() {
+                differences.push(format!(
+                    "{}: array length {} became {}",
+                    path,
+                    orig.len(),
+                    ser.len()
+                ));
+            } else {
+                for (i, (o, s)) in orig.iter().zip(ser.iter()).enumerate() {
+                    compare(&format!("{}[{}]", path, i), o, s, differences);
+                }
+            }
+        }
+        (orig, ser) => {
+            if !scalars_equal(orig, ser) {
+                differences.push(format!("{}: {} became {}", path, orig, ser));
+            }
+        }
+    }
+}
+
+fn compare_maps(
+    path: &str,
+    original: &Map<String, Value>,
+    serialized: &Map<String, Value>,
+    differences: &mut Vec<String>,
+) {
+    for (key, orig) in original {
+        let field_path = format!("{}.{}", path, key);
+        match serialized.get(key) {
+            Some(ser) => compare(&field_path, orig, ser, differences),
+            None => {
+                if !orig.is_null() && !WRITE_ONLY_FIELDS.contains(&key.as_str()) {
+                    differences.push(format!("{}: {} was lost", field_path, orig))
+                }
+            }
+        }
+    }
+    for (key, ser) in serialized {
+        if !original.contains_key(key) && !ser.is_null() {
+            differences.push(format!("{}.{}: {} was added", path, key, ser));
+        }
+    }
+}
+
+fn scalars_equal(original: &Value, serialized: &Value) -> bool {
+    if original == serialized {
+        return true;
+    }
+    if let (Some(orig), Some(ser)) = (as_decimal(original), as_decimal(serialized)) {
+        return orig == ser;
+    }
+    if let (Value::String(orig), Value::String(ser)) = (original, serialized) {
+        if let (Ok(orig), Ok(ser)) = (
+            DateTime::parse_from_rfc3339(orig),
+            DateTime::parse_from_rfc3339(ser),
+        ) {
+            return orig == ser;
+        }
+    }
+    false
+}
+
+fn as_decimal(value: &Value) -> Option<Decimal> {
+    match value {
+        Value::Number(num) => Decimal::from_str(&num.to_string()).ok(),
+        Value::String(s) => Decimal::from_str(s).ok(),
+        _ => None,
+    }
+}
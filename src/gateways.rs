@@ -0,0 +1,156 @@
+//! Validation of online payment gateways (PayPal, GoPay) enabled on invoices against account
+//! settings, so misconfigured invoice is rejected with descriptive error before it is sent.
+
+use crate::client::Fakturoid;
+use crate::currency::Currency;
+use crate::error::{FakturoidError, InvalidPaymentGateway};
+use crate::models::{Account, Invoice};
+use std::fmt;
+
+/// Currencies accepted by PayPal.
+const PAYPAL_CURRENCIES: &[&str] = &[
+    "AUD", "BRL", "CAD", "CHF", "CNY", "CZK", "DKK", "EUR", "GBP", "HKD", "HUF", "ILS", "JPY",
+    "MXN", "MYR", "NOK", "NZD", "PHP", "PLN", "SEK", "SGD", "THB", "TWD", "USD",
+];
+
+/// Currencies accepted by GoPay.
+const GOPAY_CURRENCIES: &[&str] = &["BGN", "CZK", "EUR", "GBP", "HUF", "PLN", "RON", "USD"];
+
+/// Online payment gateway which can be offered on invoice.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaymentGateway {
+    PayPal,
+    GoPay,
+}
+
+impl PaymentGateway {
+    /// ISO codes of currencies accepted by gateway.
+    pub fn currencies(self) -> &'static [&'static str] {
+        match self {
+            PaymentGateway::PayPal => PAYPAL_CURRENCIES,
+            PaymentGateway::GoPay => GOPAY_CURRENCIES,
+        }
+    }
+
+    /// Whether gateway accepts payments in given currency.
+    pub fn supports_currency(self, currency: Currency) -> bool {
+        self.currencies().iter().any(|c| currency == *c)
+    }
+}
+
+impl fmt::Display for PaymentGateway {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PaymentGateway::PayPal => "PayPal",
+            PaymentGateway::GoPay => "GoPay",
+        })
+    }
+}
+
+/// Payment gateway related part of account settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GatewaySettings {
+    pub paypal: bool,
+    pub gopay: bool,
+    /// Default currency of account, used for invoices without currency.
+    pub currency: Currency,
+}
+
+impl GatewaySettings {
+    pub fn from_account(account: &Account) -> Self {
+        Self {
+            paypal: account.invoice_paypal,
+            gopay: account.invoice_gopay,
+            currency: account.currency,
+        }
+    }
+
+    /// Checks that gateway is enabled in account and supports given currency (or default
+    /// currency of account if `None`).
+    pub fn check(
+        &self,
+        gateway: PaymentGateway,
+        currency: Option<Currency>,
+    ) -> Result<(), InvalidPaymentGateway> {
+        let enabled = match gateway {
+            PaymentGateway::PayPal => self.paypal,
+            PaymentGateway::GoPay => self.gopay,
+        };
+        if !enabled {
+            return Err(InvalidPaymentGateway::new(gateway, None));
+        }
+        let currency = currency.unwrap_or(self.currency);
+        if !gateway.supports_currency(currency) {
+            return Err(InvalidPaymentGateway::new(gateway, Some(currency.as_str())));
+        }
+        Ok(())
+    }
+
+    /// Checks all gateways enabled on invoice, see [`check()`](#method.check).
+    pub fn validate(&self, invoice: &Invoice) -> Result<(), InvalidPaymentGateway> {
+        let currency = invoice.currency;
+        if invoice.paypal == Some(true) {
+            self.check(PaymentGateway::PayPal, currency)?;
+        }
+        if invoice.gopay == Some(true) {
+            self.check(PaymentGateway::GoPay, currency)?;
+        }
+        Ok(())
+    }
+}
+
+impl Fakturoid {
+    /// Payment gateway settings of account, see `cached_account()`.
+    pub async fn gateway_settings(&self) -> Result<GatewaySettings, FakturoidError> {
+        let account = self.cached_account().await?;
+        Ok(GatewaySettings::from_account(&account))
+    }
+
+    /// Checks that payment gateways enabled on invoice are enabled in account settings and
+    /// support currency of invoice.
+    pub async fn validate_payment_gateways(&self, invoice: &Invoice) -> Result<(), FakturoidError> {
+        self.gateway_settings()
+            .await?
+            .validate(invoice)
+            .map_err(FakturoidError::from_std_err)
+    }
+
+    /// Enables payment gateway on invoice if it is enabled in account settings and supports
+    /// currency of invoice. Invoice is not changed otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::currency::Currency;
+    /// use fakturoid::gateways::PaymentGateway;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let mut invoice = Invoice::default();
+    ///     invoice.subject_id = Some(16);
+    ///     invoice.currency = Some(Currency::EUR);
+    ///     client.enable_payment_gateway(&mut invoice, PaymentGateway::GoPay).await?;
+    ///     let invoice = client.create(invoice).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn enable_payment_gateway(
+        &self,
+        invoice: &mut Invoice,
+        gateway: PaymentGateway,
+    ) -> Result<(), FakturoidError> {
+        self.gateway_settings()
+            .await?
+            .check(gateway, invoice.currency)
+            .map_err(FakturoidError::from_std_err)?;
+        match gateway {
+            PaymentGateway::PayPal => invoice.paypal = Some(true),
+            PaymentGateway::GoPay => invoice.gopay = Some(true),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+//! Invoice actions with payloads checked by compiler, shortcuts for `Fakturoid::action()`.
+
+use crate::client::Fakturoid;
+use crate::error::FakturoidError;
+use crate::models::{InvoiceAction, InvoicePayData};
+
+impl Fakturoid {
+    /// Marks invoice as sent without sending email.
+    pub async fn mark_as_sent(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::MarkAsSent, None::<()>).await
+    }
+
+    /// Sends invoice to client by email.
+    pub async fn deliver(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::Deliver, None::<()>).await
+    }
+
+    /// Marks invoice as paid.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::InvoicePayData;
+    /// use rust_decimal::Decimal;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let payment = InvoicePayData {
+    ///         paid_amount: Some(Decimal::from(1500)),
+    ///         ..InvoicePayData::default()
+    ///     };
+    ///     client.pay(27, payment).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn pay(&self, id: i32, data: InvoicePayData) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::Pay, Some(data)).await
+    }
+
+    /// Marks proforma as paid, tax document is created.
+    pub async fn pay_proforma(&self, id: i32, data: InvoicePayData) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::PayProforma, Some(data))
+            .await
+    }
+
+    /// Marks partial proforma as paid.
+    pub async fn pay_partial_proforma(
+        &self,
+        id: i32,
+        data: InvoicePayData,
+    ) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::PayPartialProforma, Some(data))
+            .await
+    }
+
+    /// Removes payment of invoice.
+    pub async fn remove_payment(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::RemovePayment, None::<()>)
+            .await
+    }
+
+    /// Sends payment reminder of overdue invoice.
+    pub async fn deliver_reminder(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::DeliverReminder, None::<()>)
+            .await
+    }
+
+    /// Cancels invoice.
+    pub async fn cancel(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::Cancel, None::<()>).await
+    }
+
+    /// Reverts cancellation of invoice.
+    pub async fn undo_cancel(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::UndoCancel, None::<()>).await
+    }
+
+    /// Locks invoice, so it can't be changed.
+    pub async fn lock(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::Lock, None::<()>).await
+    }
+
+    /// Unlocks invoice.
+    pub async fn unlock(&self, id: i32) -> Result<(), FakturoidError> {
+        self.action(id, InvoiceAction::Unlock, None::<()>).await
+    }
+}
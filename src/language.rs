@@ -0,0 +1,107 @@
+//! Selection of invoice language for subjects.
+//!
+//! Language is derived from country of subject by default, e.g. German and Austrian customers
+//! get German invoices. Default can be changed for whole countries or single subjects.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::language::LanguageSelector;
+//! use fakturoid::models::{Invoice, InvoiceLanguage, Subject};
+//!
+//! let selector = LanguageSelector::new(InvoiceLanguage::En)
+//!     .country("CH", InvoiceLanguage::Fr)
+//!     .subject(16, InvoiceLanguage::Cz);
+//!
+//! let subject = Subject {
+//!     id: Some(20),
+//!     country: Some("AT".to_string()),
+//!     ..Subject::default()
+//! };
+//! assert_eq!(selector.select(&subject), InvoiceLanguage::De);
+//!
+//! let invoice = Invoice::for_subject_with(&subject, &selector);
+//! assert_eq!(invoice.subject_id, Some(20));
+//! assert_eq!(invoice.language, Some(InvoiceLanguage::De));
+//! ```
+
+use crate::models::{InvoiceLanguage, Subject};
+use std::collections::HashMap;
+
+/// Language usually spoken in country with given ISO 3166 code, `None` if there is no
+/// supported language for the country.
+pub fn language_for_country(country: &str) -> Option<InvoiceLanguage> {
+    let language = match country.trim().to_uppercase().as_str() {
+        "CZ" => InvoiceLanguage::Cz,
+        "SK" => InvoiceLanguage::Sk,
+        "DE" | "AT" | "CH" | "LI" => InvoiceLanguage::De,
+        "FR" | "MC" => InvoiceLanguage::Fr,
+        "IT" | "SM" | "VA" => InvoiceLanguage::It,
+        "ES" | "MX" | "AR" | "CO" | "CL" | "PE" => InvoiceLanguage::Es,
+        "RU" | "BY" | "KZ" => InvoiceLanguage::Ru,
+        "HU" => InvoiceLanguage::Hu,
+        "PL" => InvoiceLanguage::Pl,
+        "RO" | "MD" => InvoiceLanguage::Ro,
+        "GB" | "IE" | "US" | "CA" | "AU" | "NZ" => InvoiceLanguage::En,
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Selects invoice language for subject. Rules are applied in order: language set for the
+/// subject, language set for country of subject, language spoken in the country and fallback
+/// language.
+#[derive(Clone, Debug)]
+pub struct LanguageSelector {
+    fallback: InvoiceLanguage,
+    countries: HashMap<String, InvoiceLanguage>,
+    subjects: HashMap<i32, InvoiceLanguage>,
+}
+
+impl LanguageSelector {
+    /// Creates selector with given fallback language used for subjects without country or from
+    /// countries without supported language.
+    pub fn new(fallback: InvoiceLanguage) -> Self {
+        Self {
+            fallback,
+            countries: HashMap::new(),
+            subjects: HashMap::new(),
+        }
+    }
+
+    /// Uses given language for all subjects from country.
+    pub fn country(mut self, country: &str, language: InvoiceLanguage) -> Self {
+        self.countries
+            .insert(country.trim().to_uppercase(), language);
+        self
+    }
+
+    /// Uses given language for subject with given id.
+    pub fn subject(mut self, subject_id: i32, language: InvoiceLanguage) -> Self {
+        self.subjects.insert(subject_id, language);
+        self
+    }
+
+    /// Language of invoices for given subject.
+    pub fn select(&self, subject: &Subject) -> InvoiceLanguage {
+        if let Some(language) = subject.id.and_then(|id| self.subjects.get(&id)) {
+            return *language;
+        }
+        let country = match subject.country.as_deref() {
+            Some(country) => country,
+            None => return self.fallback,
+        };
+        self.countries
+            .get(&country.trim().to_uppercase())
+            .copied()
+            .or_else(|| language_for_country(country))
+            .unwrap_or(self.fallback)
+    }
+}
+
+/// Selector with Czech as fallback language, which is the default language of fakturoid.cz.
+impl Default for LanguageSelector {
+    fn default() -> Self {
+        Self::new(InvoiceLanguage::Cz)
+    }
+}
@@ -4,20 +4,120 @@
 //!
 //! ## Features
 //!
-//! - Account detail
+//! - Account detail and settings
 //! - Subjects: create, update, delete, list, filters and fulltext
 //! - Invoices: create, update, delete, list, filters and fulltext, invoice actions
+//! - Expenses: create, update, delete, list, filters and fulltext, expense actions and payments
+//! - Generators: create, update, delete, list of all, recurring or template generators,
+//!   activation and deactivation of recurring generators
+//! - Inventory items and inventory moves: create, update, delete, list and filters
+//! - Bank accounts
+//! - Number formats of invoices
+//! - Current user and users of account
+//! - Switching between accounts accessible by the same credentials
+//! - Incremental synchronization of subjects, invoices and expenses
+//! - Events (activity feed) and todos
+//! - Webhooks: create, update, delete, list
+//! - Authentication by API key or OAuth 2.0 client credentials
+//! - API v2 and v3
+//! - Mock transport for offline tests of integrations
+//! - Endpoints not covered by this library through own entity types and raw requests
+//!
+//! ## Cargo features
+//!
+//! - `arbitrary-precision`: monetary amounts sent by API as JSON numbers are deserialized from
+//!   their textual representation, so they never pass through `f64` and can't lose precision.
+//!   Amounts are always serialized as strings.
+//! - `time`: date and date time fields use types from `time` crate instead of `chrono`,
+//!   see [`dates`](dates/index.html) module.
+//! - `log`: HTTP requests and responses are logged on debug level through
+//!   [`log`](https://crates.io/crates/log) facade.
+//! - `blocking`: blocking client for programs without asynchronous runtime, see
+//!   [`blocking`](blocking/index.html) module.
+//! - `cache`: GET responses can be kept in memory or persisted on disk and revalidated with
+//!   `ETag`, see `FakturoidBuilder::memory_cache()` and `FakturoidBuilder::disk_cache()`.
+//! - `scheduler`: recurring jobs planned by cron-like expressions, see
+//!   [`scheduler`](scheduler/index.html) module.
+//! - `zip`: invoice PDFs can be downloaded into ZIP archive, see [`archive`](archive/index.html)
+//!   module.
+//! - `csv`: historic expenses can be imported from CSV file, see
+//!   [`expense_import`](expense_import/index.html) module, and subjects and invoices can be
+//!   exported to and imported from CSV files, see [`csv_io`](csv_io/index.html) module.
+//! - `devtools`: generator of demo data for development accounts, see
+//!   [`devtools`](devtools/index.html) module.
+//! - `performance`: pages of large lists can be parsed into lightweight views borrowing from
+//!   response body, see [`views`](views/index.html) module.
+//! - `qr`: payload of payment QR code (Czech QR Platba or EPC QR code) can be generated from
+//!   invoice, see [`qr`](qr/index.html) module.
+//! - `ares`: subjects can be filled from Czech public registry ARES by registration number,
+//!   see [`ares`](ares/index.html) module.
 
 pub mod models;
+mod account_cache;
+pub mod archive;
+#[cfg(feature = "ares")]
+pub mod ares;
+pub mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod builders;
 pub mod client;
+#[cfg(feature = "csv")]
+pub mod csv_io;
+pub mod currency;
+pub mod dates;
+#[cfg(feature = "devtools")]
+pub mod devtools;
+mod dump;
+pub mod duplicates;
 pub mod error;
+#[cfg(feature = "csv")]
+pub mod expense_import;
 pub mod filters;
+pub mod fixtures;
+pub mod gateways;
+mod invoice_actions;
+pub mod language;
+pub mod migration;
+#[cfg(feature = "qr")]
+pub mod qr;
+mod queries;
+mod queue;
+pub mod render;
+#[cfg(feature = "cache")]
+mod response_cache;
+mod response_meta;
+mod retry;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+mod shutdown;
+mod streaming;
+mod subject_cache;
+pub mod sync;
+pub mod tax;
+pub mod terms;
+pub mod transport;
+#[cfg(feature = "performance")]
+pub mod views;
+pub mod webhooks;
 
 #[cfg(test)]
 mod tests {
     use crate::client::Fakturoid;
-    use crate::error::Kind;
-    use crate::models::Invoice;
+    use crate::currency::Currency;
+    use crate::error::{FieldErrors, Kind};
+    use crate::fixtures::{
+        assert_round_trip, ACCOUNT, EXPENSE, GENERATOR, INVOICE, INVOICE_ROUNDED, SUBJECT, USER,
+    };
+    use crate::models::{
+        Account, Expense, ExpenseState, Generator, Invoice, InvoiceLanguage, Subject,
+    };
+    use crate::queue::{Priority, RequestQueue};
+    use crate::render::{render_invoice, TextFormat};
+    use crate::tax::TaxPointRule;
+    use crate::terms::PaymentTerms;
+    use std::convert::TryFrom;
+    use std::time::Duration;
 
     #[test]
     fn test_connect() {
@@ -44,4 +144,2984 @@ mod tests {
         assert!(ser.is_ok());
         assert_eq!(ser.unwrap().as_str(), "{\"note\":\"Some note\"}");
     }
+
+    #[test]
+    fn test_invoice_lines() {
+        use crate::models::{InvoiceLine, VatPriceMode};
+        use rust_decimal::Decimal;
+
+        let mut invoice = Invoice::default();
+        invoice.add_line(InvoiceLine::new(
+            "Work",
+            Decimal::new(15, 1),
+            Some("h"),
+            Decimal::from(1000),
+            21,
+        ));
+        invoice.add_line(InvoiceLine::new(
+            "Material",
+            Decimal::from(1),
+            None,
+            Decimal::new(9999, 2),
+            15,
+        ));
+        invoice.recompute_totals();
+        assert_eq!(invoice.subtotal, Some(Decimal::new(159999, 2)));
+        assert_eq!(invoice.total, Some(Decimal::new(192999, 2)));
+
+        invoice.vat_price_mode = Some(VatPriceMode::FromTotalWithVat);
+        invoice.round_total = Some(true);
+        assert!(invoice.remove_line(1).is_some());
+        assert!(invoice.remove_line(1).is_none());
+        invoice.recompute_totals();
+        assert_eq!(invoice.subtotal, Some(Decimal::new(123967, 2)));
+        assert_eq!(invoice.total, Some(Decimal::from(1500)));
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        use crate::duplicates::{find_duplicates, DuplicateReason, MergePlan};
+
+        let subject = |id: i32, name: &str, email: &str| Subject {
+            id: Some(id),
+            name: Some(name.to_string()),
+            email: Some(email.to_string()),
+            ..Subject::default()
+        };
+        let subjects = vec![
+            subject(3, "ACME, s.r.o.", "info@acme.cz"),
+            subject(1, "Acme s.r.o.", "sales@acme.cz"),
+            subject(2, "Other company", "INFO@acme.cz "),
+        ];
+
+        let groups = find_duplicates(&subjects);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups[0].reason,
+            DuplicateReason::Email("info@acme.cz".to_string())
+        );
+        assert_eq!(
+            groups[1].reason,
+            DuplicateReason::Name("acme s r o".to_string())
+        );
+        assert_eq!(
+            groups[1].merge_plan(),
+            Some(MergePlan {
+                keep: 1,
+                remove: vec![3]
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_invoice_for_order() {
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::GET,
+            "/invoices/search.json",
+            MockResponse::json(r#"[{"id": 1, "order_number": "OBJ-7"}, {"id": 2, "order_number": "OBJ-8"}]"#)
+                .header(
+                    "Link",
+                    "<https://app.fakturoid.cz/api/v2/accounts/slug/invoices/search.json?query=OBJ-8&page=2>; \
+                     rel=\"next\"",
+                ),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let found = rt
+            .block_on(client.find_invoice_for_order("OBJ-8", &Invoice::default()))
+            .unwrap();
+        assert_eq!(found.and_then(|i| i.id), Some(2));
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary-precision")]
+    fn test_decimal_precision() {
+        use crate::models::InvoiceLine;
+        use std::str::FromStr;
+
+        let line: InvoiceLine = serde_json::from_str(
+            "{\"name\":\"Item\",\"quantity\":0.1,\"unit_price\":12345678901234.123456789,\"vat_rate\":21}"
+        ).unwrap();
+        assert_eq!(
+            line.quantity,
+            rust_decimal::Decimal::from_str("0.1").unwrap()
+        );
+        assert_eq!(
+            line.unit_price,
+            rust_decimal::Decimal::from_str("12345678901234.123456789").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(
+            invoice.to_string(),
+            "Invoice 2012-0021 for Apple Czech s.r.o.: 12039.5 CZK, paid, due 2011-10-23"
+        );
+        let subject: Subject = serde_json::from_str(SUBJECT).unwrap();
+        assert_eq!(
+            subject.to_string(),
+            "Apple Czech s.r.o. (reg. no. 28897501, pokus@test.cz)"
+        );
+    }
+
+    #[test]
+    fn test_render_invoice() {
+        let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(
+            render_invoice(&invoice, TextFormat::Plain),
+            "Faktura 2012-0021 pro Apple Czech s.r.o.: 12 039,50 CZK, uhrazená, splatná 23. 10. 2011"
+        );
+        invoice.language = Some(InvoiceLanguage::En);
+        invoice.due_on = None;
+        assert_eq!(
+            render_invoice(&invoice, TextFormat::Markdown),
+            "**Invoice 2012-0021** for Apple Czech s.r.o.: **12,039.50 CZK**, paid, due in 10 days"
+        );
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let mut data = std::collections::HashMap::new();
+        data.insert("number".to_string(), vec!["is taken".to_string()]);
+        data.insert(
+            "lines[2].unit_price".to_string(),
+            vec!["is not a number".to_string()],
+        );
+        data.insert(
+            "lines.0.name".to_string(),
+            vec!["can't be blank".to_string()],
+        );
+
+        let errors = FieldErrors::from_data_errors(&data);
+        assert_eq!(errors.messages_at("number"), ["is taken"]);
+        assert_eq!(
+            errors.messages_at("lines[2].unit_price"),
+            ["is not a number"]
+        );
+        assert_eq!(
+            errors
+                .field("lines")
+                .unwrap()
+                .item(0)
+                .unwrap()
+                .messages_at("name"),
+            ["can't be blank"]
+        );
+        assert!(errors.messages_at("lines[1].name").is_empty());
+        assert_eq!(
+            errors.to_flat().keys().collect::<Vec<_>>(),
+            ["lines[0].name", "lines[2].unit_price", "number"]
+        );
+
+        let localized = errors.localize(|path, msg| format!("{}: {}", path, msg));
+        assert_eq!(
+            localized.messages_at("lines[0].name"),
+            ["lines[0].name: can't be blank"]
+        );
+    }
+
+    #[test]
+    fn test_user_agent_validation() {
+        let builder = |ua: &str| {
+            Fakturoid::builder("user", "key", "slug")
+                .user_agent(ua)
+                .build()
+        };
+        assert!(builder("My app (developer@company.com)").is_ok());
+        assert!(builder("My app <dev@company.cz>").is_ok());
+        assert!(builder("My app").is_err());
+        assert!(builder("My app (@company)").is_err());
+
+        let client = Fakturoid::new("user", "key", "slug", None);
+        assert!(client
+            .with_user_agent("Platform for ACME (ops@acme.cz)")
+            .is_ok());
+        assert!(client.with_user_agent("ACME").is_err());
+    }
+
+    #[test]
+    fn test_request_queue() {
+        let queue = RequestQueue::new(4, 2, Duration::from_secs(60));
+        let mut waiting = false;
+        assert!(queue.try_acquire(Priority::Batch, &mut waiting).is_ok());
+        assert!(queue.try_acquire(Priority::Batch, &mut waiting).is_ok());
+        assert!(queue.try_acquire(Priority::Batch, &mut waiting).is_err());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_err());
+        assert!(waiting);
+
+        let mut batch_waiting = false;
+        assert!(queue
+            .try_acquire(Priority::Batch, &mut batch_waiting)
+            .is_err());
+        assert!(!batch_waiting);
+    }
+
+    #[test]
+    fn test_request_queue_dropped_waiter() {
+        use futures::future::FutureExt;
+
+        let queue = RequestQueue::new(2, 0, Duration::from_millis(200));
+        let mut waiting = false;
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        // interactive request gives up while waiting for next window
+        let acquired = rt.block_on(async {
+            futures::select! {
+                _ = queue.acquire(Priority::Interactive).fuse() => true,
+                _ = tokio::time::delay_for(Duration::from_millis(20)).fuse() => false,
+            }
+        });
+        assert!(!acquired);
+        rt.block_on(async { tokio::time::delay_for(Duration::from_millis(250)).await });
+        let mut batch_waiting = false;
+        assert!(queue
+            .try_acquire(Priority::Batch, &mut batch_waiting)
+            .is_ok());
+
+        assert!(Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .rate_limit(0, 0)
+            .build()
+            .is_err());
+    }
+
+    #[cfg(feature = "scheduler")]
+    #[test]
+    fn test_schedule() {
+        use crate::scheduler::Schedule;
+        use chrono::{Local, NaiveDate, TimeZone};
+
+        let local = |d: u32, h: u32, m: u32, s: u32| -> crate::dates::DateTime {
+            let time = NaiveDate::from_ymd_opt(2021, 1, d)
+                .and_then(|date| date.and_hms_opt(h, m, s))
+                .unwrap();
+            crate::dates::from_local(Local.from_local_datetime(&time).unwrap()).unwrap()
+        };
+
+        let schedule: Schedule = "30 6 * * 1-5".parse().unwrap();
+        // Saturday
+        let after = local(2, 12, 0, 0);
+        assert_eq!(schedule.next_after(&after), Some(local(4, 6, 30, 0)));
+        let after = local(4, 6, 30, 0);
+        assert_eq!(schedule.next_after(&after), Some(local(5, 6, 30, 0)));
+
+        let schedule: Schedule = "*/15 * * * *".parse().unwrap();
+        let after = local(4, 6, 31, 10);
+        assert_eq!(schedule.next_after(&after), Some(local(4, 6, 45, 0)));
+
+        let schedule: Schedule = "* * * * *".parse().unwrap();
+        let after = Local
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2021, 1, 4)
+                    .and_then(|date| date.and_hms_nano_opt(6, 31, 10, 123_456_789))
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            schedule
+                .next_after_local(&after)
+                .map(crate::dates::from_local),
+            Some(Some(local(4, 6, 32, 0)))
+        );
+
+        assert!("@weekly".parse::<Schedule>().is_ok());
+        assert!("61 * * * *".parse::<Schedule>().is_err());
+        assert!("* * *".parse::<Schedule>().is_err());
+    }
+
+    #[cfg(feature = "scheduler")]
+    #[test]
+    fn test_scheduler_job_panic() {
+        use crate::error::FakturoidError;
+        use crate::scheduler::Scheduler;
+
+        let client = Fakturoid::new("fake@user.com", "apicode", "testslug", None);
+        let mut scheduler = Scheduler::new(client);
+        scheduler
+            .add_job("panicking", "@daily", |_| async {
+                let result: Result<(), FakturoidError> = Ok(());
+                assert!(result.is_err(), "job panicked");
+                result
+            })
+            .unwrap();
+        assert!(scheduler.next_run().is_some());
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let first = rt.block_on(async { scheduler.run_now("panicking").unwrap().await });
+        assert!(first.is_err());
+        // panicked run doesn't block next runs
+        let second = rt.block_on(async { scheduler.run_now("panicking").map(|_| ()) });
+        assert!(second.is_some());
+    }
+
+    #[test]
+    fn test_payment_terms() {
+        let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(invoice.payment_terms(), Some(PaymentTerms::Days(10)));
+
+        // issued on 2011-10-13, end of month is 2011-10-31
+        invoice
+            .set_payment_terms(PaymentTerms::EndOfMonth(15))
+            .unwrap();
+        assert_eq!(invoice.due, Some(33));
+        invoice.set_payment_terms(PaymentTerms::Immediate).unwrap();
+        assert_eq!(invoice.payment_terms(), Some(PaymentTerms::Immediate));
+        assert!(invoice.set_payment_terms(PaymentTerms::Days(400)).is_err());
+        assert!(PaymentTerms::try_from(-1).is_err());
+    }
+
+    #[test]
+    fn test_taxable_fulfillment_due() {
+        let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let issued_on = invoice.issued_on.unwrap();
+        invoice.taxable_fulfillment_due = None;
+        invoice.default_taxable_fulfillment_due(&TaxPointRule::RecurringService);
+        assert_eq!(
+            invoice.taxable_fulfillment_due.unwrap().to_string(),
+            "2011-09-30"
+        );
+
+        // already set value is kept
+        invoice.default_taxable_fulfillment_due(&TaxPointRule::IssueDate);
+        assert_eq!(
+            invoice.taxable_fulfillment_due.unwrap().to_string(),
+            "2011-09-30"
+        );
+
+        let delivery = TaxPointRule::Delivery {
+            delivered_on: issued_on,
+            paid_on: invoice.due_on,
+        };
+        assert_eq!(delivery.taxable_fulfillment_due(&issued_on), issued_on);
+        let prepaid = TaxPointRule::Delivery {
+            delivered_on: invoice.due_on.unwrap(),
+            paid_on: Some(issued_on),
+        };
+        assert_eq!(prepaid.taxable_fulfillment_due(&issued_on), issued_on);
+    }
+
+    #[test]
+    fn test_rounding_matches_fixtures() {
+        use crate::models::VatRounding;
+        use rust_decimal::Decimal;
+
+        for fixture in &[INVOICE, INVOICE_ROUNDED] {
+            let original: Invoice = serde_json::from_str(fixture).unwrap();
+            let mut invoice = original.clone();
+            invoice.recompute_totals();
+            assert_eq!(invoice.subtotal, original.subtotal);
+            assert_eq!(invoice.total, original.total);
+            let lines = invoice.lines.iter().flatten();
+            for (line, orig) in lines.zip(original.lines.iter().flatten()) {
+                assert_eq!(line.unit_price_without_vat, orig.unit_price_without_vat);
+                assert_eq!(line.unit_price_with_vat, orig.unit_price_with_vat);
+            }
+        }
+
+        let mut invoice: Invoice = serde_json::from_str(INVOICE_ROUNDED).unwrap();
+        invoice.recompute_totals_with(VatRounding::PerLine);
+        assert_eq!(invoice.subtotal, Some(Decimal::new(54473, 2)));
+    }
+
+    #[test]
+    fn test_reverse_charge() {
+        use crate::tax::{is_valid_vat_no, validate_reverse_charge, TaxIssue};
+        use rust_decimal::Decimal;
+
+        let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert!(validate_reverse_charge(&invoice).is_ok());
+        invoice.transferred_tax_liability = Some(true);
+        assert_eq!(
+            validate_reverse_charge(&invoice),
+            Err(vec![
+                TaxIssue::NonZeroVatRate {
+                    line: 0,
+                    vat_rate: Decimal::from(21)
+                },
+                TaxIssue::NonZeroVatRate {
+                    line: 1,
+                    vat_rate: Decimal::from(21)
+                },
+                TaxIssue::MissingSupplyCode,
+            ])
+        );
+        for line in invoice.lines_mut() {
+            line.vat_rate = Decimal::ZERO;
+        }
+        invoice.supply_code = Some("4".to_string());
+        invoice.client_vat_no = Some("CZ123".to_string());
+        assert_eq!(
+            validate_reverse_charge(&invoice),
+            Err(vec![TaxIssue::InvalidClientVatNo("CZ123".to_string())])
+        );
+        invoice.client_vat_no = Some("CZ 2889 7501".to_string());
+        assert!(validate_reverse_charge(&invoice).is_ok());
+
+        assert!(is_valid_vat_no("ATU12345678"));
+        assert!(is_valid_vat_no("FR12345678901"));
+        assert!(!is_valid_vat_no("US123456789"));
+    }
+
+    #[test]
+    fn test_invoice_from_generator() {
+        use crate::models::{Generator, InvoiceLine};
+        use rust_decimal::Decimal;
+
+        let template: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let mut line = InvoiceLine::new(
+            "Hosting #obdobi#",
+            Decimal::from(1),
+            None,
+            Decimal::from(500),
+            21,
+        );
+        line.id = Some(7);
+        let generator = Generator {
+            id: Some(4),
+            subject_id: Some(16),
+            months_period: Some(3),
+            due: Some(14),
+            tax_date_at_end_of_last_month: Some(true),
+            note: Some("Služby za #ctvrtleti#. čtvrtletí #rok#".to_string()),
+            lines: Some(vec![line]),
+            ..Generator::default()
+        };
+
+        let issued_on = template.due_on.unwrap();
+        let invoice = Invoice::from_generator(&generator, issued_on);
+        assert_eq!(invoice.subject_id, Some(16));
+        assert_eq!(invoice.generator_id, Some(4));
+        assert_eq!(invoice.note.as_deref(), Some("Služby za 4. čtvrtletí 2011"));
+        assert_eq!(invoice.due_on.unwrap().to_string(), "2011-11-06");
+        assert_eq!(
+            invoice.taxable_fulfillment_due.unwrap().to_string(),
+            "2011-09-30"
+        );
+        let line = &invoice.lines.as_ref().unwrap()[0];
+        assert_eq!(line.name, "Hosting 10/2011 - 12/2011");
+        assert_eq!(line.id, None);
+        assert_eq!(invoice.total, Some(Decimal::from(605)));
+    }
+
+    #[test]
+    fn test_v3_proformas() {
+        use crate::models::{Generator, InvoiceDocumentType};
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::POST,
+            "/oauth/token",
+            r#"{"access_token": "token", "expires_in": 7200}"#,
+        );
+        mock.json(
+            Method::GET,
+            "/invoices.json",
+            r#"[
+                {"id": 1, "document_type": "proforma", "due_on": "2020-01-01"},
+                {"id": 2, "document_type": "partial_proforma", "due_on": "2020-01-01"},
+                {"id": 3, "document_type": "invoice", "due_on": "2020-01-01"}
+            ]"#,
+        );
+        let client = Fakturoid::oauth_builder("id", "secret", "slug")
+            .transport(mock)
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let ids = |invoices: Vec<Invoice>| invoices.iter().map(|i| i.id).collect::<Vec<_>>();
+        let expired = rt.block_on(client.expired_proformas()).unwrap();
+        assert_eq!(ids(expired), vec![Some(1), Some(2)]);
+        let overdue = rt.block_on(client.overdue_invoices()).unwrap();
+        assert_eq!(ids(overdue), vec![Some(3)]);
+
+        let generator = Generator {
+            proforma: Some(true),
+            ..Generator::default()
+        };
+        let mut invoice = Invoice::from_generator(&generator, crate::dates::today());
+        assert_eq!(invoice.document_type, Some(InvoiceDocumentType::Proforma));
+        assert!(invoice.taxable_fulfillment_due.is_none());
+        invoice.proforma = None;
+        assert!(invoice.is_proforma());
+    }
+
+    #[test]
+    fn test_generator_occurrences() {
+        use crate::models::Generator;
+
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let mut generator = Generator {
+            recurring: Some(true),
+            // 2011-10-23
+            start_date: invoice.due_on,
+            end_date: invoice.due_on.map(|d| crate::dates::add_months(&d, 5, 1)),
+            months_period: Some(2),
+            ..Generator::default()
+        };
+        let dates: Vec<String> = generator
+            .upcoming_occurrences(5)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(dates, ["2011-10-23", "2011-12-23", "2012-02-23"]);
+
+        generator.last_day_in_month = Some(true);
+        generator.months_period = Some(1);
+        let dates: Vec<String> = generator
+            .upcoming_occurrences(2)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(dates, ["2011-10-31", "2011-11-30"]);
+
+        generator.recurring = Some(false);
+        assert!(generator.upcoming_occurrences(2).is_empty());
+    }
+
+    #[test]
+    fn test_webhook_dispatcher() {
+        use crate::webhooks::{WebhookDispatcher, WebhookEventType};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let paid = Arc::new(AtomicUsize::new(0));
+        let unknown = Arc::new(AtomicUsize::new(0));
+        let mut dispatcher = WebhookDispatcher::new();
+        let counter = paid.clone();
+        dispatcher.on_invoice_paid(move |payload| {
+            assert_eq!(payload.invoice_id, Some(27));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let counter = unknown.clone();
+        dispatcher.on_unknown(move |payload| {
+            assert_eq!(
+                payload.event_type(),
+                WebhookEventType::Unknown("invoice_accepted".to_string())
+            );
+            assert_eq!(payload.extra["total"], "1210.0");
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let paid_body = br#"{"event_name": "invoice_paid", "invoice_id": 27}"#;
+        let unknown_body =
+            br#"{"event_name": "invoice_accepted", "invoice_id": 27, "total": "1210.0"}"#;
+        assert!(dispatcher.dispatch_json(paid_body).unwrap());
+        assert!(dispatcher.dispatch_json(unknown_body).unwrap());
+        assert!(dispatcher.dispatch_json(b"not json").is_err());
+        assert_eq!(paid.load(Ordering::SeqCst), 1);
+        assert_eq!(unknown.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_payment_gateways() {
+        use crate::gateways::{GatewaySettings, PaymentGateway};
+
+        let account: Account = serde_json::from_str(ACCOUNT).unwrap();
+        let mut settings = GatewaySettings::from_account(&account);
+        let mut invoice = Invoice::default();
+        invoice.gopay = Some(true);
+        let err = settings.validate(&invoice).unwrap_err();
+        assert_eq!(err.gateway(), PaymentGateway::GoPay);
+        assert_eq!(err.unsupported_currency(), None);
+
+        settings.gopay = true;
+        assert!(settings.validate(&invoice).is_ok());
+        invoice.currency = Some("JPY".parse().unwrap());
+        let err = settings.validate(&invoice).unwrap_err();
+        assert_eq!(err.unsupported_currency(), Some("JPY"));
+        assert_eq!(err.to_string(), "GoPay doesn't support payments in JPY");
+
+        settings.paypal = true;
+        assert!(settings
+            .check(PaymentGateway::PayPal, Some("jpy".parse().unwrap()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_shutdown() {
+        use crate::error::ClientShutDown;
+        use crate::shutdown::ShutdownState;
+        use std::sync::Arc;
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let state = Arc::new(ShutdownState::default());
+            let guard = state.enter();
+            assert!(guard.is_some());
+            assert_eq!(state.close(Duration::from_millis(30)).await, 1);
+            assert!(state.enter().is_none());
+            drop(guard);
+            assert_eq!(state.close(Duration::from_millis(30)).await, 0);
+
+            let client = Fakturoid::new("fake@user.com", "apicode", "testslug", None);
+            let clone = client.clone();
+            assert!(client.shutdown(Duration::from_secs(1)).await.is_ok());
+            assert!(clone.is_shut_down());
+            let err = clone.account().await.unwrap_err();
+            assert!(err
+                .into_std_err()
+                .downcast_ref::<ClientShutDown>()
+                .is_some());
+        });
+    }
+
+    #[test]
+    fn test_shutdown_waits_for_body() {
+        use crate::models::RemoteAttachment;
+        use crate::transport::{MockResponse, MockTransport};
+        use futures::TryStreamExt;
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::GET,
+            "/api/v2/accounts/slug/expenses/5/attachment",
+            MockResponse::new(StatusCode::OK, "content"),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let attachment: RemoteAttachment = serde_json::from_value(serde_json::json!({
+            "file_name": "receipt.pdf",
+            "content_type": "application/pdf",
+            "download_url": "https://app.fakturoid.cz/api/v2/accounts/slug/expenses/5/attachment",
+        }))
+        .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            let mut chunks = Box::pin(client.download_attachment_stream(&attachment));
+            assert_eq!(
+                chunks.try_next().await.unwrap().as_deref(),
+                Some(&b"content"[..])
+            );
+            assert!(client.shutdown(Duration::from_millis(30)).await.is_err());
+            drop(chunks);
+            assert!(client.shutdown(Duration::from_millis(30)).await.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_download_attachment() {
+        use crate::error::ForeignUrl;
+        use crate::models::RemoteAttachment;
+        use crate::transport::{MockResponse, MockTransport};
+        use futures::StreamExt;
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::GET,
+            "/expenses/5/attachment",
+            MockResponse::new(StatusCode::OK, "content"),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut attachment: RemoteAttachment = serde_json::from_value(serde_json::json!({
+            "file_name": "receipt.pdf",
+            "content_type": "application/pdf",
+            "download_url": "https://app.fakturoid.cz/api/v2/accounts/slug/expenses/5/attachment",
+        }))
+        .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let content = rt
+            .block_on(client.download_attachment(&attachment))
+            .unwrap();
+        assert_eq!(&content[..], &b"content"[..]);
+
+        attachment.download_url = "https://evil.example.com/expenses/5/attachment".to_string();
+        let err = rt
+            .block_on(client.download_attachment(&attachment))
+            .unwrap_err();
+        assert!(err.into_std_err().downcast_ref::<ForeignUrl>().is_some());
+        let chunks: Vec<_> = rt.block_on(
+            client
+                .download_attachment_stream(&attachment)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].is_err());
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_rate_limit_state() {
+        use crate::transport::MockTransport;
+
+        let path = std::env::temp_dir().join(format!("fakturoid-rate-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let queue = RequestQueue::new(3, 0, Duration::from_secs(60)).persistent(path.clone());
+        let mut waiting = false;
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        assert!(queue.save().is_ok());
+
+        let queue = RequestQueue::new(3, 0, Duration::from_secs(60)).persistent(path.clone());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_ok());
+        assert!(queue
+            .try_acquire(Priority::Interactive, &mut waiting)
+            .is_err());
+
+        let mock = MockTransport::new();
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .rate_limit(3, 0)
+            .rate_limit_state(&path)
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async { assert!(client.shutdown(Duration::from_secs(1)).await.is_ok()) });
+        let saved: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved["used"], 2);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_streamed_array_parsing() {
+        use crate::streaming::ArrayParser;
+
+        let body = br#" [{"id": 1, "name": "a,]}"}, {"id": 2, "name": "b\"]"},{"id":3}] "#;
+        let mut parser = ArrayParser::new();
+        let mut subjects: Vec<Subject> = Vec::new();
+        for chunk in body.chunks(5) {
+            parser.feed(chunk, &mut subjects).unwrap();
+        }
+        assert!(parser.finish().is_ok());
+        let ids: Vec<_> = subjects.iter().filter_map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(subjects[0].name.as_deref(), Some("a,]}"));
+        assert_eq!(subjects[1].name.as_deref(), Some("b\"]"));
+
+        let mut parser = ArrayParser::new();
+        parser.feed(br#"[{"id": 1}, {"id""#, &mut subjects).unwrap();
+        assert!(parser.finish().is_err());
+        let mut parser = ArrayParser::new();
+        assert!(parser.feed(b"{}", &mut subjects).is_err());
+
+        for malformed in &[&b"[,,1,]"[..], b"[1,]", b"[,1]", b"[1,,2]", b"[ , ]"] {
+            let mut parser = ArrayParser::new();
+            let mut items: Vec<i32> = Vec::new();
+            assert!(parser.feed(malformed, &mut items).is_err());
+        }
+        let mut parser = ArrayParser::new();
+        let mut items: Vec<i32> = Vec::new();
+        parser.feed(b" [ ] ", &mut items).unwrap();
+        assert!(parser.finish().is_ok());
+        assert!(items.is_empty());
+    }
+
+    #[cfg(feature = "performance")]
+    #[test]
+    fn test_invoice_views() {
+        use crate::views::InvoiceView;
+        use std::borrow::Cow;
+
+        let body = format!("[{}]", INVOICE);
+        let views: Vec<InvoiceView> = serde_json::from_str(&body).unwrap();
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(views.len(), 1);
+        assert_eq!(views[0].id, 27);
+        assert!(matches!(views[0].number, Some(Cow::Borrowed("2012-0021"))));
+        assert_eq!(views[0].client_name.as_deref(), Some("Apple Czech s.r.o."));
+        assert_eq!(views[0].tags, vec!["translations", "applecorp"]);
+        assert_eq!(views[0].total, invoice.total);
+        assert_eq!(views[0].due_on, invoice.due_on);
+        assert!(views[0].custom_id.is_none());
+
+        let escaped: Vec<InvoiceView> =
+            serde_json::from_str(r#"[{"id": 1, "note": "x", "number": "A\"B"}]"#).unwrap();
+        assert!(matches!(escaped[0].number, Some(Cow::Owned(_))));
+    }
+
+    #[test]
+    fn test_summaries() {
+        use crate::models::{InvoiceSummary, SubjectSummary};
+
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let summary: InvoiceSummary = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(Some(summary.id), invoice.id);
+        assert_eq!(summary.number, invoice.number);
+        assert_eq!(summary.total, invoice.total);
+        assert_eq!(summary.due_on, invoice.due_on);
+
+        let subject: Subject = serde_json::from_str(SUBJECT).unwrap();
+        let summary: SubjectSummary = serde_json::from_str(SUBJECT).unwrap();
+        assert_eq!(Some(summary.id), subject.id);
+        assert_eq!(summary.name, subject.name);
+    }
+
+    #[test]
+    fn test_pdf_archive_names() {
+        use crate::archive::PdfArchive;
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+        use std::fs;
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/invoices.json",
+            r#"[
+                {"id": 1, "number": "2024/1"},
+                {"id": 2, "number": "2024-1"},
+                {"id": 3, "number": "a"},
+                {"id": 4, "number": "A"},
+                {"id": 5}
+            ]"#,
+        );
+        mock.on(
+            Method::GET,
+            "/download.pdf",
+            MockResponse::new(StatusCode::OK, "%PDF"),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock)
+            .build()
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("fakturoid-pdfs-{}", std::process::id()));
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let count = rt
+            .block_on(client.download_invoice_pdfs(None, PdfArchive::Directory(dir.clone()), 2))
+            .unwrap();
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(count, 5);
+        assert_eq!(
+            names,
+            vec!["2024-1-2.pdf", "2024-1.pdf", "5.pdf", "A-4.pdf", "a.pdf"]
+        );
+    }
+
+    #[test]
+    fn test_migration_resume() {
+        use crate::migration::{read_resume_file, ImportItem, ImportOutcome, Importer};
+        use std::fs;
+
+        let path = std::env::temp_dir().join(format!("fakturoid-resume-{}", std::process::id()));
+        fs::write(&path, "C-1\t16\nwith\\ttab\t17\nbroken line\n").unwrap();
+        let imported = read_resume_file(&path).unwrap();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(imported["C-1"], 16);
+        assert_eq!(imported["with\ttab"], 17);
+
+        let client = Fakturoid::new("fake@user.com", "apicode", "testslug", None);
+        let importer = Importer::new(client).resume_file(&path);
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let report = rt
+            .block_on(importer.run(vec![ImportItem::subject("C-1", Subject::default())]))
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(report.skipped(), 1);
+        assert_eq!(report.results[0].outcome, ImportOutcome::Skipped(16));
+        assert_eq!(report.ids()["C-1"], 16);
+    }
+
+    #[test]
+    fn test_migration_retries() {
+        use crate::migration::{ImportItem, ImportOutcome, Importer};
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        let status = |status| MockResponse::new(status, "");
+        mock.on(
+            Method::POST,
+            "/subjects.json",
+            status(StatusCode::TOO_MANY_REQUESTS),
+        );
+        mock.json(Method::POST, "/subjects.json", SUBJECT);
+        mock.on(
+            Method::POST,
+            "/invoices.json",
+            status(StatusCode::SERVICE_UNAVAILABLE),
+        );
+        mock.json(Method::POST, "/invoices.json", INVOICE);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let importer = Importer::new(client)
+            .concurrency(1)
+            .retry_delay(Duration::from_millis(1));
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let report = rt
+            .block_on(importer.run(vec![
+                ImportItem::subject("C-1", Subject::default()),
+                ImportItem::invoice("I-1", Invoice::default()),
+            ]))
+            .unwrap();
+        assert_eq!(report.results[0].outcome, ImportOutcome::Created(16));
+        match &report.results[1].outcome {
+            ImportOutcome::Failed { attempts, .. } => assert_eq!(*attempts, 1),
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        }
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_parse_expenses_csv() {
+        use crate::expense_import::parse_expenses_csv;
+        use rust_decimal::Decimal;
+
+        let csv = "original_number,supplier_name,issued_on,amount,vat_rate,tags,note\n\
+                   FV-001, Supplier s.r.o. ,2020-02-01,1000.50,21,hardware; office,ignored\n\
+                   FV-002,,,200,,,\n";
+        let expenses = parse_expenses_csv(csv.as_bytes()).unwrap();
+        assert_eq!(expenses.len(), 2);
+        assert_eq!(expenses[0].original_number.as_deref(), Some("FV-001"));
+        assert_eq!(
+            expenses[0].supplier_name.as_deref(),
+            Some("Supplier s.r.o.")
+        );
+        assert!(expenses[0].issued_on.is_some());
+        assert_eq!(
+            expenses[0].tags,
+            Some(vec!["hardware".to_string(), "office".to_string()])
+        );
+        let line = &expenses[0].lines.as_ref().unwrap()[0];
+        assert_eq!(line.unit_price, Decimal::new(100050, 2));
+        assert_eq!(line.vat_rate, Decimal::from(21));
+        assert_eq!(expenses[1].supplier_name, None);
+        assert!(expenses[1].lines.as_ref().unwrap()[0].vat_rate.is_zero());
+
+        assert!(parse_expenses_csv("original_number,amount\nFV-003,abc\n".as_bytes()).is_err());
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_import_expenses_csv() {
+        use crate::migration::{ImportOutcome, Importer};
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/expenses.json",
+            r#"[{"id": 1, "original_number": "FV-001", "supplier_name": "Alfa s.r.o.",
+                 "supplier_registration_no": "12345678"},
+                {"id": 2, "original_number": "FV-002", "supplier_name": "Beta"}]"#,
+        );
+        mock.json(Method::POST, "/expenses.json", r#"{"id": 10}"#);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let csv = "original_number,supplier_name,supplier_registration_no,amount\n\
+                   FV-001,Alfa s.r.o.,12345678,100\n\
+                   FV-001,Gama,87654321,100\n\
+                   FV-002,BETA,,100\n\
+                   FV-001,alfa s.r.o.,,100\n\
+                   FV-002,Beta,11111111,100\n\
+                   FV-003,Gama,87654321,100\n\
+                   FV-003,Gama,87654321,100\n";
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let report = rt
+            .block_on(Importer::new(client).import_expenses_csv(csv.as_bytes()))
+            .unwrap();
+        let outcome = |key: &str| {
+            report
+                .results
+                .iter()
+                .filter(|r| r.key == key)
+                .map(|r| r.outcome.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(outcome("12345678/FV-001"), vec![ImportOutcome::Skipped(1)]);
+        assert_eq!(outcome("87654321/FV-001"), vec![ImportOutcome::Created(10)]);
+        assert_eq!(outcome("BETA/FV-002"), vec![ImportOutcome::Skipped(2)]);
+        assert_eq!(
+            outcome("alfa s.r.o./FV-001"),
+            vec![ImportOutcome::Skipped(1)]
+        );
+        assert_eq!(outcome("11111111/FV-002"), vec![ImportOutcome::Skipped(2)]);
+        assert_eq!(outcome("87654321/FV-003").len(), 2);
+        assert_eq!(report.created(), 2);
+        assert_eq!(report.skipped(), 4);
+        assert_eq!(report.failed(), 1);
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_csv_records() {
+        use crate::csv_io::CsvRecords;
+        use rust_decimal::Decimal;
+
+        let subject: Subject = serde_json::from_str(SUBJECT).unwrap();
+        let mut csv = Vec::new();
+        vec![subject.clone()].to_csv(&mut csv).unwrap();
+        let subjects = Vec::<Subject>::from_csv(csv.as_slice()).unwrap();
+        assert_eq!(subjects.len(), 1);
+        assert_eq!(subjects[0].custom_id, subject.custom_id);
+        assert_eq!(subjects[0].name, subject.name);
+        assert_eq!(subjects[0].registration_no, subject.registration_no);
+
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let mut empty = Invoice::default();
+        empty.number = Some("2012-0022".to_string());
+        empty.tags = Some(vec!["a".to_string(), "b".to_string()]);
+        let mut csv = Vec::new();
+        vec![invoice.clone(), empty].to_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv.clone()).unwrap().lines().count(), 4);
+        let invoices = Vec::<Invoice>::from_csv(csv.as_slice()).unwrap();
+        assert_eq!(invoices.len(), 2);
+        assert_eq!(invoices[0].number, invoice.number);
+        assert_eq!(invoices[0].issued_on, invoice.issued_on);
+        assert_eq!(invoices[0].currency, Some(Currency::CZK));
+        let lines = invoices[0].lines.as_ref().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].name, invoice.lines.as_ref().unwrap()[1].name);
+        assert_eq!(
+            lines[1].unit_price,
+            invoice.lines.as_ref().unwrap()[1].unit_price
+        );
+        assert!(invoices[1].lines.is_none());
+        assert_eq!(
+            invoices[1].tags,
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+
+        let csv = "number,client_name,line_name,line_quantity,line_unit_price,line_vat_rate\n\
+                   FA-1,Client,Work,2,500,21\n\
+                   FA-1,Client,Travel,,100,0\n";
+        let invoices = Vec::<Invoice>::from_csv(csv.as_bytes()).unwrap();
+        assert_eq!(invoices.len(), 1);
+        let lines = invoices[0].lines.as_ref().unwrap();
+        assert_eq!(lines[0].quantity, Decimal::new(2, 0));
+        assert_eq!(lines[1].quantity, Decimal::ONE);
+        assert!(Vec::<Invoice>::from_csv("number,due\nFA-2,soon\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_account_defaults() {
+        let account: Account = serde_json::from_str(ACCOUNT).unwrap();
+        let mut invoice = Invoice::default();
+        invoice.currency = Some(Currency::EUR);
+        let invoice = invoice.with_account_defaults(&account);
+        assert_eq!(invoice.your_name, Some(account.name.clone()));
+        assert_eq!(invoice.your_city, Some(account.city.clone()));
+        assert_eq!(invoice.your_registration_no, account.registration_no);
+        assert_eq!(invoice.currency, Some(Currency::EUR));
+        assert_eq!(invoice.due, Some(account.due));
+        assert_eq!(invoice.bank_account, Some(account.bank_account.clone()));
+        assert_eq!(invoice.language, Some(InvoiceLanguage::Cz));
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn test_demo_generator() {
+        use crate::devtools::{DemoGenerator, DEMO_TAG};
+
+        let mut generator = DemoGenerator::new(7);
+        let subject = generator.subject();
+        let invoice = generator.invoice(16);
+        let expense = generator.expense(16);
+        let registration_no = subject.registration_no.unwrap();
+        let digits: Vec<u32> = registration_no
+            .chars()
+            .filter_map(|c| c.to_digit(10))
+            .collect();
+        let sum: u32 = (0..7).map(|i| digits[i] * (8 - i as u32)).sum();
+        assert_eq!(digits.len(), 8);
+        assert_eq!(digits[7], (11 - sum % 11) % 10);
+        assert!(subject.name.is_some());
+        assert_eq!(invoice.subject_id, Some(16));
+        assert!(!invoice.lines.unwrap().is_empty());
+        assert_eq!(expense.tags, Some(vec![DEMO_TAG.to_string()]));
+
+        let mut same = DemoGenerator::new(7);
+        assert_eq!(same.subject().name, subject.name);
+    }
+
+    #[cfg(feature = "devtools")]
+    #[test]
+    fn test_demo_cleanup() {
+        use crate::devtools::DemoData;
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        let no_content = || MockResponse::new(StatusCode::NO_CONTENT, "");
+        mock.on(Method::DELETE, "/invoices/1.json", no_content());
+        mock.on(Method::DELETE, "/subjects/3.json", no_content());
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let data = DemoData {
+            subject_ids: vec![3],
+            invoice_ids: vec![1],
+            expense_ids: vec![2],
+        };
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let deleted = rt.block_on(client.cleanup_demo_data(&data)).unwrap();
+        assert_eq!(deleted, 2);
+        let requests = mock.requests();
+        assert!(requests.iter().all(|r| r.method == Method::DELETE));
+        let urls: Vec<_> = requests.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://app.fakturoid.cz/api/v2/accounts/slug/invoices/1.json",
+                "https://app.fakturoid.cz/api/v2/accounts/slug/expenses/2.json",
+                "https://app.fakturoid.cz/api/v2/accounts/slug/subjects/3.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expense() {
+        let expense: Expense = serde_json::from_str(EXPENSE).unwrap();
+        assert_eq!(expense.original_number.as_deref(), Some("FV-2012-0311"));
+        assert!(matches!(expense.status, Some(ExpenseState::Paid)));
+        assert_eq!(expense.lines.as_ref().map(|l| l.len()), Some(2));
+        assert_eq!(
+            expense.attachment().map(|a| a.file_name.as_str()),
+            Some("faktura.pdf")
+        );
+        assert_eq!(ExpenseState::Overdue.to_string(), "overdue");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_generator_filter() {
+        use crate::client::Entity;
+        use crate::filters::Filter;
+        use crate::models::GeneratorKind;
+
+        let query = Generator::filter_builder().build(
+            Filter::new()
+                .subject_id(16)
+                .number("2012-0021")
+                .expense_status(ExpenseState::Paid),
+        );
+        assert_eq!(query.len(), 1);
+        assert_eq!(query.get("subject_id").map(|s| s.as_str()), Some("16"));
+        assert_eq!(GeneratorKind::Template.to_string(), "template");
+    }
+
+    #[test]
+    fn test_auth_debug() {
+        use crate::auth::Auth;
+
+        let auth = Auth::client_credentials("id123", "secret456");
+        let debug = format!("{:?}", auth);
+        assert!(auth.is_bearer());
+        assert!(debug.contains("id123"));
+        assert!(!debug.contains("secret456"));
+        assert!(!format!("{:?}", Auth::basic("user@company.com", "apicode")).contains("apicode"));
+    }
+
+    #[test]
+    fn test_api_v3() {
+        use crate::client::ApiVersion;
+        use crate::models::InvoiceDocumentType;
+
+        let client = Fakturoid::oauth_builder("id", "secret", "slug")
+            .build()
+            .unwrap();
+        assert_eq!(client.api_version(), ApiVersion::V3);
+        let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+            .build()
+            .unwrap();
+        assert_eq!(client.api_version(), ApiVersion::V2);
+
+        let invoice: Invoice = serde_json::from_str(
+            r#"{"id": 1, "document_type": "partial_proforma", "paid_on": "2023-10-02"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            invoice.document_type,
+            Some(InvoiceDocumentType::PartialProforma)
+        );
+        assert_eq!(invoice.paid_on.unwrap().to_string(), "2023-10-02");
+        let invoice: Invoice =
+            serde_json::from_str(r#"{"id": 2, "document_type": "self_billing"}"#).unwrap();
+        assert_eq!(invoice.document_type, Some(InvoiceDocumentType::Other));
+
+        assert!(Fakturoid::builder("user@company.com", "apicode", "slug")
+            .api_version(ApiVersion::V3)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_api_v3_paging() {
+        use crate::filters::SubjectFilters;
+        use crate::transport::MockTransport;
+        use futures::TryStreamExt;
+        use reqwest::Method;
+
+        let full_page = format!(
+            "[{}]",
+            (1..=40)
+                .map(|id| format!(r#"{{"id": {}}}"#, id))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let mock = MockTransport::new();
+        mock.json(
+            Method::POST,
+            "/oauth/token",
+            r#"{"access_token": "token", "expires_in": 7200}"#,
+        );
+        for _ in 0..2 {
+            mock.json(Method::GET, "/subjects.json", &full_page);
+            mock.json(Method::GET, "/subjects.json", r#"[{"id": 41}, {"id": 42}]"#);
+        }
+        let client = Fakturoid::oauth_builder("id", "secret", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let first = rt
+            .block_on(client.list_filtered(SubjectFilters::new().custom_id("C")))
+            .unwrap();
+        assert!(first.has_next());
+        assert!(!first.has_prev());
+        let subjects = rt.block_on(first.collect_all(None)).unwrap();
+        assert_eq!(subjects.len(), 42);
+        let streamed: Vec<Subject> = rt
+            .block_on(client.list_streamed::<Subject>(None).try_collect())
+            .unwrap();
+        assert_eq!(streamed.len(), 42);
+
+        let urls: Vec<String> = mock
+            .requests()
+            .into_iter()
+            .filter(|r| r.method == Method::GET)
+            .map(|r| r.url)
+            .collect();
+        assert_eq!(urls.len(), 4);
+        assert!(urls[1].ends_with("/api/v3/accounts/slug/subjects.json?custom_id=C&page=2"));
+        assert!(urls[3].ends_with("/subjects.json?page=2"));
+    }
+
+    #[test]
+    fn test_invoice_payments() {
+        use crate::models::InvoicePayment;
+        use rust_decimal::Decimal;
+
+        let payment = InvoicePayment::new(Decimal::new(50000, 2));
+        assert_eq!(
+            serde_json::to_value(&payment).unwrap(),
+            serde_json::json!({ "amount": "500.00" })
+        );
+
+        let invoice: Invoice = serde_json::from_str(
+            r#"{"id": 27, "payments": [{"id": 3, "paid_on": "2023-11-20", "currency": "CZK",
+                "amount": "1000.0", "native_amount": "1000.0", "mark_document_as_paid": false,
+                "tax_document_id": null}]}"#,
+        )
+        .unwrap();
+        let payments = invoice.payments.unwrap();
+        assert_eq!(payments[0].id, Some(3));
+        assert_eq!(payments[0].amount, Some(Decimal::from(1000)));
+        assert_eq!(payments[0].paid_on.unwrap().to_string(), "2023-11-20");
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_inventory() {
+        use crate::client::Entity;
+        use crate::filters::Filter;
+        use crate::models::{InventoryDirection, InventoryItem};
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let filter = || Filter::new().sku("KB-01").subject_id(16).page(2);
+        let query = InventoryItem::filter_builder().build(filter());
+        assert_eq!(query.len(), 2);
+        assert_eq!(query.get("sku").map(|s| s.as_str()), Some("KB-01"));
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/inventory_items/12/inventory_moves.json",
+            r#"[{"id": 5, "direction": "out", "moved_on": "2023-11-20", "quantity_change": "2.0",
+                "inventory_item_id": 12, "document": {"id": 27, "type": "Invoice", "line_id": 1304}}]"#,
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let moves = rt
+            .block_on(client.list_inventory_moves(12, Some(filter())))
+            .unwrap()
+            .into_data();
+        assert_eq!(moves[0].direction, Some(InventoryDirection::Out));
+        assert_eq!(moves[0].document.as_ref().unwrap().document_type, "Invoice");
+        assert!(mock.requests()[0]
+            .url
+            .ends_with("/accounts/slug/inventory_items/12/inventory_moves.json?page=2"));
+    }
+
+    #[test]
+    fn test_bank_accounts() {
+        use crate::models::BankAccount;
+
+        let accounts: Vec<BankAccount> = serde_json::from_str(
+            r#"[{"id": 1, "name": "Hlavní", "currency": "CZK", "number": "1234/2010",
+                 "iban": null, "swift_bic": null, "pairing": true, "expense_pairing": false,
+                 "payment_adjustment": false, "default": true,
+                 "created_at": "2012-05-01T10:00:00.000+02:00",
+                 "updated_at": "2012-05-01T10:00:00.000+02:00"}]"#,
+        )
+        .unwrap();
+        assert_eq!(accounts[0].id, Some(1));
+        assert_eq!(accounts[0].default, Some(true));
+        assert_eq!(accounts[0].number.as_deref(), Some("1234/2010"));
+    }
+
+    #[test]
+    fn test_number_formats() {
+        use crate::client::Entity;
+        use crate::error::UnknownNumberFormat;
+        use crate::models::NumberFormat;
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let formats: Vec<NumberFormat> = serde_json::from_str(
+            r##"[{"id": 31, "format": "#yyyy#-#dddd#", "preview": "2023-0001", "default": true,
+                  "created_at": "2023-01-02T10:00:00.000+01:00",
+                  "updated_at": "2023-01-02T10:00:00.000+01:00"}]"##,
+        )
+        .unwrap();
+        assert_eq!(formats[0].id, Some(31));
+        assert_eq!(formats[0].preview.as_deref(), Some("2023-0001"));
+        assert_eq!(NumberFormat::url_part(), "number_formats/invoices");
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/number_formats/invoices.json",
+            r##"[{"id": 31, "format": "#yyyy#-#dddd#"}]"##,
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut invoice = Invoice::default();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        assert!(rt.block_on(client.validate_number_format(&invoice)).is_ok());
+        assert!(mock.requests().is_empty());
+        invoice.number_format_id = Some(31);
+        assert!(rt.block_on(client.validate_number_format(&invoice)).is_ok());
+        invoice.number_format_id = Some(32);
+        let err = rt
+            .block_on(client.validate_number_format(&invoice))
+            .unwrap_err();
+        assert_eq!(
+            err.into_std_err().downcast_ref::<UnknownNumberFormat>(),
+            Some(&UnknownNumberFormat::new(32))
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_todos() {
+        use crate::client::Entity;
+        use crate::filters::Filter;
+        use crate::models::Todo;
+
+        let todo: Todo = serde_json::from_str(
+            r#"{"id": 2, "name": "invoice_overdue", "created_at": "2012-11-21T00:05:10.000+01:00",
+                "completed_at": null, "text": "Faktura 2012-0021 je po splatnosti.",
+                "related_objects": [{"type": "Invoice", "id": 27}], "params": {}}"#,
+        )
+        .unwrap();
+        assert!(!todo.is_completed());
+        let related = &todo.related_objects.as_ref().unwrap()[0];
+        assert_eq!((related.object_type.as_str(), related.id), ("Invoice", 27));
+
+        let query = Todo::filter_builder().build(Filter::new().page(2).subject_id(16));
+        assert_eq!(query.len(), 1);
+    }
+
+    #[test]
+    fn test_webhook_model() {
+        use crate::models::Webhook;
+
+        let mut webhook = Webhook::new("https://example.com/hooks", &["invoice_paid"]);
+        webhook.auth_header = Some("Bearer secret".to_string());
+        assert_eq!(
+            serde_json::to_value(&webhook).unwrap(),
+            serde_json::json!({
+                "webhook_url": "https://example.com/hooks",
+                "auth_header": "Bearer secret",
+                "events": ["invoice_paid"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_webhook_events() {
+        use crate::webhooks::{parse_verified, WebhookEvent, WebhookEventType};
+
+        let body = format!(
+            r#"{{"webhook_id": 3, "event_name": "invoice_paid",
+                "created_at": "2012-06-02T09:34:47.000+02:00", "body": {{"invoice": {}}}}}"#,
+            INVOICE
+        );
+        match parse_verified(body.as_bytes(), Some("Bearer s3cr3t"), "Bearer s3cr3t").unwrap() {
+            WebhookEvent::Invoice {
+                event_type,
+                invoice,
+            } => {
+                assert_eq!(event_type, WebhookEventType::InvoicePaid);
+                assert_eq!(invoice.number.as_deref(), Some("2012-0021"));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(parse_verified(body.as_bytes(), Some("Bearer s3cr3u"), "Bearer s3cr3t").is_err());
+        assert!(parse_verified(body.as_bytes(), None, "Bearer s3cr3t").is_err());
+        assert!(parse_verified(body.as_bytes(), None, "").is_err());
+        assert!(parse_verified(body.as_bytes(), Some(""), "").is_err());
+
+        let body = br#"{"event_name": "invoice_accepted", "invoice_id": 27}"#;
+        assert!(matches!(
+            parse_verified(body, Some("x"), "x").unwrap(),
+            WebhookEvent::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_invoice_attachments() {
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(invoice.attachments().len(), 1);
+        assert!(serde_json::to_value(&invoice)
+            .unwrap()
+            .get("attachment")
+            .is_none());
+
+        let mut invoice: Invoice = serde_json::from_str(
+            r#"{"attachments": [{"id": 4, "filename": "order.pdf",
+                "content_type": "application/pdf",
+                "download_url": "https://app.fakturoid.cz/api/v3/attachments/4/download"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(invoice.attachment().and_then(|a| a.id), Some(4));
+        assert_eq!(invoice.attachments()[0].file_name, "order.pdf");
+        assert!(serde_json::to_value(&invoice)
+            .unwrap()
+            .get("attachments")
+            .is_none());
+
+        invoice.add_attachment_bytes("note.txt", b"Hello");
+        assert_eq!(invoice.attachments().len(), 1);
+        let value = serde_json::to_value(&invoice).unwrap();
+        assert_eq!(
+            value["attachments"],
+            serde_json::json!([{
+                "filename": "note.txt",
+                "data_url": "data:text/plain;base64,SGVsbG8"
+            }])
+        );
+        assert!(value.get("attachment").is_none());
+    }
+
+    #[test]
+    fn test_invoice_attachment_reader() {
+        let mut invoice = Invoice::default();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(invoice.set_attachment_reader("note.txt", "text/markdown", &b"Hello"[..]))
+            .unwrap();
+        let value = serde_json::to_value(&invoice).unwrap();
+        assert_eq!(value["attachment"], "data:text/markdown;base64,SGVsbG8");
+        assert!(value.get("attachments").is_none());
+
+        let mut expense = Expense::default();
+        rt.block_on(expense.set_attachment_reader("note.txt", "text/markdown", &b"Hello"[..]))
+            .unwrap();
+        let expense_value = serde_json::to_value(&expense).unwrap();
+        assert_eq!(expense_value["attachment"], value["attachment"]);
+
+        expense.set_attachment_bytes("scan.PDF", "", b"Hello");
+        assert_eq!(
+            serde_json::to_value(&expense).unwrap()["attachment"],
+            "data:application/pdf;base64,SGVsbG8"
+        );
+    }
+
+    #[test]
+    fn test_attachment_error() {
+        use std::error::Error;
+        use std::path::Path;
+
+        let path = Path::new("/nonexistent/receipt.pdf");
+        let err = Invoice::default().set_attachment(path).unwrap_err();
+        assert_eq!(err.path(), path);
+        assert_eq!(err.io_error().kind(), std::io::ErrorKind::NotFound);
+        assert!(err.source().is_some());
+        assert!(err
+            .to_string()
+            .starts_with("Can't attach file /nonexistent/receipt.pdf: "));
+
+        let err = Invoice::default()
+            .add_attachment(Path::new("/"))
+            .unwrap_err();
+        assert_eq!(err.io_error().kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_builders() {
+        use crate::builders::InvoiceBuilder;
+        use rust_decimal::Decimal;
+
+        let err = Invoice::builder()
+            .item("Consulting", rust_decimal::Decimal::from(1500), 21)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.field(), "subject_id");
+        assert_eq!(
+            Invoice::builder()
+                .subject_id(16)
+                .build()
+                .unwrap_err()
+                .field(),
+            "lines"
+        );
+
+        let subject = Subject::builder()
+            .name("Apple Czech s.r.o.")
+            .address("Klimentská 1216/46", "Praha", "11000", "CZ")
+            .build()
+            .unwrap();
+        assert!(Subject::builder().email("a@b.cz").build().is_err());
+
+        let mut subject = subject;
+        subject.id = Some(16);
+        let invoice = InvoiceBuilder::for_subject(&subject)
+            .due(14)
+            .tag("web")
+            .line(
+                "Hosting",
+                Decimal::from(12),
+                Some("měs"),
+                Decimal::from(100),
+                21,
+            )
+            .build()
+            .unwrap();
+        assert_eq!(invoice.subject_id, Some(16));
+        assert_eq!(invoice.language, Some(InvoiceLanguage::Cz));
+        assert_eq!(invoice.lines.unwrap()[0].unit_name.as_deref(), Some("měs"));
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        use crate::client::RetryPolicy;
+        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+
+        let policy = RetryPolicy::new(5).initial_delay(Duration::from_millis(500));
+        assert_eq!(policy.delay(0, None), Duration::from_millis(500));
+        assert_eq!(policy.delay(3, None), Duration::from_secs(4));
+        assert_eq!(policy.delay(10, None), Duration::from_secs(60));
+        assert_eq!(
+            policy.delay(3, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(crate::retry::retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("12"));
+        assert_eq!(
+            crate::retry::retry_after(&headers),
+            Some(Duration::from_secs(12))
+        );
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(
+            crate::retry::retry_after(&headers),
+            Some(Duration::from_secs(0))
+        );
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_memory_response_cache() {
+        use crate::response_cache::{is_revalidated, CachedResponse, ResponseCache};
+
+        let url = "https://app.fakturoid.cz/api/v2/accounts/slug/invoices/27.json";
+        let response: reqwest::Response = http::Response::builder()
+            .header("ETag", "\"abc\"")
+            .body(INVOICE)
+            .unwrap()
+            .into();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let (entry, response) = rt
+            .block_on(CachedResponse::from_response(url, response))
+            .unwrap();
+        assert!(!is_revalidated(&response));
+
+        let cache = ResponseCache::memory(1);
+        cache.put(&entry.unwrap());
+        let entry = cache.get(url).unwrap();
+        let mut request = reqwest::Client::new().get(url).build().unwrap();
+        entry.apply_conditions(&mut request);
+        assert_eq!(request.headers()["If-None-Match"], "\"abc\"");
+
+        let replayed = entry.to_revalidated_response();
+        assert!(is_revalidated(&replayed));
+        let invoice: Invoice = rt.block_on(replayed.json()).unwrap();
+        assert_eq!(invoice.id, Some(27));
+
+        cache.put(&entry);
+        assert!(cache.get("https://app.fakturoid.cz/other").is_none());
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_response_cache_binary_body() {
+        use crate::response_cache::{CachedResponse, ResponseCache};
+        use std::fs;
+
+        let url = "https://app.fakturoid.cz/api/v2/accounts/slug/invoices/27/download.pdf";
+        let pdf = vec![0x25, 0x50, 0x44, 0x46, 0xff, 0xfe, 0x00, 0xc3, 0x28];
+        let response: reqwest::Response = http::Response::builder()
+            .header("ETag", "\"pdf\"")
+            .header("Content-Type", "application/pdf")
+            .body(pdf.clone())
+            .unwrap()
+            .into();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let (entry, response) = rt
+            .block_on(CachedResponse::from_response(url, response))
+            .unwrap();
+        assert_eq!(rt.block_on(response.bytes()).unwrap().to_vec(), pdf);
+
+        let dir = std::env::temp_dir().join("fakturoid_test_binary_cache");
+        let cache = ResponseCache::disk(dir.clone());
+        cache.put(&entry.unwrap());
+        let replayed = cache.get(url).unwrap().to_revalidated_response();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(rt.block_on(replayed.bytes()).unwrap().to_vec(), pdf);
+    }
+
+    #[test]
+    fn test_response_meta() {
+        use crate::client::ResponseMeta;
+        use reqwest::header::{HeaderMap, HeaderValue};
+        use reqwest::StatusCode;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "X-RateLimit-Policy",
+            HeaderValue::from_static("default;q=400;w=60"),
+        );
+        headers.insert(
+            "X-RateLimit",
+            HeaderValue::from_static("default;r=398;t=55"),
+        );
+        headers.insert("X-Request-Id", HeaderValue::from_static("a1b2c3"));
+        let meta = ResponseMeta::new(StatusCode::OK, headers, false);
+        assert_eq!(meta.rate_limit(), Some(400));
+        assert_eq!(meta.rate_limit_remaining(), Some(398));
+        assert_eq!(meta.rate_limit_reset(), Some(Duration::from_secs(55)));
+        assert_eq!(meta.request_id(), Some("a1b2c3"));
+        assert_eq!(meta.total_count(), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-RateLimit-Limit", HeaderValue::from_static("200"));
+        headers.insert("X-RateLimit-Remaining", HeaderValue::from_static("12"));
+        headers.insert("X-Total-Count", HeaderValue::from_static("87"));
+        let meta = ResponseMeta::new(StatusCode::OK, headers, true);
+        assert_eq!(meta.rate_limit(), Some(200));
+        assert_eq!(meta.rate_limit_remaining(), Some(12));
+        assert_eq!(meta.rate_limit_reset(), None);
+        assert_eq!(meta.total_count(), Some(87));
+        assert!(meta.was_cached());
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_client() {
+        use crate::blocking;
+
+        let client = blocking::Fakturoid::oauth_builder("id", "secret", "testslug")
+            .build_blocking()
+            .unwrap();
+        assert_eq!(client.api_version(), crate::client::ApiVersion::V3);
+        let clone = client.clone();
+        assert_eq!(clone.block_on(async { 40 + 2 }), 42);
+        clone.shutdown(Duration::from_secs(1)).unwrap();
+        assert!(client.inner().is_shut_down());
+        assert!(client.account().is_err());
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_blocking_client_concurrent_calls() {
+        use crate::blocking;
+        use futures::channel::oneshot;
+
+        let client = blocking::Fakturoid::builder("fake@user.com", "apicode", "testslug")
+            .build_blocking()
+            .unwrap();
+        let (sender, receiver) = oneshot::channel();
+        let waiting = client.clone();
+        let waiter = std::thread::spawn(move || waiting.block_on(receiver).unwrap());
+        std::thread::sleep(Duration::from_millis(50));
+        client.block_on(async move { sender.send(42).unwrap() });
+        assert_eq!(waiter.join().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_base_url() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..read]);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                ACCOUNT.len(),
+                ACCOUNT
+            )
+            .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let client = Fakturoid::builder("fake@user.com", "apicode", "testslug")
+            .http_client(reqwest::Client::new())
+            .base_url(&format!("http://{}", address))
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let account = rt.block_on(client.account()).unwrap();
+        assert_eq!(account.subdomain, "applecorp");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /api/v2/accounts/testslug/account.json HTTP/1.1\r\n"));
+    }
+
+    #[test]
+    fn test_mock_transport() {
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::GET,
+            "/subjects.json",
+            MockResponse::json(&format!("[{}]", SUBJECT)).header(
+                "Link",
+                r#"<https://app.fakturoid.cz/api/v2/accounts/slug/subjects.json?page=2>; rel="next""#,
+            ),
+        );
+        mock.on(
+            Method::POST,
+            "/subjects.json",
+            MockResponse::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                r#"{"errors": {"name": ["je povinná položka"]}}"#,
+            )
+            .header("Content-Type", "application/json"),
+        );
+        mock.json(Method::POST, "/subjects.json", SUBJECT);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let page = rt.block_on(client.list::<Subject>(None)).unwrap();
+        assert_eq!(page.data().len(), 1);
+        assert!(page.has_next());
+        let err = rt.block_on(client.create(Subject::default())).unwrap_err();
+        assert_eq!(err.kind(), &Kind::UnprocessableEntity);
+        let created = rt.block_on(client.create(Subject::default())).unwrap();
+        assert_eq!(created.id, Some(16));
+        assert!(rt.block_on(client.detail::<Invoice>(1)).is_err());
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[1].method, Method::POST);
+        assert_eq!(requests[1].body.as_deref(), Some("{}"));
+        assert!(requests[3].url.ends_with("/accounts/slug/invoices/1.json"));
+    }
+
+    #[test]
+    fn test_http_dump_keeps_url() {
+        use crate::dump::HttpDump;
+        use crate::transport::{MockTransport, Transport};
+        use reqwest::{Method, Request, Url};
+        use std::sync::{Arc, Mutex};
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/subjects.json", "[]");
+        let url =
+            Url::parse("https://app.fakturoid.cz/api/v2/accounts/slug/subjects.json").unwrap();
+        let dump = HttpDump::Writer(Arc::new(Mutex::new(Box::new(std::io::sink()))));
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let response = rt
+            .block_on(mock.execute(Request::new(Method::GET, url.clone())))
+            .unwrap();
+        assert_eq!(response.url(), &url);
+        let response = rt.block_on(dump.response(response)).unwrap();
+        assert_eq!(response.url(), &url);
+        assert_eq!(rt.block_on(response.text()).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_update_account() {
+        use crate::models::{AccountSettings, InvoiceLanguage, PaymentMethod};
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mut updated: serde_json::Value = serde_json::from_str(ACCOUNT).unwrap();
+        updated["due"] = serde_json::json!(30);
+        updated["invoice_language"] = serde_json::json!("en");
+        updated["invoice_payment_method"] = serde_json::json!("cash");
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/account.json", ACCOUNT);
+        mock.json(Method::PATCH, "/account.json", &updated.to_string());
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        assert_eq!(rt.block_on(client.cached_account()).unwrap().due, 14);
+        let settings = AccountSettings {
+            due: Some(30),
+            ..AccountSettings::default()
+        };
+        let account = rt.block_on(client.update_account(&settings)).unwrap();
+        assert_eq!(account.due, 30);
+        assert_eq!(account.invoice_language, Some(InvoiceLanguage::En));
+        assert_eq!(rt.block_on(client.cached_account()).unwrap().due, 30);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body.as_deref(), Some(r#"{"due":30}"#));
+
+        let invoice = Invoice::default().with_account_defaults(&account);
+        assert_eq!(invoice.language, Some(InvoiceLanguage::En));
+        assert!(matches!(invoice.payment_method, Some(PaymentMethod::Cash)));
+    }
+
+    #[test]
+    fn test_users() {
+        use crate::models::User;
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let user: User = serde_json::from_str(USER).unwrap();
+        assert_eq!(user.slugs(), vec!["applecorp", "testing"]);
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/api/v2/user.json", USER);
+        mock.json(Method::GET, "/users.json", &format!("[{}]", USER));
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let current = rt.block_on(client.current_user()).unwrap();
+        assert_eq!(current.default_account.as_deref(), Some("applecorp"));
+        let users = rt.block_on(client.account_users()).unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(users[0].permission.as_deref(), Some("owner"));
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].url, "https://app.fakturoid.cz/api/v2/user.json");
+        assert_eq!(
+            requests[1].url,
+            "https://app.fakturoid.cz/api/v2/accounts/slug/users.json"
+        );
+    }
+
+    #[test]
+    fn test_with_slug() {
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/accounts/first/account.json", ACCOUNT);
+        let mut second: serde_json::Value = serde_json::from_str(ACCOUNT).unwrap();
+        second["subdomain"] = serde_json::json!("second");
+        mock.json(
+            Method::GET,
+            "/accounts/second/account.json",
+            &second.to_string(),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "first")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let other = client.with_slug("second");
+        assert_eq!(client.slug(), "first");
+        assert_eq!(other.slug(), "second");
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let first_account = rt.block_on(client.cached_account()).unwrap();
+        let second_account = rt.block_on(other.cached_account()).unwrap();
+        assert_eq!(first_account.subdomain, "applecorp");
+        assert_eq!(second_account.subdomain, "second");
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[test]
+    fn test_typed_filters() {
+        use crate::filters::{
+            DocumentType, ExpenseFilters, Filter, InvoiceFilters, SubjectFilters,
+        };
+        use crate::models::{InvoiceDocumentType, InvoiceState};
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let filter = InvoiceFilters::new()
+            .status(InvoiceState::Overdue)
+            .subject_id(5);
+        let query = Filter::from(filter).into_query();
+        assert_eq!(query.get("status").map(String::as_str), Some("overdue"));
+        assert_eq!(query.get("subject_id").map(String::as_str), Some("5"));
+        let query = Filter::from(ExpenseFilters::new().status(ExpenseState::Paid)).into_query();
+        assert_eq!(query.get("status").map(String::as_str), Some("paid"));
+        let query =
+            Filter::from(InvoiceFilters::new().document_type(DocumentType::Proforma)).into_query();
+        assert_eq!(
+            query.get("document_type").map(String::as_str),
+            Some("proforma")
+        );
+        assert_eq!(DocumentType::TaxDocument.to_string(), "tax_document");
+        let query =
+            Filter::from(InvoiceFilters::new().document_type(InvoiceDocumentType::PartialProforma))
+                .into_query();
+        assert_eq!(
+            query.get("document_type").map(String::as_str),
+            Some("proforma")
+        );
+        assert_eq!(
+            DocumentType::from(InvoiceDocumentType::FinalInvoice),
+            DocumentType::Regular
+        );
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/subjects.json", &format!("[{}]", SUBJECT));
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let subjects = rt
+            .block_on(client.list_filtered(SubjectFilters::new().custom_id("CUST-1")))
+            .unwrap();
+        assert_eq!(subjects.data()[0].id, Some(16));
+        assert!(mock.requests()[0]
+            .url
+            .ends_with("/subjects.json?custom_id=CUST-1"));
+    }
+
+    #[test]
+    fn test_fulltext_filter() {
+        use crate::filters::{Filter, InvoiceFilters};
+        use crate::models::InvoiceState;
+        use crate::transport::MockTransport;
+        use reqwest::{Method, Url};
+        use std::collections::HashMap;
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/invoices/search.json",
+            &format!("[{}]", INVOICE),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let filter = InvoiceFilters::new().status(InvoiceState::Paid).page(3);
+        let invoices = rt
+            .block_on(client.fulltext::<Invoice>("ACME", Some(Filter::from(filter))))
+            .unwrap();
+        assert_eq!(invoices.data().len(), 1);
+        let filter = Filter::default().status(InvoiceState::Paid);
+        let subjects = rt.block_on(client.fulltext::<Subject>("ACME", Some(filter)));
+        assert!(subjects.is_err());
+
+        let requests = mock.requests();
+        let url = Url::parse(&requests[0].url).unwrap();
+        let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(query.len(), 3);
+        assert_eq!(query["query"], "ACME");
+        assert_eq!(query["status"], "paid");
+        assert_eq!(query["page"], "3");
+        let url = Url::parse(&requests[1].url).unwrap();
+        assert_eq!(url.query(), Some("query=ACME"));
+    }
+
+    #[test]
+    fn test_generator_actions() {
+        use crate::models::{GeneratorAction, GeneratorActivateData, InvoiceAction};
+        use crate::transport::MockTransport;
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::PATCH,
+            "/recurring_generators/15/activate.json",
+            crate::transport::MockResponse::new(StatusCode::NO_CONTENT, ""),
+        );
+        mock.on(
+            Method::PATCH,
+            "/recurring_generators/15/deactivate.json",
+            crate::transport::MockResponse::new(StatusCode::NO_CONTENT, ""),
+        );
+        mock.on(
+            Method::POST,
+            "/invoices/27/fire.json",
+            crate::transport::MockResponse::new(StatusCode::OK, ""),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let data = GeneratorActivateData {
+            next_occurrence_on: crate::dates::from_ymd(2021, 3, 1),
+        };
+        rt.block_on(client.action(15, GeneratorAction::Activate, Some(data)))
+            .unwrap();
+        rt.block_on(client.action(15, GeneratorAction::Deactivate, None::<()>))
+            .unwrap();
+        rt.block_on(client.action(27, InvoiceAction::Lock, None::<()>))
+            .unwrap();
+
+        let requests = mock.requests();
+        assert!(requests[0]
+            .url
+            .ends_with("/recurring_generators/15/activate.json?next_occurrence_on=2021-03-01"));
+        assert!(requests[1]
+            .url
+            .ends_with("/recurring_generators/15/deactivate.json"));
+        assert!(requests[2]
+            .url
+            .ends_with("/invoices/27/fire.json?event=lock"));
+    }
+
+    #[test]
+    fn test_invoice_actions() {
+        use crate::models::InvoicePayData;
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, Url};
+        use rust_decimal::Decimal;
+        use std::collections::HashMap;
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::POST,
+            "/fire.json",
+            MockResponse::new(reqwest::StatusCode::OK, ""),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(client.mark_as_sent(27)).unwrap();
+        let payment = InvoicePayData {
+            paid_amount: Some(Decimal::from(1500)),
+            ..InvoicePayData::default()
+        };
+        rt.block_on(client.pay(27, payment)).unwrap();
+        rt.block_on(client.cancel(28)).unwrap();
+
+        let queries: Vec<HashMap<String, String>> = mock
+            .requests()
+            .iter()
+            .map(|r| {
+                let url = Url::parse(&r.url).unwrap();
+                url.query_pairs().into_owned().collect()
+            })
+            .collect();
+        assert_eq!(queries[0].len(), 1);
+        assert_eq!(queries[0]["event"], "mark_as_sent");
+        assert_eq!(queries[1]["event"], "pay");
+        assert_eq!(queries[1]["paid_amount"], "1500");
+        assert_eq!(queries[2]["event"], "cancel");
+        assert!(mock.requests()[2].url.contains("/invoices/28/fire.json"));
+    }
+
+    #[test]
+    fn test_action_updated() {
+        use crate::models::{InvoiceAction, InvoiceState};
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::POST,
+            "/invoices/27/fire.json",
+            MockResponse::new(StatusCode::OK, ""),
+        );
+        mock.json(Method::GET, "/invoices/27.json", INVOICE);
+        let mut paid: serde_json::Value = serde_json::from_str(INVOICE).unwrap();
+        paid["status"] = serde_json::json!("paid");
+        mock.json(Method::POST, "/invoices/28/fire.json", &paid.to_string());
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let invoice = rt
+            .block_on(client.action_updated(27, InvoiceAction::MarkAsSent, None::<()>))
+            .unwrap();
+        assert_eq!(invoice.number.as_deref(), Some("2012-0021"));
+        let invoice = rt
+            .block_on(client.action_updated(28, InvoiceAction::Pay, None::<()>))
+            .unwrap();
+        assert!(matches!(invoice.status, Some(InvoiceState::Paid)));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].method, Method::GET);
+    }
+
+    #[test]
+    fn test_expense_payments() {
+        use crate::models::{ExpenseAction, ExpensePayment};
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+        use rust_decimal::Decimal;
+
+        let mut expense: serde_json::Value = serde_json::from_str(EXPENSE).unwrap();
+        expense["payments"] = serde_json::json!([{
+            "id": 3,
+            "paid_on": "2021-02-01",
+            "currency": "CZK",
+            "amount": "1200.0",
+            "native_amount": "1200.0",
+            "variable_symbol": "2021001",
+            "bank_account_id": null,
+            "created_at": "2021-02-01T10:00:00.000+01:00",
+            "updated_at": "2021-02-01T10:00:00.000+01:00"
+        }]);
+        assert_round_trip::<Expense>(&expense.to_string());
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::POST,
+            "/expenses/9/payments.json",
+            &expense["payments"][0].to_string(),
+        );
+        mock.json(Method::GET, "/expenses/9.json", &expense.to_string());
+        mock.on(
+            Method::DELETE,
+            "/expenses/9/payments/3.json",
+            MockResponse::new(StatusCode::NO_CONTENT, ""),
+        );
+        mock.on(
+            Method::POST,
+            "/expenses/9/fire.json",
+            MockResponse::new(StatusCode::OK, ""),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let payment = rt
+            .block_on(client.create_expense_payment(9, ExpensePayment::new(Decimal::from(1200))))
+            .unwrap();
+        assert_eq!(payment.id, Some(3));
+        let payments = rt.block_on(client.list_expense_payments(9)).unwrap();
+        assert_eq!(payments.len(), 1);
+        rt.block_on(client.delete_expense_payment(9, 3)).unwrap();
+        let locked = rt
+            .block_on(client.action_updated(9, ExpenseAction::Lock, None::<()>))
+            .unwrap();
+        assert_eq!(locked.payments.map(|p| p.len()), Some(1));
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].body.as_deref(), Some(r#"{"amount":"1200"}"#));
+        assert!(requests[3]
+            .url
+            .ends_with("/expenses/9/fire.json?event=lock"));
+    }
+
+    #[test]
+    fn test_changes_since() {
+        use crate::sync::Change;
+        use crate::transport::MockTransport;
+        use futures::TryStreamExt;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/subjects.json",
+            &format!("[{}, {}]", SUBJECT, SUBJECT),
+        );
+        mock.json(Method::GET, "/invoices.json", &format!("[{}]", INVOICE));
+        mock.json(Method::GET, "/expenses.json", &format!("[{}]", EXPENSE));
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let since = serde_json::from_str::<Subject>(SUBJECT).unwrap().updated_at;
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let changes: Vec<Change> = rt
+            .block_on(client.changes_since(since).try_collect())
+            .unwrap();
+        assert_eq!(changes.len(), 4);
+        let count = |f: fn(&Change) -> bool| changes.iter().filter(|c| f(c)).count();
+        assert_eq!(count(|c| matches!(c, Change::Subject(_))), 2);
+        assert_eq!(count(|c| matches!(c, Change::Invoice(_))), 1);
+        assert_eq!(count(|c| matches!(c, Change::Expense(_))), 1);
+        assert!(changes.iter().all(|c| c.updated_at().is_some()));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| r.url.contains("updated_since=")));
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/subjects.json", "[]");
+        mock.json(Method::GET, "/expenses.json", "[]");
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock)
+            .build()
+            .unwrap();
+        let result: Result<Vec<Change>, _> = rt.block_on(client.changes_since(None).try_collect());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_many() {
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::POST,
+            "/subjects.json",
+            MockResponse::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                r#"{"errors": {"name": ["je povinná položka"]}}"#,
+            )
+            .header("Content-Type", "application/json"),
+        );
+        mock.json(Method::POST, "/subjects.json", SUBJECT);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .rate_limit(200, 50)
+            .build()
+            .unwrap();
+        let subjects = vec![Subject::default(); 5];
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let results = rt.block_on(client.create_many(subjects, 2));
+        assert_eq!(results.len(), 5);
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        assert!(results.iter().skip(1).all(|r| r.is_ok()));
+        assert_eq!(mock.requests().len(), 5);
+        assert!(rt
+            .block_on(client.create_many(Vec::<Subject>::new(), 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_upsert() {
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/subjects.json", &format!("[{}]", SUBJECT));
+        mock.json(Method::PATCH, "/subjects/16.json", SUBJECT);
+        mock.json(Method::POST, "/subjects.json", SUBJECT);
+        mock.json(Method::GET, "/invoices.json", &format!("[{}]", INVOICE));
+        mock.json(Method::POST, "/invoices.json", INVOICE);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let subject = Subject {
+            custom_id: Some("10".to_string()),
+            email: Some("info@example.com".to_string()),
+            ..Subject::default()
+        };
+        let upserted = rt.block_on(client.upsert_subject(subject)).unwrap();
+        assert!(!upserted.was_created());
+        assert_eq!(upserted.into_inner().id, Some(16));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].url.contains("custom_id=10"));
+        assert_eq!(requests[1].method, Method::PATCH);
+        assert!(!requests[1].body.as_ref().unwrap().contains("\"name\""));
+
+        let subject = Subject {
+            custom_id: Some("11".to_string()),
+            ..Subject::default()
+        };
+        let upserted = rt.block_on(client.upsert_subject(subject)).unwrap();
+        assert!(upserted.was_created());
+        assert_eq!(mock.requests()[3].method, Method::POST);
+
+        let invoice = Invoice::builder()
+            .subject_id(16)
+            .custom_id("eshop-10045")
+            .item("Consulting", rust_decimal::Decimal::from(1500), 21)
+            .build()
+            .unwrap();
+        let upserted = rt.block_on(client.upsert_invoice(invoice)).unwrap();
+        assert!(upserted.was_created());
+        assert_eq!(mock.requests().len(), 6);
+
+        let err = rt
+            .block_on(client.upsert_subject(Subject::default()))
+            .unwrap_err();
+        assert!(err.to_string().contains("custom_id"));
+        assert_eq!(mock.requests().len(), 6);
+    }
+
+    #[test]
+    fn test_update_fields() {
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(Method::GET, "/invoices/27.json", INVOICE);
+        mock.json(Method::PATCH, "/invoices/27.json", INVOICE);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let invoice: Invoice = rt
+            .block_on(client.update_fields(27, |invoice: &mut Invoice| {
+                invoice.note = Some("Changed note".to_string());
+                invoice.order_number = None;
+            }))
+            .unwrap();
+        assert_eq!(invoice.id, Some(27));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, Method::PATCH);
+        let patch: serde_json::Value =
+            serde_json::from_str(requests[1].body.as_ref().unwrap()).unwrap();
+        let patch = patch.as_object().unwrap();
+        assert_eq!(patch.len(), 2);
+        assert_eq!(patch["note"], "Changed note");
+        assert!(patch["order_number"].is_null());
+
+        rt.block_on(client.update_fields(27, |_: &mut Invoice| {}))
+            .unwrap();
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_currency() {
+        use crate::error::InvalidCurrency;
+        use rust_decimal::Decimal;
+
+        assert_eq!(" czk ".parse::<Currency>(), Ok(Currency::CZK));
+        assert_eq!(Currency::new("EUR").unwrap().to_string(), "EUR");
+        assert_eq!(Currency::USD, "usd");
+        assert_eq!(Currency::new("EU").unwrap_err(), InvalidCurrency::new("EU"));
+        assert!(Currency::new("EUR1").is_err());
+        assert!(Currency::new("€UR").is_err());
+
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        assert_eq!(invoice.currency, Some(Currency::CZK));
+        assert_eq!(invoice.exchange_rate, Some(Decimal::new(10, 1)));
+        let json = serde_json::to_value(&invoice).unwrap();
+        assert_eq!(json["currency"], "CZK");
+        assert_eq!(json["exchange_rate"], "1.0");
+        assert!(serde_json::from_str::<Invoice>(r#"{"currency": "Kč"}"#).is_err());
+    }
+
+    #[test]
+    fn test_money() {
+        use crate::currency::Money;
+        use rust_decimal::Decimal;
+
+        let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let total = invoice.total_money().unwrap();
+        assert_eq!(total.currency, Currency::CZK);
+        assert_eq!(Some(total.amount), invoice.total);
+        let paid = invoice
+            .paid_money()
+            .unwrap_or_else(|| Money::zero(Currency::CZK));
+        assert_eq!(total.checked_sub(&paid), invoice.remaining_money());
+        assert_eq!(
+            invoice.native_total_money(Currency::CZK).map(|m| m.amount),
+            invoice.native_total
+        );
+        assert_eq!(Invoice::default().total_money(), None);
+
+        let price = Money::new(Decimal::new(100005, 3), Currency::EUR);
+        assert_eq!(price.to_string(), "100.01 EUR");
+        assert_eq!((-price).round().amount, Decimal::new(-10001, 2));
+        let items = [price, price * Decimal::from(2)];
+        assert_eq!(
+            Money::sum(Currency::EUR, items.iter()).unwrap().amount,
+            Decimal::new(300015, 3)
+        );
+        assert_eq!(Money::sum(Currency::CZK, items.iter()), None);
+        assert_eq!(price.checked_sub(&Money::zero(Currency::USD)), None);
+        assert!(Money::zero(Currency::EUR).is_zero());
+
+        let expense: Expense = serde_json::from_str(EXPENSE).unwrap();
+        assert_eq!(expense.total_money().map(|m| m.amount), expense.total);
+    }
+
+    #[test]
+    fn test_vat_rates() {
+        use crate::models::InvoiceLine;
+        use crate::tax::{validate_vat_rates, vat_country, TaxIssue, VatRate};
+        use rust_decimal::Decimal;
+
+        assert_eq!(VatRate::Standard.percent("CZ"), Some(Decimal::from(21)));
+        assert_eq!(VatRate::Reduced(0).percent("cz"), Some(Decimal::from(12)));
+        assert_eq!(VatRate::Reduced(1).percent("CZ"), None);
+        assert_eq!(VatRate::Reduced(1).percent("FR"), Some(Decimal::new(55, 1)));
+        assert_eq!(VatRate::Zero.percent("DE"), Some(Decimal::ZERO));
+        assert_eq!(VatRate::Standard.percent("US"), None);
+        assert_eq!(
+            VatRate::from_percent("SK", Decimal::from(19)),
+            Some(VatRate::Reduced(0))
+        );
+        assert_eq!(VatRate::from_percent("CZ", Decimal::from(15)), None);
+
+        let mut invoice = Invoice::default();
+        invoice.language = Some(InvoiceLanguage::Sk);
+        assert_eq!(vat_country(&invoice).as_deref(), Some("SK"));
+        assert_eq!(
+            invoice.resolve_vat_rate(VatRate::Standard),
+            Some(Decimal::from(23))
+        );
+        invoice.your_country = Some("CZ".to_string());
+        assert_eq!(vat_country(&invoice).as_deref(), Some("CZ"));
+        for vat_rate in [21, 12, 0, 15].iter() {
+            invoice.add_line(InvoiceLine::new(
+                "Item",
+                Decimal::from(1),
+                None,
+                Decimal::from(100),
+                *vat_rate,
+            ));
+        }
+        assert_eq!(
+            validate_vat_rates(&invoice),
+            Err(vec![TaxIssue::VatRateNotAllowed {
+                line: 3,
+                vat_rate: Decimal::from(15)
+            }])
+        );
+        invoice.remove_line(3);
+        assert!(validate_vat_rates(&invoice).is_ok());
+
+        invoice.your_country = Some("FR".to_string());
+        invoice.lines = None;
+        let rate = invoice.resolve_vat_rate(VatRate::Reduced(1)).unwrap();
+        invoice.add_line(InvoiceLine::new(
+            "Book",
+            Decimal::ONE,
+            None,
+            Decimal::TEN,
+            rate,
+        ));
+        assert!(validate_vat_rates(&invoice).is_ok());
+
+        invoice.your_country = Some("US".to_string());
+        invoice.add_line(InvoiceLine::new(
+            "Item",
+            Decimal::from(1),
+            None,
+            Decimal::from(1),
+            7,
+        ));
+        assert!(validate_vat_rates(&invoice).is_ok());
+        assert_eq!(invoice.resolve_vat_rate(VatRate::Standard), None);
+    }
+
+    #[test]
+    fn test_eu_electronic_service() {
+        use crate::models::InvoiceLine;
+        use crate::tax::{validate_eu_electronic_service, TaxIssue};
+        use rust_decimal::Decimal;
+
+        let mut invoice = Invoice::default();
+        invoice.eu_electronic_service = Some(true);
+        invoice.your_country = Some("CZ".to_string());
+        for (country, rate) in [("FI", 255), ("FR", 55), ("IE", 135), ("DE", 190)].iter() {
+            invoice.client_country = Some(country.to_string());
+            invoice.lines = None;
+            let rate = Decimal::new(*rate, 1);
+            invoice.add_line(InvoiceLine::new(
+                "E-book",
+                Decimal::ONE,
+                None,
+                Decimal::TEN,
+                rate,
+            ));
+            assert!(validate_eu_electronic_service(&invoice).is_ok());
+        }
+        invoice.client_country = Some("FI".to_string());
+        invoice.lines = None;
+        invoice.add_line(InvoiceLine::new(
+            "E-book",
+            Decimal::ONE,
+            None,
+            Decimal::TEN,
+            25,
+        ));
+        assert_eq!(
+            validate_eu_electronic_service(&invoice),
+            Err(vec![TaxIssue::VatRateNotAllowed {
+                line: 0,
+                vat_rate: Decimal::from(25)
+            }])
+        );
+        let totals = invoice.compute_totals();
+        invoice.lines.as_mut().unwrap()[0].vat_rate = Decimal::new(255, 1);
+        assert_eq!(totals.vat, Decimal::new(250, 2));
+        assert_eq!(invoice.compute_totals().vat, Decimal::new(255, 2));
+    }
+
+    #[test]
+    fn test_compute_totals() {
+        use crate::models::{InvoiceLine, VatPriceMode, VatRecapitulation};
+        use rust_decimal::Decimal;
+
+        let mut invoice = Invoice::default();
+        invoice.vat_price_mode = Some(VatPriceMode::FromTotalWithVat);
+        invoice.round_total = Some(true);
+        invoice.add_line(InvoiceLine::new(
+            "Work",
+            Decimal::new(15, 1),
+            Some("h"),
+            Decimal::from(1000),
+            21,
+        ));
+        invoice.add_line(InvoiceLine::new(
+            "Material",
+            Decimal::from(1),
+            None,
+            Decimal::new(9999, 2),
+            15,
+        ));
+        let totals = invoice.compute_totals();
+        assert_eq!(
+            totals.rates,
+            vec![
+                VatRecapitulation {
+                    vat_rate: Decimal::from(15),
+                    base: Decimal::new(8695, 2),
+                    vat: Decimal::new(1304, 2),
+                },
+                VatRecapitulation {
+                    vat_rate: Decimal::from(21),
+                    base: Decimal::new(123967, 2),
+                    vat: Decimal::new(26033, 2),
+                },
+            ]
+        );
+        assert_eq!(totals.subtotal, Decimal::new(132662, 2));
+        assert_eq!(totals.vat, Decimal::new(27337, 2));
+        assert_eq!(totals.total, Decimal::from(1600));
+        assert_eq!(totals.rounding, Decimal::new(1, 2));
+        assert_eq!(invoice.total, None);
+
+        for fixture in &[INVOICE, INVOICE_ROUNDED] {
+            let invoice: Invoice = serde_json::from_str(fixture).unwrap();
+            let totals = invoice.compute_totals();
+            assert_eq!(Some(totals.subtotal), invoice.subtotal);
+            assert_eq!(Some(totals.total), invoice.total);
+        }
+    }
+
+    #[test]
+    fn test_destroy_line() {
+        use crate::transport::MockTransport;
+        use reqwest::Method;
+
+        let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        let total = invoice.compute_totals().total;
+        assert!(invoice.destroy_line_by_id(1304));
+        assert!(!invoice.destroy_line_by_id(1));
+        assert!(invoice.lines.as_ref().unwrap()[0].is_destroyed());
+        assert!(invoice.compute_totals().total < total);
+
+        let json = serde_json::to_value(&invoice).unwrap();
+        assert_eq!(json["lines"][0]["_destroy"], true);
+        assert!(json["lines"][1].get("_destroy").is_none());
+        let parsed: Invoice = serde_json::from_value(json).unwrap();
+        assert!(parsed.lines.unwrap()[0].is_destroyed());
+
+        let mock = MockTransport::new();
+        mock.json(Method::PATCH, "/invoices/27.json", INVOICE);
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut update = Invoice::default();
+        update.lines = invoice.lines;
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(client.update(27, update)).unwrap();
+        let body = mock.requests()[0].body.clone().unwrap();
+        assert!(body.contains(r#""id":1304"#));
+        assert!(body.contains(r#""_destroy":true"#));
+    }
+
+    #[test]
+    fn test_inventory_lines() {
+        use crate::models::{InventoryItem, InvoiceLine};
+        use rust_decimal::Decimal;
+
+        let item = InventoryItem {
+            id: Some(26),
+            name: Some("Keyboard".to_string()),
+            unit_name: Some("ks".to_string()),
+            native_retail_price: Some(Decimal::from(990)),
+            ..InventoryItem::default()
+        };
+        let line = InvoiceLine::for_inventory_item(&item, Decimal::from(2), 21);
+        assert_eq!(line.name, "Keyboard");
+        assert_eq!(line.unit_price, Decimal::from(990));
+        let json = serde_json::to_value(&line).unwrap();
+        assert_eq!(json["inventory_item_id"], 26);
+        assert!(json.get("inventory").is_none());
+
+        let line: InvoiceLine = serde_json::from_str(
+            r#"{"id": 1304, "name": "Keyboard", "quantity": "2.0", "unit_name": "ks",
+            "unit_price": "990.0", "vat_rate": 21, "inventory": {"item_id": 26,
+            "sku": "KB-01", "article_number_type": "ean", "article_number": "8594",
+            "move_id": 55}}"#,
+        )
+        .unwrap();
+        assert_eq!(line.inventory.as_ref().unwrap().item_id, 26);
+        assert_eq!(line.inventory_move_id(), Some(55));
+        assert!(serde_json::to_value(&line)
+            .unwrap()
+            .get("inventory")
+            .is_none());
+    }
+
+    #[cfg(feature = "qr")]
+    #[test]
+    fn test_payment_qr() {
+        use crate::error::PaymentQrError;
+        use crate::qr::PaymentQr;
+        use rust_decimal::Decimal;
+
+        let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+        invoice.remaining_amount = Some(Decimal::new(5005, 1));
+        invoice.bank_account = Some("19-2000145399/0800".to_string());
+        invoice.swift_bic = Some("gibacz px".to_string());
+        invoice.number = Some("2012*21".to_string());
+        let qr = PaymentQr::from_invoice(&invoice).unwrap();
+        assert_eq!(qr.iban, "CZ6508000000192000145399");
+        assert_eq!(
+            qr.to_spayd(),
+            "SPD*1.0*ACC:CZ6508000000192000145399+GIBACZPX*AM:500.50*CC:CZK*DT:20111023\
+             *MSG:Faktura 2012%2A21*RN:Alexandr Hejsek*X-VS:20120021"
+        );
+        assert_eq!(
+            invoice.payment_qr_epc(),
+            Err(PaymentQrError::UnsupportedCurrency(Currency::CZK))
+        );
+
+        invoice.currency = Some(Currency::EUR);
+        invoice.iban = Some("SK31 1200 0000 1987 4263 7541".to_string());
+        invoice.swift_bic = None;
+        assert_eq!(
+            invoice.payment_qr_epc().unwrap(),
+            "BCD\n002\n1\nSCT\n\nAlexandr Hejsek\nSK3112000000198742637541\nEUR500.50\n\n\n\
+             /VS20120021/ Faktura 2012*21"
+        );
+
+        invoice.iban = Some("SK32 1200 0000 1987 4263 7541".to_string());
+        assert!(matches!(
+            invoice.payment_qr(),
+            Err(PaymentQrError::InvalidAccount(_))
+        ));
+        invoice.iban = None;
+        invoice.bank_account = Some("1234".to_string());
+        assert!(invoice.payment_qr().is_err());
+        invoice.bank_account = None;
+        assert_eq!(
+            invoice.payment_qr(),
+            Err(PaymentQrError::MissingField("iban"))
+        );
+
+        invoice.iban = Some("SK31 1200 0000 1987 4263 7541".to_string());
+        invoice.variable_symbol = Some("12345678901".to_string());
+        assert_eq!(
+            invoice.payment_qr(),
+            Err(PaymentQrError::InvalidVariableSymbol(
+                "12345678901".to_string()
+            ))
+        );
+        invoice.variable_symbol = Some("2012-21".to_string());
+        assert!(invoice.payment_qr().is_err());
+        invoice.variable_symbol = None;
+        invoice.remaining_amount = Some(Decimal::ZERO);
+        assert_eq!(invoice.payment_qr(), Err(PaymentQrError::NothingToPay));
+    }
+
+    #[cfg(feature = "ares")]
+    #[test]
+    fn test_ares() {
+        use crate::ares::Ares;
+        use crate::error::Kind;
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/ekonomicke-subjekty/27074358",
+            r#"{"ico": "27074358", "obchodniJmeno": "Asseco Central Europe, a.s.",
+                "sidlo": {"kodStatu": "CZ", "nazevObce": "Praha", "nazevCastiObce": "Michle",
+                "nazevUlice": "Budějovická", "cisloDomovni": 778, "cisloOrientacni": 3,
+                "cisloOrientacniPismeno": "a", "psc": 14000}, "dic": "CZ27074358"}"#,
+        );
+        mock.json(
+            Method::GET,
+            "/ekonomicke-subjekty/00064581",
+            r#"{"ico": "00064581", "obchodniJmeno": "Obec Lhota",
+                "sidlo": {"kodStatu": "CZ", "nazevObce": "Lhota", "nazevCastiObce": "Lhota",
+                "cisloDomovni": 12, "psc": 2001}}"#,
+        );
+        mock.on(
+            Method::GET,
+            "/ekonomicke-subjekty/25596641",
+            MockResponse::new(StatusCode::NOT_FOUND, "{}"),
+        );
+        let ares = Ares::new().transport(mock.clone());
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let subject = rt.block_on(ares.subject(" 27074358 ")).unwrap();
+        assert_eq!(subject.registration_no.as_deref(), Some("27074358"));
+        assert_eq!(subject.name.as_deref(), Some("Asseco Central Europe, a.s."));
+        assert_eq!(subject.vat_no.as_deref(), Some("CZ27074358"));
+        assert_eq!(subject.street.as_deref(), Some("Budějovická 778/3a"));
+        assert_eq!(subject.city.as_deref(), Some("Praha"));
+        assert_eq!(subject.zip.as_deref(), Some("14000"));
+        assert_eq!(subject.country.as_deref(), Some("CZ"));
+
+        let mut subject: Subject = serde_json::from_str(SUBJECT).unwrap();
+        subject.registration_no = Some("64581".to_string());
+        let email = subject.email.clone();
+        rt.block_on(ares.fill_subject(&mut subject)).unwrap();
+        assert_eq!(subject.registration_no.as_deref(), Some("00064581"));
+        assert_eq!(subject.street.as_deref(), Some("Lhota 12"));
+        assert_eq!(subject.zip.as_deref(), Some("02001"));
+        assert_eq!(subject.email, email);
+
+        let err = rt.block_on(ares.lookup("25596641")).unwrap_err();
+        assert_eq!(err.kind(), &Kind::EntityDoesNotExists);
+        assert!(rt.block_on(ares.lookup("27074359")).is_err());
+        assert!(rt.block_on(ares.lookup("IČO")).is_err());
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_into_stream_is_send() {
+        use crate::transport::MockTransport;
+        use futures::TryStreamExt;
+        use reqwest::Method;
+
+        let mock = MockTransport::new();
+        mock.json(
+            Method::GET,
+            "/subjects.json",
+            r#"[{"id": 1, "name": "Alpha"}, {"id": 2, "name": "Beta"}]"#,
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock)
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        let subjects = rt.block_on(client.list::<Subject>(None)).unwrap();
+        let stream = subjects.into_stream();
+        let names = rt
+            .block_on(rt.spawn(async move {
+                stream
+                    .map_ok(|s| s.name)
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .map_err(|err| err.to_string())
+            }))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            names,
+            vec![Some("Alpha".to_string()), Some("Beta".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_custom_endpoints() {
+        use crate::error::Kind;
+        use crate::transport::{MockResponse, MockTransport};
+        use reqwest::{Method, StatusCode};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Note {
+            id: Option<i32>,
+            text: String,
+        }
+        crate::entity!(Note, "notes");
+
+        let mock = MockTransport::new();
+        mock.on(
+            Method::GET,
+            "/subjects/16/notes.json",
+            MockResponse::json(r#"[{"id": 1, "text": "Call back"}]"#).header(
+                "Link",
+                "<https://app.fakturoid.cz/api/v2/accounts/slug/subjects/16/notes.json?page=2>; \
+                 rel=\"next\"",
+            ),
+        );
+        mock.json(
+            Method::POST,
+            "/subjects/16/notes.json",
+            r#"{"id": 2, "text": "Sent offer"}"#,
+        );
+        mock.on(
+            Method::DELETE,
+            "/subjects/16/notes/2.json",
+            MockResponse::new(StatusCode::NO_CONTENT, ""),
+        );
+        mock.on(
+            Method::PATCH,
+            "/subjects/16/notes/2.json",
+            MockResponse::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                r#"{"errors": {"text": ["je povinná položka"]}}"#,
+            ),
+        );
+        let client = Fakturoid::builder("fake@user.com", "apicode", "slug")
+            .transport(mock.clone())
+            .build()
+            .unwrap();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        let notes = rt
+            .block_on(client.list_url::<Note>("subjects/16/notes.json", None))
+            .unwrap();
+        assert_eq!(notes.data()[0].text, "Call back");
+        assert!(notes.has_next());
+
+        let note: Note = rt
+            .block_on(client.raw_request(
+                Method::POST,
+                "/subjects/16/notes.json",
+                Some(Note {
+                    id: None,
+                    text: "Sent offer".to_string(),
+                }),
+            ))
+            .unwrap();
+        assert_eq!(note.id, Some(2));
+        rt.block_on(client.raw_request::<(), ()>(Method::DELETE, "subjects/16/notes/2.json", None))
+            .unwrap();
+        let err = rt
+            .block_on(client.raw_request::<Note, _>(
+                Method::PATCH,
+                "subjects/16/notes/2.json",
+                Some(serde_json::json!({"text": ""})),
+            ))
+            .unwrap_err();
+        assert_eq!(err.kind(), &Kind::UnprocessableEntity);
+        assert!(err.data_errors().unwrap().contains_key("text"));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[0]
+            .url
+            .ends_with("/api/v2/accounts/slug/subjects/16/notes.json"));
+        assert!(requests[1]
+            .body
+            .as_ref()
+            .unwrap()
+            .contains("\"text\":\"Sent offer\""));
+        assert_eq!(requests[2].body, None);
+
+        let err = rt
+            .block_on(client.raw_request::<Note, ()>(
+                Method::GET,
+                "https://evil.example.com/app.fakturoid.cz/notes.json",
+                None,
+            ))
+            .unwrap_err();
+        assert!(err.to_string().contains("outside of Fakturoid"));
+        rt.block_on(client.raw_request::<(), ()>(
+            Method::DELETE,
+            "https://app.fakturoid.cz/api/v2/accounts/slug/subjects/16/notes/2.json",
+            None,
+        ))
+        .unwrap();
+        assert_eq!(mock.requests().len(), 5);
+    }
+
+    #[test]
+    fn test_fixtures_round_trip() {
+        assert_round_trip::<Account>(ACCOUNT);
+        assert_round_trip::<Subject>(SUBJECT);
+        assert_round_trip::<Invoice>(INVOICE);
+        assert_round_trip::<Invoice>(INVOICE_ROUNDED);
+        assert_round_trip::<Expense>(EXPENSE);
+        assert_round_trip::<Generator>(GENERATOR);
+        assert_round_trip::<crate::models::User>(USER);
+    }
 }
@@ -0,0 +1,370 @@
+//! Bulk import of subjects, invoices and expenses exported from other systems.
+//!
+//! Records are created with bounded concurrency, failures which guarantee that record wasn't
+//! created (rate limit, failed connection) are retried and result of every record is reported.
+//! Server errors and timeouts are not retried, record might have been stored before the failure
+//! and it would be created twice, they are reported as failed. Keys of imported records
+//! are appended to resume file, so interrupted import can be run again without creating
+//! duplicates.
+//!
+//! Invoices usually refer to subjects, so subjects should be imported first and ids of created
+//! subjects (see [`ImportReport::ids()`](struct.ImportReport.html#method.ids)) filled into
+//! invoices.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::migration::{ImportItem, Importer};
+//! use fakturoid::models::{Invoice, Subject};
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+//!         .rate_limit(200, 0)
+//!         .build()?;
+//!     let importer = Importer::new(client)
+//!         .concurrency(4)
+//!         .resume_file("subjects.resume");
+//!
+//!     let mut subject = Subject::default();
+//!     subject.name = Some("Apple Czech s.r.o.".to_string());
+//!     let report = importer
+//!         .run(vec![ImportItem::subject("C-1001", subject)])
+//!         .await?;
+//!     for (key, message) in report.failures() {
+//!         eprintln!("{} failed: {}", key, message);
+//!     }
+//!
+//!     let ids = report.ids();
+//!     let mut invoice = Invoice::default();
+//!     invoice.subject_id = ids.get("C-1001").copied();
+//!     let report = importer
+//!         .resume_file("invoices.resume")
+//!         .run(vec![ImportItem::invoice("INV-2020-001", invoice)])
+//!         .await?;
+//!     println!("{} invoices created", report.created());
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::Fakturoid;
+use crate::error::{FakturoidError, Kind};
+use crate::models::{Expense, Invoice, Subject};
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::time::delay_for;
+
+/// Record to be created.
+#[derive(Clone, Debug)]
+pub enum ImportRecord {
+    Subject(Box<Subject>),
+    Invoice(Box<Invoice>),
+    Expense(Box<Expense>),
+}
+
+/// Record with key identifying it in source system, e.g. its id or number there.
+#[derive(Clone, Debug)]
+pub struct ImportItem {
+    pub key: String,
+    pub record: ImportRecord,
+}
+
+impl ImportItem {
+    pub fn subject(key: &str, subject: Subject) -> Self {
+        Self {
+            key: key.to_string(),
+            record: ImportRecord::Subject(Box::new(subject)),
+        }
+    }
+
+    pub fn invoice(key: &str, invoice: Invoice) -> Self {
+        Self {
+            key: key.to_string(),
+            record: ImportRecord::Invoice(Box::new(invoice)),
+        }
+    }
+
+    pub fn expense(key: &str, expense: Expense) -> Self {
+        Self {
+            key: key.to_string(),
+            record: ImportRecord::Expense(Box::new(expense)),
+        }
+    }
+}
+
+/// What happened with one record.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportOutcome {
+    /// Record was created with given id.
+    Created(i32),
+    /// Record already exists: it was imported by previous run according to resume file or it
+    /// was found in account (see `Importer::import_expenses_csv()`).
+    Skipped(i32),
+    /// Record wasn't created, error of last attempt is kept.
+    Failed { message: String, attempts: u32 },
+}
+
+/// Result of one record.
+#[derive(Clone, Debug)]
+pub struct ImportResult {
+    pub key: String,
+    pub outcome: ImportOutcome,
+}
+
+/// Results of all records in order in which they were finished.
+#[derive(Clone, Debug, Default)]
+pub struct ImportReport {
+    pub results: Vec<ImportResult>,
+}
+
+impl ImportReport {
+    /// Number of created records.
+    pub fn created(&self) -> usize {
+        self.count(|o| matches!(o, ImportOutcome::Created(_)))
+    }
+
+    /// Number of records skipped thanks to resume file.
+    pub fn skipped(&self) -> usize {
+        self.count(|o| matches!(o, ImportOutcome::Skipped(_)))
+    }
+
+    /// Number of failed records.
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, ImportOutcome::Failed { .. }))
+    }
+
+    /// Keys of failed records with error messages.
+    pub fn failures(&self) -> Vec<(&str, &str)> {
+        self.results
+            .iter()
+            .filter_map(|r| match &r.outcome {
+                ImportOutcome::Failed { message, .. } => Some((r.key.as_str(), message.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Ids of created and skipped records by their keys.
+    pub fn ids(&self) -> HashMap<String, i32> {
+        self.results
+            .iter()
+            .filter_map(|r| match r.outcome {
+                ImportOutcome::Created(id) | ImportOutcome::Skipped(id) => {
+                    Some((r.key.clone(), id))
+                }
+                ImportOutcome::Failed { .. } => None,
+            })
+            .collect()
+    }
+
+    fn count<F: Fn(&ImportOutcome) -> bool>(&self, predicate: F) -> usize {
+        self.results
+            .iter()
+            .filter(|r| predicate(&r.outcome))
+            .count()
+    }
+}
+
+/// Imports records into account of client. Use `FakturoidBuilder::rate_limit()` for client, so
+/// import stays within API rate limit.
+#[derive(Clone)]
+pub struct Importer {
+    client: Fakturoid,
+    concurrency: usize,
+    max_attempts: u32,
+    retry_delay: Duration,
+    resume_file: Option<PathBuf>,
+}
+
+impl Importer {
+    /// Creates importer with concurrency 2, three attempts per record and no resume file.
+    pub fn new(client: Fakturoid) -> Self {
+        Self {
+            client,
+            concurrency: 2,
+            max_attempts: 3,
+            retry_delay: Duration::from_secs(2),
+            resume_file: None,
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    pub(crate) fn client(&self) -> &Fakturoid {
+        &self.client
+    }
+
+    /// Maximum number of records created at once.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// How many times record is attempted when request was rejected by rate limit or connection
+    /// failed.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Delay before next attempt, it is multiplied by number of attempt.
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// File where keys and ids of imported records are appended. Records listed in it are
+    /// skipped, so interrupted import can be resumed.
+    pub fn resume_file<P: AsRef<Path>>(&self, path: P) -> Self {
+        let mut importer = self.clone();
+        importer.resume_file = Some(path.as_ref().to_path_buf());
+        importer
+    }
+
+    /// Imports records. Failures of individual records are reported, error is returned only if
+    /// resume file can't be read or written.
+    pub async fn run<I>(&self, items: I) -> Result<ImportReport, FakturoidError>
+    where
+        I: IntoIterator<Item = ImportItem>,
+    {
+        let imported = match self.resume_file.as_ref() {
+            Some(path) => read_resume_file(path)?,
+            None => HashMap::new(),
+        };
+        let mut resume = match self.resume_file.as_ref() {
+            Some(path) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(FakturoidError::from_std_err)?,
+            ),
+            None => None,
+        };
+
+        let mut report = ImportReport::default();
+        let mut pending = Vec::new();
+        for item in items {
+            match imported.get(&item.key) {
+                Some(id) => report.results.push(ImportResult {
+                    key: item.key,
+                    outcome: ImportOutcome::Skipped(*id),
+                }),
+                None => pending.push(item),
+            }
+        }
+
+        let mut imports = stream::iter(pending)
+            .map(|item| async move {
+                let outcome = self.import(&item.record).await;
+                ImportResult {
+                    key: item.key,
+                    outcome,
+                }
+            })
+            .buffer_unordered(self.concurrency);
+        while let Some(result) = imports.next().await {
+            if let (ImportOutcome::Created(id), Some(file)) = (&result.outcome, resume.as_mut()) {
+                writeln!(file, "{}\t{}", escape_key(&result.key), id)
+                    .and_then(|_| file.flush())
+                    .map_err(FakturoidError::from_std_err)?;
+            }
+            report.results.push(result);
+        }
+        Ok(report)
+    }
+
+    async fn import(&self, record: &ImportRecord) -> ImportOutcome {
+        let mut attempt = 1;
+        loop {
+            let result = match record {
+                ImportRecord::Subject(subject) => {
+                    self.client.create((**subject).clone()).await.map(|s| s.id)
+                }
+                ImportRecord::Invoice(invoice) => {
+                    self.client.create((**invoice).clone()).await.map(|i| i.id)
+                }
+                ImportRecord::Expense(expense) => {
+                    self.client.create((**expense).clone()).await.map(|e| e.id)
+                }
+            };
+            match result {
+                Ok(Some(id)) => return ImportOutcome::Created(id),
+                Ok(None) => {
+                    return ImportOutcome::Failed {
+                        message: "Created record has no id".to_string(),
+                        attempts: attempt,
+                    }
+                }
+                Err(err) if attempt < self.max_attempts && is_transient(&err) => {
+                    delay_for(self.retry_delay * attempt).await;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    return ImportOutcome::Failed {
+                        message: err.to_string(),
+                        attempts: attempt,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Failures which can disappear when request is sent again and after which record surely
+/// doesn't exist, so creating it again can't make duplicate.
+fn is_transient(err: &FakturoidError) -> bool {
+    match err.kind() {
+        Kind::TooManyRequests => true,
+        _ => err.request_err().map(|e| e.is_connect()).unwrap_or(false),
+    }
+}
+
+/// Keys are stored one per line, so line breaks and tabs in them must be escaped.
+fn escape_key(key: &str) -> String {
+    key.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape_key(key: &str) -> String {
+    let mut unescaped = String::with_capacity(key.len());
+    let mut chars = key.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Reads keys and ids of imported records, missing file means nothing was imported yet.
+pub(crate) fn read_resume_file(path: &Path) -> Result<HashMap<String, i32>, FakturoidError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(FakturoidError::from_std_err(err)),
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let tab = line.rfind('\t')?;
+            let id = line[tab + 1..].trim().parse().ok()?;
+            Some((unescape_key(&line[..tab]), id))
+        })
+        .collect())
+}
@@ -1,14 +1,24 @@
 //! Data model. All structs and attributes coresponds with official API
 //! [documentation](https://fakturoid.docs.apiary.io)
 
-use chrono::{DateTime, Local, NaiveDate};
-use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use crate::currency::{Currency, Money};
+use crate::dates::{self, Date, DateTime};
+use crate::error::{AttachmentError, InvalidPaymentTerms};
+use crate::language::{language_for_country, LanguageSelector};
+use crate::tax::{self, TaxPointRule, VatRate};
+use crate::terms::PaymentTerms;
+use futures::io::{AsyncRead, AsyncReadExt};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Formatter;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read};
 use std::path::Path;
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum VatMode {
     VatPayer,
@@ -16,12 +26,21 @@ pub enum VatMode {
     IdentifiedPerson
 }
 
-#[derive(Debug, Deserialize)]
+/// Account details and settings. Fields which are sent only by some versions of API are
+/// optional or have default values.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Account {
     pub subdomain: String,
+    /// Name of subscription plan, e.g. `Profi`.
     pub plan: String,
     pub plan_price: i32,
+    /// Number of paid users of plan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_paid_users: Option<i32>,
+    /// Email of account owner, API v3 doesn't send it.
+    #[serde(default)]
     pub email: String,
+    /// Email used as sender of invoices.
     pub invoice_email: Option<String>,
     pub phone: Option<String>,
     pub web: Option<String>,
@@ -36,23 +55,115 @@ pub struct Account {
     pub city: String,
     pub zip: String,
     pub country: String,
+    /// Default bank account of API v2, all bank accounts are listed by
+    /// `Fakturoid::bank_accounts()`.
+    #[serde(default)]
     pub bank_account: String,
     pub iban: Option<String>,
     pub swift_bic: Option<String>,
-    pub currency: String,
+    /// Default currency of documents.
+    pub currency: Currency,
+    /// Default unit of invoice lines.
     pub unit_name: Option<String>,
+    /// Default VAT rate of invoice lines.
     pub vat_rate: i32,
     pub displayed_note: Option<String>,
     pub invoice_note: Option<String>,
+    /// Default number of days until invoice is due.
     pub due: i32,
+    /// Default language of invoices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_language: Option<InvoiceLanguage>,
+    /// Default payment method of invoices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_payment_method: Option<PaymentMethod>,
+    /// Proforma invoices are issued by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_proforma: Option<bool>,
+    /// Exchange rate of invoices in foreign currency is fixed when invoice is issued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed_exchange_rate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_selfbilling: Option<bool>,
+    /// Reminder is sent when invoice is overdue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_overdue_email: Option<bool>,
+    /// Number of days after due date when reminder is sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overdue_email_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_repeated_reminders: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_invoice_from_proforma_email: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_thank_you_email: Option<bool>,
+    #[serde(default)]
     pub custom_email_text: String,
+    #[serde(default)]
     pub overdue_email_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_invoice_from_proforma_email_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thank_you_email_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_estimate_email_text: Option<String>,
+    #[serde(default)]
     pub invoice_paypal: bool,
+    #[serde(default)]
     pub invoice_gopay: bool,
     pub html_url: String,
     pub url: String,
-    pub created_at: DateTime<Local>,
-    pub updated_at: DateTime<Local>,
+    #[serde(with = "dates::datetime")]
+    pub created_at: DateTime,
+    #[serde(with = "dates::datetime")]
+    pub updated_at: DateTime,
+}
+
+/// Account settings changed by `Fakturoid::update_account()`, only filled fields are sent.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AccountSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vat_price_mode: Option<VatPriceMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vat_rate: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub displayed_note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_language: Option<InvoiceLanguage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_payment_method: Option<PaymentMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_proforma: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed_exchange_rate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_overdue_email: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overdue_email_days: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_repeated_reminders: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_thank_you_email: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_email_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overdue_email_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thank_you_email_text: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -69,7 +180,7 @@ pub struct Subject {
     pub id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub sub_type: Option<SubjectType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -115,10 +226,62 @@ pub struct Subject {
     pub html_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Commonly needed fields of subject for listings. It can be used with `list()` instead of
+/// `Subject` when complete records aren't needed, `hydrate()` downloads complete subject.
+/// Summary is read-only, it can't be created or updated because it would clear other fields.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SubjectSummary {
+    pub id: i32,
+    #[serde(default)]
+    pub custom_id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub registration_no: Option<String>,
+    #[serde(default)]
+    pub vat_no: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default, with = "dates::option_datetime")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Concise one line summary, e.g. `Apple Czech s.r.o. (reg. no. 28897501, pokus@test.cz)`.
+impl fmt::Display for Subject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name.as_deref().unwrap_or("(no name)"))?;
+        let details: Vec<String> = [
+            self.registration_no
+                .as_ref()
+                .map(|r| format!("reg. no. {}", r)),
+            self.email.clone(),
+        ]
+        .iter()
+        .flatten()
+        .filter(|d| !d.is_empty())
+        .cloned()
+        .collect();
+        if !details.is_empty() {
+            f.write_fmt(format_args!(" ({})", details.join(", ")))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -131,15 +294,15 @@ pub enum InvoiceState {
     Cancelled,
 }
 
-impl ToString for InvoiceState {
-    fn to_string(&self) -> String {
-        match self {
-            InvoiceState::Open => "open".to_string(),
-            InvoiceState::Sent => "sent".to_string(),
-            InvoiceState::Overdue => "overdue".to_string(),
-            InvoiceState::Paid => "paid".to_string(),
-            InvoiceState::Cancelled => "cancelled".to_string(),
-        }
+impl fmt::Display for InvoiceState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvoiceState::Open => "open",
+            InvoiceState::Sent => "sent",
+            InvoiceState::Overdue => "overdue",
+            InvoiceState::Paid => "paid",
+            InvoiceState::Cancelled => "cancelled",
+        })
     }
 }
 
@@ -151,9 +314,11 @@ pub enum PaymentMethod {
     Cod,
     Paypal,
     Card,
+    /// Custom payment method of API v3.
+    Custom,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum InvoiceLanguage {
     Cz,
@@ -176,6 +341,15 @@ pub enum VatPriceMode {
     FromTotalWithVat,
 }
 
+/// How VAT is rounded when totals are computed locally.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VatRounding {
+    /// VAT is rounded once for each VAT rate, as fakturoid.cz does.
+    PerRate,
+    /// VAT is rounded for each line.
+    PerLine,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum EetStatus {
     Waiting,
@@ -191,7 +365,8 @@ pub struct EetRecord {
     pub number: String,
     pub store: i32,
     pub cash_register: String,
-    pub paid_at: DateTime<Local>,
+    #[serde(with = "dates::datetime")]
+    pub paid_at: DateTime,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vat_base0: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -213,50 +388,218 @@ pub struct EetRecord {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pkp: Option<String>,
     pub status: EetStatus,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub fik_received_at: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub fik_received_at: Option<DateTime>,
     pub external: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attempts: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_attempt_at: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_attempt_at: Option<DateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_uuid: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub playground: Option<bool>,
     pub invoice_id: i32,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
 }
 
+/// Attachment of document stored in fakturoid.cz. It can be downloaded with
+/// `Fakturoid::download_attachment()`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RemoteAttachment {
-    file_name: String,
-    content_type: String,
-    download_url: String,
+    /// Id of attachment, API v3 only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(alias = "filename")]
+    pub file_name: String,
+    pub content_type: String,
+    pub download_url: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum Attachment {
     Update(String),
+    /// One of several attachments uploaded with API v3.
+    Upload { filename: String, data_url: String },
     Received(RemoteAttachment),
 }
 
+impl Attachment {
+    fn read_file(path: &Path) -> Result<Vec<u8>, AttachmentError> {
+        if path.is_dir() {
+            return Err(AttachmentError::new(
+                path,
+                io::Error::new(io::ErrorKind::InvalidInput, "path is a directory"),
+            ));
+        }
+        let read = || -> io::Result<Vec<u8>> {
+            let mut file = File::open(path)?;
+            let mut file_content: Vec<u8> = Vec::new();
+            file.read_to_end(&mut file_content)?;
+            Ok(file_content)
+        };
+        read().map_err(|err| AttachmentError::new(path, err))
+    }
+
+    fn data_url(content_type: &str, content: &[u8]) -> String {
+        format!(
+            "data:{};base64,{}",
+            content_type,
+            base64::encode_config(content, base64::STANDARD_NO_PAD)
+        )
+    }
+
+    fn from_path(path: &Path) -> Result<Self, AttachmentError> {
+        let content_type = content_type_of(path);
+        Ok(Self::from_bytes(content_type, &Self::read_file(path)?))
+    }
+
+    fn from_bytes(content_type: &str, content: &[u8]) -> Self {
+        Attachment::Update(Self::data_url(content_type, content))
+    }
+
+    fn upload_path(path: &Path) -> Result<Self, AttachmentError> {
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                AttachmentError::new(
+                    path,
+                    io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"),
+                )
+            })?;
+        Ok(Self::upload_bytes(&filename, &Self::read_file(path)?))
+    }
+
+    fn upload_bytes(filename: &str, content: &[u8]) -> Self {
+        Attachment::Upload {
+            filename: filename.to_string(),
+            data_url: Self::data_url(content_type_of(Path::new(filename)), content),
+        }
+    }
+
+    /// Attachments received from API are not sent back, only new attachments are uploaded.
+    fn skip(attachment: &Option<Self>) -> bool {
+        attachment.iter().all(|a| a.received().is_some())
+    }
+
+    fn skip_all(attachments: &Option<Vec<Self>>) -> bool {
+        attachments.iter().flatten().all(|a| a.received().is_some())
+    }
+
+    fn serialize_new<S: Serializer>(
+        attachments: &Option<Vec<Self>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            attachments
+                .iter()
+                .flatten()
+                .filter(|a| a.received().is_none()),
+        )
+    }
+
+    fn received(&self) -> Option<&RemoteAttachment> {
+        if let Attachment::Received(rcv) = self {
+            Some(rcv)
+        } else {
+            None
+        }
+    }
+}
+
+/// Content type of attached file according to its extension, `application/octet-stream` if
+/// extension is not known.
+fn content_type_of(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "pdf" => "application/pdf",
+        "isdoc" | "xml" => "application/xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "zip" => "application/zip",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Given content type, or content type according to extension of file name if it's empty.
+fn content_type_or<'a>(content_type: &'a str, name: &str) -> &'a str {
+    if content_type.is_empty() {
+        content_type_of(Path::new(name))
+    } else {
+        content_type
+    }
+}
+
+/// Type of invoice document in API v3, it replaces `proforma`, `partial_proforma` and
+/// `correction` flags of API v2.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceDocumentType {
+    Invoice,
+    Proforma,
+    PartialProforma,
+    Correction,
+    TaxDocument,
+    FinalInvoice,
+    /// Type unknown to this version of library, e.g. added to API later.
+    #[serde(other)]
+    Other,
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Invoice {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom_id: Option<String>,
+    /// Type of document, API v3 only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_type: Option<InvoiceDocumentType>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub proforma: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub partial_proforma: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub number: Option<String>,
+    /// Id of number format (numbering series) used for number of new invoice.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_format_id: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub variable_symbol: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -313,24 +656,63 @@ pub struct Invoice {
     pub status: Option<InvoiceState>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub order_number: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub issued_on: Option<NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub taxable_fulfillment_due: Option<NaiveDate>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issued_on: Option<Date>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub taxable_fulfillment_due: Option<Date>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub due: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub due_on: Option<NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sent_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub paid_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub reminder_sent_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub accepted_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cancelled_at: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due_on: Option<Date>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sent_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub paid_at: Option<DateTime>,
+    /// Date of payment, API v3 only (it replaces `paid_at`).
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub paid_on: Option<Date>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub reminder_sent_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub accepted_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub cancelled_at: Option<DateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -350,9 +732,9 @@ pub struct Invoice {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payment_method: Option<PaymentMethod>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub currency: Option<String>,
+    pub currency: Option<Currency>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub exchange_rate: Option<String>,
+    pub exchange_rate: Option<Decimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub paypal: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -391,8 +773,16 @@ pub struct Invoice {
     pub eet_store: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub eet_records: Option<Vec<EetRecord>>,
+    /// Payments recorded on invoice, API v3 only.
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub payments: Option<Vec<InvoicePayment>>,
+    #[serde(skip_serializing_if = "Attachment::skip")]
     attachment: Option<Attachment>,
+    #[serde(
+        skip_serializing_if = "Attachment::skip_all",
+        serialize_with = "Attachment::serialize_new"
+    )]
+    attachments: Option<Vec<Attachment>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub html_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -403,14 +793,54 @@ pub struct Invoice {
     pub pdf_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub subject_url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<DateTime<Local>>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lines: Option<Vec<InvoiceLine>>,
 }
 
+/// Commonly needed fields of invoice for listings. It can be used with `list()` instead of
+/// `Invoice` when complete documents aren't needed, `hydrate()` downloads complete invoice.
+/// Summary is read-only, it can't be created or updated because it would clear other fields.
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvoiceSummary {
+    pub id: i32,
+    #[serde(default)]
+    pub custom_id: Option<String>,
+    #[serde(default)]
+    pub proforma: Option<bool>,
+    #[serde(default)]
+    pub number: Option<String>,
+    #[serde(default)]
+    pub client_name: Option<String>,
+    #[serde(default)]
+    pub subject_id: Option<i32>,
+    #[serde(default)]
+    pub status: Option<InvoiceState>,
+    #[serde(default, with = "dates::option_date")]
+    pub issued_on: Option<Date>,
+    #[serde(default, with = "dates::option_date")]
+    pub due_on: Option<Date>,
+    #[serde(default)]
+    pub currency: Option<Currency>,
+    #[serde(default)]
+    pub total: Option<Decimal>,
+    #[serde(default)]
+    pub remaining_amount: Option<Decimal>,
+    #[serde(default, with = "dates::option_datetime")]
+    pub updated_at: Option<DateTime>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InvoiceLine {
     pub id: Option<i32>,
@@ -418,18 +848,36 @@ pub struct InvoiceLine {
     pub quantity: Decimal,
     pub unit_name: Option<String>,
     pub unit_price: Decimal,
-    pub vat_rate: i32,
+    /// VAT rate in percent, it may be fractional, e.g. 25.5 % in Finland.
+    pub vat_rate: Decimal,
     pub unit_price_without_vat: Option<Decimal>,
     pub unit_price_with_vat: Option<Decimal>,
+    /// Inventory item which stock is moved by this line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory_item_id: Option<i32>,
+    /// Stock keeping unit of inventory item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    /// Inventory item and stock move of line as returned by API, API v3 only.
+    #[serde(skip_serializing)]
+    pub inventory: Option<LineInventory>,
+    /// Line is deleted by update of invoice, see [`mark_destroyed()`](#method.mark_destroyed).
+    #[serde(
+        rename = "_destroy",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    destroy: bool,
 }
 
 impl InvoiceLine {
-    pub fn new(
+    /// New line, VAT rate is given in percent, e.g. `21` or `Decimal::new(255, 1)`.
+    pub fn new<R: Into<Decimal>>(
         name: &str,
         quantity: Decimal,
         unit_name: Option<&str>,
         unit_price: Decimal,
-        vat_rate: i32,
+        vat_rate: R,
     ) -> Self {
         Self {
             id: None,
@@ -437,82 +885,1767 @@ impl InvoiceLine {
             quantity,
             unit_name: unit_name.map(|n| n.to_string()),
             unit_price,
-            vat_rate,
+            vat_rate: vat_rate.into(),
             unit_price_without_vat: None,
             unit_price_with_vat: None,
+            inventory_item_id: None,
+            sku: None,
+            inventory: None,
+            destroy: false,
+        }
+    }
+
+    /// Line selling given quantity of inventory item, its name, unit and retail price (in
+    /// currency of account) are used. If item tracks quantity, stock is moved out when invoice
+    /// is created.
+    pub fn for_inventory_item<R: Into<Decimal>>(
+        item: &InventoryItem,
+        quantity: Decimal,
+        vat_rate: R,
+    ) -> Self {
+        Self {
+            inventory_item_id: item.id,
+            ..Self::new(
+                item.name.as_deref().unwrap_or_default(),
+                quantity,
+                item.unit_name.as_deref(),
+                item.native_retail_price.unwrap_or_default(),
+                vat_rate,
+            )
         }
     }
+
+    /// Id of inventory move created by line, see `inventory`.
+    pub fn inventory_move_id(&self) -> Option<i32> {
+        self.inventory.as_ref().and_then(|i| i.move_id)
+    }
+
+    /// Marks line to be deleted when invoice is updated. Line is sent with `_destroy` flag, only
+    /// lines with `id` can be deleted this way.
+    pub fn mark_destroyed(&mut self) {
+        self.destroy = true;
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.destroy
+    }
+
+    /// Price of line (quantity times unit price) rounded to two decimal places. It is without
+    /// or with VAT depending on `vat_price_mode` of invoice.
+    pub(crate) fn price(&self) -> Decimal {
+        round_money(self.quantity * self.unit_price)
+    }
+
+    /// Price of line without VAT and VAT amount, both rounded to two decimal places.
+    pub(crate) fn base_and_vat(&self, prices_with_vat: bool) -> (Decimal, Decimal) {
+        split_vat(self.price(), self.vat_rate, prices_with_vat)
+    }
+
+    /// Fills `unit_price_without_vat` and `unit_price_with_vat` the way API does.
+    pub(crate) fn recompute_unit_prices(&mut self, prices_with_vat: bool) {
+        let (base, vat) = split_vat(self.unit_price, self.vat_rate, prices_with_vat);
+        self.unit_price_without_vat = Some(base);
+        self.unit_price_with_vat = Some(base + vat);
+    }
 }
 
 impl Invoice {
-    pub fn set_attachment(&mut self, path: &Path) -> Result<(), ()> {
-        if path.is_file() {
-            let mut file = File::open(path).map_err(|_| ())?;
-            let mut file_content: Vec<u8> = Vec::new();
-            file.read_to_end(&mut file_content).map_err(|_| ())?;
-            self.attachment = Some(Attachment::Update(format!(
-                "data:{};base64,{}",
-                tree_magic::from_u8(&file_content),
-                base64::encode_config(file_content, base64::STANDARD_NO_PAD)
-            )));
-            return Ok(());
-        }
-        Err(())
+    /// Sets the only attachment of invoice, it is sent as `attachment` field of API v2. Methods
+    /// `set_attachment*()` always write the `attachment` field, methods `add_attachment*()` write
+    /// `attachments` field of API v3. Content type is given by file extension.
+    pub fn set_attachment(&mut self, path: &Path) -> Result<(), AttachmentError> {
+        self.attachment = Some(Attachment::from_path(path)?);
+        Ok(())
     }
 
+    /// Sets the only attachment of invoice from file content held in memory, see
+    /// [`set_attachment()`](#method.set_attachment). Content type is put into data URL as it
+    /// is, if it's empty, it's given by extension of `name`.
+    pub fn set_attachment_bytes(&mut self, name: &str, content_type: &str, data: &[u8]) {
+        self.attachment = Some(Attachment::from_bytes(
+            content_type_or(content_type, name),
+            data,
+        ));
+    }
+
+    /// Sets the only attachment of invoice from async reader, e.g. body of uploaded file, see
+    /// [`set_attachment_bytes()`](#method.set_attachment_bytes).
+    pub async fn set_attachment_reader<R: AsyncRead + Unpin>(
+        &mut self,
+        name: &str,
+        content_type: &str,
+        mut reader: R,
+    ) -> io::Result<()> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        self.set_attachment_bytes(name, content_type, &data);
+        Ok(())
+    }
+
+    /// Attachment received with API v2, first of [`attachments()`](#method.attachments) with
+    /// API v3.
     pub fn attachment(&self) -> Option<&RemoteAttachment> {
-        if let Some(attachment) = self.attachment.as_ref() {
-            if let Attachment::Received(rcv) = attachment {
-                Some(rcv)
-            } else {
-                None
-            }
+        self.attachments().into_iter().next()
+    }
+
+    /// Adds attachment from file, name of file is kept. Several attachments are supported by
+    /// API v3 only. Content type is given by file extension.
+    pub fn add_attachment(&mut self, path: &Path) -> Result<(), AttachmentError> {
+        let attachment = Attachment::upload_path(path)?;
+        self.attachments.get_or_insert_with(Vec::new).push(attachment);
+        Ok(())
+    }
+
+    /// Adds attachment from file content held in memory, see
+    /// [`add_attachment()`](#method.add_attachment).
+    pub fn add_attachment_bytes(&mut self, name: &str, content: &[u8]) {
+        self.attachments
+            .get_or_insert_with(Vec::new)
+            .push(Attachment::upload_bytes(name, content));
+    }
+
+    /// All attachments stored in fakturoid.cz, attachments added by
+    /// [`add_attachment()`](#method.add_attachment) are not included until invoice is saved.
+    pub fn attachments(&self) -> Vec<&RemoteAttachment> {
+        self.attachment
+            .iter()
+            .chain(self.attachments.iter().flatten())
+            .filter_map(|a| a.received())
+            .collect()
+    }
+
+    /// Invoice is proforma or partial proforma. `proforma` flag of API v2 is used, type of
+    /// document is checked when the flag is missing (API v3).
+    pub fn is_proforma(&self) -> bool {
+        self.proforma.unwrap_or(matches!(
+            self.document_type,
+            Some(InvoiceDocumentType::Proforma) | Some(InvoiceDocumentType::PartialProforma)
+        ))
+    }
+
+    /// Mutable reference to invoice lines. Empty vector is created if invoice has no lines.
+    pub fn lines_mut(&mut self) -> &mut Vec<InvoiceLine> {
+        self.lines.get_or_insert_with(Vec::new)
+    }
+
+    /// Appends line to invoice.
+    pub fn add_line(&mut self, line: InvoiceLine) {
+        self.lines_mut().push(line);
+    }
+
+    /// Removes line at given position. Removed line is returned, `None` is returned if there
+    /// is no such line. Line is removed only locally, use
+    /// [`destroy_line_by_id()`](#method.destroy_line_by_id) to delete existing line by update.
+    pub fn remove_line(&mut self, index: usize) -> Option<InvoiceLine> {
+        let lines = self.lines.as_mut()?;
+        if index < lines.len() {
+            Some(lines.remove(index))
         } else {
             None
         }
     }
+
+    /// Removes line with given id. Removed line is returned, `None` is returned if there
+    /// is no such line.
+    pub fn remove_line_by_id(&mut self, id: i32) -> Option<InvoiceLine> {
+        let index = self
+            .lines
+            .as_ref()?
+            .iter()
+            .position(|l| l.id == Some(id))?;
+        self.remove_line(index)
+    }
+
+    /// Marks line with given id to be deleted by update of invoice, see
+    /// `InvoiceLine::mark_destroyed()`. Returns `false` if there is no such line.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    /// use fakturoid::models::Invoice;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let invoice = client.detail::<Invoice>(27).await?;
+    ///     let mut update = Invoice::default();
+    ///     update.lines = invoice.lines;
+    ///     update.destroy_line_by_id(1304);
+    ///     let invoice = client.update(27, update).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn destroy_line_by_id(&mut self, id: i32) -> bool {
+        match self.lines_mut().iter_mut().find(|l| l.id == Some(id)) {
+            Some(line) => {
+                line.mark_destroyed();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Creates invoice for given subject. Language of invoice is selected according to country
+    /// of subject, see [`LanguageSelector`](../language/struct.LanguageSelector.html) for
+    /// custom rules.
+    pub fn for_subject(subject: &Subject) -> Self {
+        Self::for_subject_with(subject, &LanguageSelector::default())
+    }
+
+    /// Fills empty fields with defaults from account settings: seller details (`your_*`
+    /// fields), currency, due days, bank account, VAT price mode, payment method, language and
+    /// note. Without default language of account it's derived from country of account. Fields
+    /// which are already set are kept.
+    pub fn with_account_defaults(mut self, account: &Account) -> Self {
+        fn fill(field: &mut Option<String>, value: &str) {
+            if field.is_none() && !value.is_empty() {
+                *field = Some(value.to_string());
+            }
+        }
+        fn fill_opt(field: &mut Option<String>, value: &Option<String>) {
+            if field.is_none() {
+                *field = value.clone().filter(|v| !v.is_empty());
+            }
+        }
+
+        fill(&mut self.your_name, &account.name);
+        fill(&mut self.your_street, &account.street);
+        fill_opt(&mut self.your_street2, &account.street2);
+        fill(&mut self.your_city, &account.city);
+        fill(&mut self.your_zip, &account.zip);
+        fill(&mut self.your_country, &account.country);
+        fill_opt(&mut self.your_registration_no, &account.registration_no);
+        fill_opt(&mut self.your_vat_no, &account.vat_no);
+        if self.currency.is_none() {
+            self.currency = Some(account.currency);
+        }
+        fill(&mut self.bank_account, &account.bank_account);
+        fill_opt(&mut self.iban, &account.iban);
+        fill_opt(&mut self.swift_bic, &account.swift_bic);
+        fill_opt(&mut self.note, &account.invoice_note);
+        if self.due.is_none() {
+            self.due = Some(account.due);
+        }
+        if self.vat_price_mode.is_none() {
+            self.vat_price_mode = Some(account.vat_price_mode.clone());
+        }
+        if self.payment_method.is_none() {
+            self.payment_method = account.invoice_payment_method.clone();
+        }
+        if self.language.is_none() {
+            self.language = account
+                .invoice_language
+                .or_else(|| language_for_country(&account.country));
+        }
+        self
+    }
+
+    /// Creates invoice for given subject with language selected by given selector.
+    pub fn for_subject_with(subject: &Subject, languages: &LanguageSelector) -> Self {
+        Self {
+            subject_id: subject.id,
+            language: Some(languages.select(subject)),
+            ..Self::default()
+        }
+    }
+
+    /// Creates invoice from generator issued on given date, so it can be previewed or customized
+    /// before it is created. Subject, lines, payment settings and texts are copied, due date
+    /// and date of taxable supply are computed and totals are recomputed.
+    ///
+    /// Placeholders in note, footer note and names of lines are resolved according to issue
+    /// date: `#rok#` (year), `#mesic#` (month), `#ctvrtleti#` (quarter) and `#obdobi#` (billing
+    /// period, e.g. `3/2024` or `1/2024 - 3/2024` for generators with longer `months_period`).
+    pub fn from_generator(generator: &Generator, issued_on: Date) -> Self {
+        let placeholders = Placeholders::new(&issued_on, generator.months_period.unwrap_or(1));
+        let rule = if generator.tax_date_at_end_of_last_month.unwrap_or(false) {
+            TaxPointRule::RecurringService
+        } else {
+            TaxPointRule::IssueDate
+        };
+        let lines = generator.lines.as_ref().map(|lines| {
+            lines
+                .iter()
+                .map(|line| InvoiceLine {
+                    id: None,
+                    name: placeholders.resolve(&line.name),
+                    ..line.clone()
+                })
+                .collect()
+        });
+
+        let mut invoice = Self {
+            document_type: generator.proforma.map(|proforma| {
+                if proforma {
+                    InvoiceDocumentType::Proforma
+                } else {
+                    InvoiceDocumentType::Invoice
+                }
+            }),
+            proforma: generator.proforma,
+            number_format_id: generator.number_format_id,
+            subject_id: generator.subject_id,
+            generator_id: generator.id,
+            order_number: generator.order_number.clone(),
+            issued_on: Some(issued_on),
+            taxable_fulfillment_due: Some(rule.taxable_fulfillment_due(&issued_on)),
+            due: generator.due,
+            due_on: generator
+                .due
+                .map(|due| dates::add_days(&issued_on, i64::from(due))),
+            note: generator.note.as_deref().map(|n| placeholders.resolve(n)),
+            footer_note: generator.footer_note.as_deref().map(|n| placeholders.resolve(n)),
+            tags: generator.tags.clone(),
+            bank_account_id: generator.bank_account_id,
+            payment_method: generator.payment_method.clone(),
+            currency: generator.currency,
+            exchange_rate: generator.exchange_rate,
+            paypal: generator.paypal,
+            gopay: generator.gopay,
+            language: generator.language,
+            transferred_tax_liability: generator.transferred_tax_liability,
+            supply_code: generator.supply_code.clone(),
+            eu_electronic_service: generator.eu_electronic_service,
+            vat_price_mode: generator.vat_price_mode.clone(),
+            round_total: generator.round_total,
+            lines,
+            ..Self::default()
+        };
+        if invoice.is_proforma() {
+            invoice.taxable_fulfillment_due = None;
+        }
+        invoice.recompute_totals();
+        invoice
+    }
+
+    /// Sets `due` according to payment terms. Issue date of invoice is used to resolve terms
+    /// relative to end of month, today is used if invoice has no issue date yet.
+    pub fn set_payment_terms(&mut self, terms: PaymentTerms) -> Result<(), InvalidPaymentTerms> {
+        let issued_on = self.issued_on.unwrap_or_else(dates::today);
+        self.due = Some(terms.due_days(&issued_on)?);
+        Ok(())
+    }
+
+    /// Total including VAT in currency of invoice, `None` if total or currency is missing.
+    pub fn total_money(&self) -> Option<Money> {
+        money(self.total, self.currency)
+    }
+
+    /// Total without VAT in currency of invoice.
+    pub fn subtotal_money(&self) -> Option<Money> {
+        money(self.subtotal, self.currency)
+    }
+
+    /// Amount remaining to be paid in currency of invoice.
+    pub fn remaining_money(&self) -> Option<Money> {
+        money(self.remaining_amount, self.currency)
+    }
+
+    /// Amount already paid in currency of invoice.
+    pub fn paid_money(&self) -> Option<Money> {
+        money(self.paid_amount, self.currency)
+    }
+
+    /// Total including VAT in currency of account, which isn't part of invoice, so it has to be
+    /// given (see `Account::currency`).
+    pub fn native_total_money(&self, native_currency: Currency) -> Option<Money> {
+        self.native_total.map(|amount| Money::new(amount, native_currency))
+    }
+
+    /// VAT rate of line in country of invoice (see `tax::vat_country()`), `None` if country is
+    /// unknown or rate doesn't exist there.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fakturoid::models::{Invoice, InvoiceLine};
+    /// use fakturoid::tax::VatRate;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let mut invoice = Invoice::default();
+    /// invoice.your_country = Some("CZ".to_string());
+    /// let vat_rate = invoice.resolve_vat_rate(VatRate::Reduced(0)).unwrap();
+    /// assert_eq!(vat_rate, Decimal::from(12));
+    /// let price = Decimal::from(300);
+    /// invoice.add_line(InvoiceLine::new("Book", Decimal::from(1), None, price, vat_rate));
+    /// ```
+    pub fn resolve_vat_rate(&self, rate: VatRate) -> Option<Decimal> {
+        rate.percent(&tax::vat_country(self)?)
+    }
+
+    /// Payment terms from `due` field, `None` if `due` is not set or is out of range.
+    pub fn payment_terms(&self) -> Option<PaymentTerms> {
+        self.due.and_then(|days| PaymentTerms::try_from(days).ok())
+    }
+
+    /// Fills `taxable_fulfillment_due` according to given rule unless it is already set.
+    /// Proformas are not tax documents, so they are left untouched. Issue date of invoice is used
+    /// by rules relative to it, today is used if invoice has no issue date yet.
+    pub fn default_taxable_fulfillment_due(&mut self, rule: &TaxPointRule) {
+        if self.taxable_fulfillment_due.is_some() || self.proforma.unwrap_or(false) {
+            return;
+        }
+        let issued_on = self.issued_on.unwrap_or_else(dates::today);
+        self.taxable_fulfillment_due = Some(rule.taxable_fulfillment_due(&issued_on));
+    }
+
+    /// Recomputes `subtotal` and `total` from invoice lines with respect to `vat_price_mode`
+    /// and `round_total`, the same way as fakturoid.cz does, see
+    /// [`recompute_totals_with()`](#method.recompute_totals_with). Should be called after lines
+    /// are changed if totals are needed before invoice is sent to API.
+    pub fn recompute_totals(&mut self) {
+        self.recompute_totals_with(VatRounding::PerRate);
+    }
+
+    /// Recomputes `subtotal`, `total` and unit prices with and without VAT of lines using given
+    /// VAT rounding, see [`compute_totals_with()`](#method.compute_totals_with).
+    pub fn recompute_totals_with(&mut self, rounding: VatRounding) {
+        let prices_with_vat = self.prices_with_vat();
+        for line in self.lines.iter_mut().flatten() {
+            line.recompute_unit_prices(prices_with_vat);
+        }
+        let totals = self.compute_totals_with(rounding);
+        self.subtotal = Some(totals.subtotal);
+        self.total = Some(totals.total);
+    }
+
+    /// Computes subtotal, VAT recapitulation and total from invoice lines with respect to
+    /// `vat_price_mode` and `round_total` the same way as fakturoid.cz does, so amounts can be
+    /// previewed before invoice is sent to API. Invoice is not changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fakturoid::models::Invoice;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let invoice = Invoice::builder()
+    ///     .subject_id(16)
+    ///     .item("Consulting", Decimal::from(1000), 21)
+    ///     .item("Book", Decimal::from(300), 12)
+    ///     .build()
+    ///     .unwrap();
+    /// let totals = invoice.compute_totals();
+    /// assert_eq!(totals.subtotal, Decimal::from(1300));
+    /// assert_eq!(totals.vat, Decimal::from(246));
+    /// assert_eq!(totals.total, Decimal::from(1546));
+    /// assert_eq!(totals.rates.len(), 2);
+    /// ```
+    pub fn compute_totals(&self) -> Totals {
+        self.compute_totals_with(VatRounding::PerRate)
+    }
+
+    /// Computes totals from invoice lines using given VAT rounding.
+    ///
+    /// Prices of lines are rounded to two decimal places first. With `VatRounding::PerRate`
+    /// prices of lines with the same VAT rate are summed and VAT is computed and rounded once
+    /// for the sum, as in VAT recapitulation of fakturoid.cz documents. When prices include VAT
+    /// (`VatPriceMode::FromTotalWithVat`), price without VAT is computed from the sum and VAT is
+    /// the difference. If `round_total` is set, total is rounded to whole units of currency and
+    /// subtotal and VAT are kept. Lines marked to be destroyed are skipped.
+    pub fn compute_totals_with(&self, rounding: VatRounding) -> Totals {
+        let prices_with_vat = self.prices_with_vat();
+        let mut rates: BTreeMap<Decimal, VatRecapitulation> = BTreeMap::new();
+        for line in self.lines.iter().flatten().filter(|l| !l.is_destroyed()) {
+            let rate = rates
+                .entry(line.vat_rate)
+                .or_insert_with(|| VatRecapitulation::new(line.vat_rate));
+            match rounding {
+                VatRounding::PerLine => {
+                    let (base, vat) = line.base_and_vat(prices_with_vat);
+                    rate.base += base;
+                    rate.vat += vat;
+                }
+                // Sum of prices is kept in base until VAT is split below.
+                VatRounding::PerRate => rate.base += line.price(),
+            }
+        }
+        if rounding == VatRounding::PerRate {
+            for rate in rates.values_mut() {
+                let (base, vat) = split_vat(rate.base, rate.vat_rate, prices_with_vat);
+                rate.base = base;
+                rate.vat = vat;
+            }
+        }
+        let rates: Vec<VatRecapitulation> = rates.into_values().collect();
+        let subtotal: Decimal = rates.iter().map(|r| r.base).sum();
+        let vat: Decimal = rates.iter().map(|r| r.vat).sum();
+        let unrounded = subtotal + vat;
+        let total = if self.round_total.unwrap_or(false) {
+            unrounded.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+        } else {
+            unrounded
+        };
+        Totals {
+            subtotal,
+            vat,
+            total,
+            rounding: total - unrounded,
+            rates,
+        }
+    }
+
+    fn prices_with_vat(&self) -> bool {
+        matches!(self.vat_price_mode, Some(VatPriceMode::FromTotalWithVat))
+    }
 }
 
-#[derive(Serialize)]
-pub struct InvoicePayData {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub paid_at: Option<DateTime<Local>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub paid_amount: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub variable_symbol: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub bank_account_id: Option<i32>
+/// Amounts of invoice computed locally from its lines, see `Invoice::compute_totals()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Totals {
+    /// Total without VAT.
+    pub subtotal: Decimal,
+    /// Sum of VAT of all rates.
+    pub vat: Decimal,
+    /// Total including VAT, rounded to whole units of currency if invoice has `round_total`.
+    pub total: Decimal,
+    /// Difference between rounded and unrounded total, zero without `round_total`.
+    pub rounding: Decimal,
+    /// Amounts for each VAT rate used on invoice, ordered by rate.
+    pub rates: Vec<VatRecapitulation>,
 }
 
-pub enum InvoiceAction {
-    MarkAsSent,
-    Deliver,
-    Pay,
-    PayProforma,
-    PayPartialProforma,
-    RemovePayment,
-    DeliverReminder,
-    Cancel,
-    UndoCancel,
+/// Amount without VAT and VAT amount of lines with the same VAT rate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VatRecapitulation {
+    pub vat_rate: Decimal,
+    pub base: Decimal,
+    pub vat: Decimal,
+}
+
+impl VatRecapitulation {
+    fn new(vat_rate: Decimal) -> Self {
+        Self {
+            vat_rate,
+            base: Decimal::new(0, 0),
+            vat: Decimal::new(0, 0),
+        }
+    }
+}
+
+fn round_money(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Splits price into amount without VAT and VAT amount, both rounded to two decimal places.
+/// VAT is computed from price without VAT, or price without VAT is computed from price with VAT
+/// and VAT is the difference.
+fn split_vat(price: Decimal, vat_rate: Decimal, price_with_vat: bool) -> (Decimal, Decimal) {
+    let rate = vat_rate / Decimal::from(100);
+    if price_with_vat {
+        let base = round_money(price / (Decimal::from(1) + rate));
+        (base, round_money(price) - base)
+    } else {
+        let base = round_money(price);
+        (base, round_money(base * rate))
+    }
+}
+
+/// Values of placeholders used in texts of generators.
+struct Placeholders {
+    year: i32,
+    month: u32,
+    period: String,
+}
+
+impl Placeholders {
+    fn new(issued_on: &Date, months_period: i32) -> Self {
+        let (year, month, _) = dates::ymd(issued_on);
+        let months = months_period.max(1) as u32;
+        let period = if months == 1 {
+            format!("{}/{}", month, year)
+        } else {
+            let last = month - 1 + months - 1;
+            format!(
+                "{}/{} - {}/{}",
+                month,
+                year,
+                last % 12 + 1,
+                year + (last / 12) as i32
+            )
+        };
+        Self {
+            year,
+            month,
+            period,
+        }
+    }
+
+    fn resolve(&self, text: &str) -> String {
+        text.replace("#rok#", &self.year.to_string())
+            .replace("#mesic#", &self.month.to_string())
+            .replace("#ctvrtleti#", &((self.month - 1) / 3 + 1).to_string())
+            .replace("#obdobi#", &self.period)
+    }
+}
+
+/// Concise one line summary, e.g.
+/// `Invoice 2012-0021 for Apple Czech s.r.o.: 12039.5 CZK, paid, due 2011-10-23`.
+impl fmt::Display for Invoice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = if self.proforma.unwrap_or(false) {
+            "Proforma"
+        } else if self.correction.unwrap_or(false) {
+            "Correction"
+        } else {
+            "Invoice"
+        };
+        f.write_fmt(format_args!(
+            "{} {}",
+            kind,
+            self.number.as_deref().unwrap_or("(no number)")
+        ))?;
+        if let Some(client) = self.client_name.as_ref() {
+            f.write_fmt(format_args!(" for {}", client))?;
+        }
+        write_summary(
+            f,
+            self.total.as_ref(),
+            self.currency.as_ref().map(Currency::as_str),
+            self.status.as_ref().map(|s| s.to_string()),
+            self.due_on.as_ref(),
+        )
+    }
+}
+
+/// Data of payment actions of invoice, see `Fakturoid::pay()`. Invoice is paid now in full
+/// amount if fields are omitted.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct InvoicePayData {
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub paid_at: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paid_amount: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account_id: Option<i32>
+}
+
+/// Payment of invoice (API v3), see `Fakturoid::create_payment()`. Only `amount` is needed for
+/// new payment, it is paid today in currency of invoice if other fields are omitted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InvoicePayment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub paid_on: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Decimal>,
+    /// Amount in currency of account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_amount: Option<Decimal>,
+    /// Invoice is marked as paid even if payment doesn't cover remaining amount.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mark_document_as_paid: Option<bool>,
+    /// Document created for paid proforma: `final_invoice`, `tax_document` or `none`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proforma_followup_document: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_thank_you_email: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account_id: Option<i32>,
+    /// Tax document created for payment of proforma.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_document_id: Option<i32>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+impl InvoicePayment {
+    /// New payment of given amount.
+    pub fn new(amount: Decimal) -> Self {
+        Self {
+            amount: Some(amount),
+            ..Self::default()
+        }
+    }
+}
+
+pub enum InvoiceAction {
+    MarkAsSent,
+    Deliver,
+    Pay,
+    PayProforma,
+    PayPartialProforma,
+    RemovePayment,
+    DeliverReminder,
+    Cancel,
+    UndoCancel,
     Lock,
     Unlock
 }
 
-impl ToString for InvoiceAction {
-    fn to_string(&self) -> String {
-        match self {
-            InvoiceAction::MarkAsSent => { "mark_as_sent" }
-            InvoiceAction::Deliver => { "deliver" }
-            InvoiceAction::Pay => { "pay" }
-            InvoiceAction::PayProforma => { "pay_proforma" }
-            InvoiceAction::PayPartialProforma => { "pay_partial_proforma" }
-            InvoiceAction::RemovePayment => { "remove_payment" }
-            InvoiceAction::DeliverReminder => { "deliver_reminder" }
-            InvoiceAction::Cancel => { "cancel" }
-            InvoiceAction::UndoCancel => { "undo_cancel" }
-            InvoiceAction::Lock => { "lock" }
-            InvoiceAction::Unlock => { "unlock" }
-        }.to_string()
+impl fmt::Display for InvoiceAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InvoiceAction::MarkAsSent => "mark_as_sent",
+            InvoiceAction::Deliver => "deliver",
+            InvoiceAction::Pay => "pay",
+            InvoiceAction::PayProforma => "pay_proforma",
+            InvoiceAction::PayPartialProforma => "pay_partial_proforma",
+            InvoiceAction::RemovePayment => "remove_payment",
+            InvoiceAction::DeliverReminder => "deliver_reminder",
+            InvoiceAction::Cancel => "cancel",
+            InvoiceAction::UndoCancel => "undo_cancel",
+            InvoiceAction::Lock => "lock",
+            InvoiceAction::Unlock => "unlock",
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpenseState {
+    Open,
+    Overdue,
+    Paid,
+}
+
+impl fmt::Display for ExpenseState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExpenseState::Open => "open",
+            ExpenseState::Overdue => "overdue",
+            ExpenseState::Paid => "paid",
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpenseDocumentType {
+    Invoice,
+    Bill,
+    Other,
+}
+
+/// Expense (received invoice) from supplier.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Expense {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    /// Number of document assigned by supplier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_street: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_street2: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_zip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_registration_no: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_vat_no: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier_local_vat_no: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ExpenseState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_type: Option<ExpenseDocumentType>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub issued_on: Option<Date>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub taxable_fulfillment_due: Option<Date>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub received_on: Option<Date>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due_on: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remind_due_date: Option<bool>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub paid_on: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iban: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swift_bic: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_method: Option<PaymentMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transferred_tax_liability: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vat_price_mode: Option<VatPriceMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_code: Option<String>,
+    /// Proportional VAT deduction in percents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proportional_vat_deduction: Option<i32>,
+    /// Expense is tax deductible.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_deductible: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtotal: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_subtotal: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_total: Option<Decimal>,
+    /// Payments recorded on expense, API v3 only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payments: Option<Vec<ExpensePayment>>,
+    #[serde(skip_serializing_if = "Attachment::skip")]
+    attachment: Option<Attachment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_url: Option<String>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<ExpenseLine>>,
+}
+
+impl Expense {
+    /// Sets attachment (typically scan of receipt) from file. Content type is given by file
+    /// extension.
+    pub fn set_attachment(&mut self, path: &Path) -> Result<(), AttachmentError> {
+        self.attachment = Some(Attachment::from_path(path)?);
+        Ok(())
+    }
+
+    /// Sets attachment from file content held in memory. Content type is put into data URL as
+    /// it is, if it's empty, it's given by extension of `name`.
+    pub fn set_attachment_bytes(&mut self, name: &str, content_type: &str, data: &[u8]) {
+        self.attachment = Some(Attachment::from_bytes(
+            content_type_or(content_type, name),
+            data,
+        ));
+    }
+
+    /// Sets attachment from async reader, e.g. body of uploaded file, see
+    /// [`set_attachment_bytes()`](#method.set_attachment_bytes).
+    pub async fn set_attachment_reader<R: AsyncRead + Unpin>(
+        &mut self,
+        name: &str,
+        content_type: &str,
+        mut reader: R,
+    ) -> io::Result<()> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        self.set_attachment_bytes(name, content_type, &data);
+        Ok(())
+    }
+
+    /// Attachment stored in fakturoid.cz.
+    pub fn attachment(&self) -> Option<&RemoteAttachment> {
+        self.attachment.as_ref().and_then(|a| a.received())
+    }
+
+    /// Total including VAT in currency of expense, `None` if total or currency is missing.
+    pub fn total_money(&self) -> Option<Money> {
+        money(self.total, self.currency)
+    }
+
+    /// Total without VAT in currency of expense.
+    pub fn subtotal_money(&self) -> Option<Money> {
+        money(self.subtotal, self.currency)
+    }
+
+    /// Total including VAT in currency of account, see `Invoice::native_total_money()`.
+    pub fn native_total_money(&self, native_currency: Currency) -> Option<Money> {
+        self.native_total.map(|amount| Money::new(amount, native_currency))
+    }
+}
+
+/// Payment of expense (API v3), see `Fakturoid::create_expense_payment()`. Only `amount` is
+/// needed for new payment, it is paid today in currency of expense if other fields are omitted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ExpensePayment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub paid_on: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Decimal>,
+    /// Amount in currency of account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_amount: Option<Decimal>,
+    /// Expense is marked as paid even if payment doesn't cover remaining amount.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mark_document_as_paid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account_id: Option<i32>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+impl ExpensePayment {
+    /// New payment of given amount.
+    pub fn new(amount: Decimal) -> Self {
+        Self {
+            amount: Some(amount),
+            ..Self::default()
+        }
+    }
+}
+
+/// Actions on expenses, see `Fakturoid::action()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExpenseAction {
+    /// Locks expense, so it can't be changed.
+    Lock,
+    Unlock,
+}
+
+impl fmt::Display for ExpenseAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExpenseAction::Lock => "lock",
+            ExpenseAction::Unlock => "unlock",
+        })
+    }
+}
+
+/// Line item of expense.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExpenseLine {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    pub name: String,
+    pub quantity: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_name: Option<String>,
+    pub unit_price: Decimal,
+    /// VAT rate in percent.
+    pub vat_rate: Decimal,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_price_without_vat: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_price_with_vat: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_price_without_vat: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_vat: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_total_price_without_vat: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_total_vat: Option<Decimal>,
+    /// Inventory item which stock is moved by this line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory_item_id: Option<i32>,
+    /// Stock keeping unit of inventory item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    /// Inventory item and stock move of line as returned by API, API v3 only.
+    #[serde(skip_serializing)]
+    pub inventory: Option<LineInventory>,
+    /// Line is deleted by update of expense, see [`mark_destroyed()`](#method.mark_destroyed).
+    #[serde(
+        rename = "_destroy",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    destroy: bool,
+}
+
+impl ExpenseLine {
+    /// New line, VAT rate is given in percent, see `InvoiceLine::new()`.
+    pub fn new<R: Into<Decimal>>(
+        name: &str,
+        quantity: Decimal,
+        unit_name: Option<&str>,
+        unit_price: Decimal,
+        vat_rate: R,
+    ) -> Self {
+        Self {
+            id: None,
+            name: name.to_string(),
+            quantity,
+            unit_name: unit_name.map(|n| n.to_string()),
+            unit_price,
+            vat_rate: vat_rate.into(),
+            unit_price_without_vat: None,
+            unit_price_with_vat: None,
+            total_price_without_vat: None,
+            total_vat: None,
+            native_total_price_without_vat: None,
+            native_total_vat: None,
+            inventory_item_id: None,
+            sku: None,
+            inventory: None,
+            destroy: false,
+        }
+    }
+
+    /// Marks line to be deleted when expense is updated. Line is sent with `_destroy` flag, only
+    /// lines with `id` can be deleted this way.
+    pub fn mark_destroyed(&mut self) {
+        self.destroy = true;
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.destroy
+    }
+}
+
+/// Concise one line summary, e.g.
+/// `Expense 2020-0001 from Supplier s.r.o.: 1210.0 CZK, open, due 2020-02-14`.
+impl fmt::Display for Expense {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "Expense {}",
+            self.number
+                .as_deref()
+                .or(self.original_number.as_deref())
+                .unwrap_or("(no number)")
+        ))?;
+        if let Some(supplier) = self.supplier_name.as_ref() {
+            f.write_fmt(format_args!(" from {}", supplier))?;
+        }
+        let status = self.status.as_ref().map(|s| {
+            match s {
+                ExpenseState::Open => "open",
+                ExpenseState::Overdue => "overdue",
+                ExpenseState::Paid => "paid",
+            }
+            .to_string()
+        });
+        write_summary(
+            f,
+            self.total.as_ref(),
+            self.currency.as_ref().map(Currency::as_str),
+            status,
+            self.due_on.as_ref(),
+        )
+    }
+}
+
+fn money(amount: Option<Decimal>, currency: Option<Currency>) -> Option<Money> {
+    Some(Money::new(amount?, currency?))
+}
+
+/// Writes `: total currency, status, due date` part of document summary.
+fn write_summary(
+    f: &mut Formatter<'_>,
+    total: Option<&Decimal>,
+    currency: Option<&str>,
+    status: Option<String>,
+    due_on: Option<&Date>,
+) -> fmt::Result {
+    let mut parts = Vec::new();
+    if let Some(total) = total {
+        parts.push(match currency {
+            Some(currency) => format!("{} {}", total, currency),
+            None => total.to_string(),
+        });
+    }
+    parts.extend(status);
+    if let Some(due_on) = due_on {
+        parts.push(format!("due {}", due_on));
+    }
+    if !parts.is_empty() {
+        f.write_fmt(format_args!(": {}", parts.join(", ")))?;
+    }
+    Ok(())
+}
+
+/// Link from event to related object.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EventLink {
+    pub rel: String,
+    pub url: String,
+}
+
+/// Bank account configured in account settings, its id can be used as `bank_account_id` of
+/// invoices and payments.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BankAccount {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iban: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swift_bic: Option<String>,
+    /// Incoming payments are paired with invoices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pairing: Option<bool>,
+    /// Outgoing payments are paired with expenses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expense_pairing: Option<bool>,
+    /// Payments which differ from invoice total by rounding are paired too.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_adjustment: Option<bool>,
+    /// Account is used for invoices without `bank_account_id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+/// User of fakturoid.cz. Current user (`Fakturoid::current_user()`) contains list of accounts
+/// accessible by credentials, users of account (`Fakturoid::account_users()`) contain their
+/// permission in the account.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct User {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// Slug of account opened after login.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_account: Option<String>,
+    /// Role of user in account, e.g. `owner`, `power` or `read`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<String>,
+    /// Parts of account accessible by user, e.g. `invoices` or `expenses`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_scope: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<UserAccount>>,
+}
+
+impl User {
+    /// Slugs of accounts accessible by user.
+    pub fn slugs(&self) -> Vec<&str> {
+        self.accounts
+            .iter()
+            .flatten()
+            .map(|a| a.slug.as_str())
+            .collect()
+    }
+}
+
+/// Account accessible by user, its slug can be used to create client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UserAccount {
+    pub slug: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration_no: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_scope: Option<Vec<String>>,
+}
+
+/// Number format (numbering series) of invoices, its id can be used as `number_format_id` of
+/// invoices and generators.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NumberFormat {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    /// Format of number, e.g. `#yyyy#-#dddd#`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Example of number, e.g. `2023-0001`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    /// Format is used for invoices without `number_format_id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Registration of callback URL which is notified about events in account, see
+/// [`webhooks`](../webhooks/index.html) module for parsing of deliveries.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Webhook {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Value of `Authorization` header sent with deliveries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
+    /// Names of events delivered to URL, e.g. `invoice_paid`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+impl Webhook {
+    /// New webhook delivering given events to URL.
+    pub fn new(webhook_url: &str, events: &[&str]) -> Self {
+        Self {
+            webhook_url: Some(webhook_url.to_string()),
+            events: Some(events.iter().map(|e| e.to_string()).collect()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Item in stock of account.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InventoryItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Stock keeping unit, unique in account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    /// Type of article number: `ian`, `ean` or `isbn`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub article_number_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub article_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit_name: Option<String>,
+    /// Quantity in stock is tracked by inventory moves.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_quantity: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_quantity: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_quantity: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_below_zero: Option<bool>,
+    /// Date when quantity fell below minimum.
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub low_quantity_date: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_purchase_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_retail_price: Option<Decimal>,
+    /// VAT rate level: `standard`, `reduced`, `reduced2` or `zero`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vat_rate: Option<String>,
+    /// `goods` or `service`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_native_purchase_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_note: Option<String>,
+    /// Documents where item is suggested: `invoices`, `expenses` or `both`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggest_for: Option<String>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryDirection {
+    In,
+    Out,
+}
+
+/// Inventory item linked to invoice or expense line and stock move created by line.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LineInventory {
+    pub item_id: i32,
+    #[serde(default)]
+    pub sku: Option<String>,
+    #[serde(default)]
+    pub article_number_type: Option<String>,
+    #[serde(default)]
+    pub article_number: Option<String>,
+    /// Inventory move, `None` if item doesn't track quantity.
+    #[serde(default)]
+    pub move_id: Option<i32>,
+}
+
+/// Document (invoice or expense) which created inventory move.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InventoryMoveDocument {
+    pub id: i32,
+    /// `Invoice` or `Expense`.
+    #[serde(rename = "type")]
+    pub document_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_id: Option<i32>,
+}
+
+/// Change of quantity of inventory item in stock.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InventoryMove {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<InventoryDirection>,
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub moved_on: Option<Date>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity_change: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_purchase_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retail_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retail_currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_retail_price: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory_item_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<InventoryMoveDocument>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+}
+
+impl InventoryMove {
+    /// New move of given quantity into or out of stock, it is moved today if `moved_on` isn't
+    /// set.
+    pub fn new(direction: InventoryDirection, quantity_change: Decimal) -> Self {
+        Self {
+            direction: Some(direction),
+            quantity_change: Some(quantity_change),
+            ..Self::default()
+        }
+    }
+}
+
+/// Event in account activity feed, e.g. sent or paid invoice.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Event {
+    /// Type of event, e.g. `invoice_paid`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    /// Human readable description of event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expense_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<i32>,
+    /// Additional data of event, they differ by type of event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<EventLink>>,
+}
+
+/// Object related to todo.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RelatedObject {
+    /// Type of object, e.g. `Invoice`.
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub id: i32,
+}
+
+/// Task in account todo list, e.g. overdue invoice which needs reminder.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Todo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    /// Type of todo, e.g. `invoice_overdue`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    /// Time of completion, `None` for todos which are not completed.
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub completed_at: Option<DateTime>,
+    /// Human readable description of todo.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_objects: Option<Vec<RelatedObject>>,
+    /// Additional data of todo, they differ by type of todo.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Value>,
+}
+
+impl Todo {
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+}
+
+/// Kind of generators listed by `Fakturoid::list_generators()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeneratorKind {
+    /// Generators which create invoices periodically.
+    Recurring,
+    /// Templates used for manually created invoices.
+    Template,
+}
+
+impl fmt::Display for GeneratorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GeneratorKind::Recurring => "recurring",
+            GeneratorKind::Template => "template",
+        })
+    }
+}
+
+/// Actions on recurring generators of API v3, see `Fakturoid::action()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeneratorAction {
+    /// Starts (resumes) creation of invoices, accepts `GeneratorActivateData`.
+    Activate,
+    /// Pauses creation of invoices, e.g. for churned customers.
+    Deactivate,
+}
+
+impl fmt::Display for GeneratorAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GeneratorAction::Activate => "activate",
+            GeneratorAction::Deactivate => "deactivate",
+        })
+    }
+}
+
+/// Data of `GeneratorAction::Activate`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GeneratorActivateData {
+    /// Date when next invoice will be created.
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_occurrence_on: Option<Date>,
+}
+
+/// Template of invoice, recurring generator creates invoices from template periodically.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Generator {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    /// Name of template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Recurring generator creates invoices automatically, other generators are templates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurring: Option<bool>,
+    /// Recurring generator isn't paused, see `GeneratorAction`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proforma: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paypal: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gopay: Option<bool>,
+    /// Date of taxable supply of created invoices is the last day of previous month.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_date_at_end_of_last_month: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number_format_id: Option<i32>,
+    /// First day of recurring generation.
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_date: Option<Date>,
+    /// Last day of recurring generation.
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub end_date: Option<Date>,
+    /// Invoice is created every `months_period` months.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub months_period: Option<i32>,
+    /// Date when next invoice will be created.
+    #[serde(
+        default,
+        with = "dates::option_date",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub next_occurrence_on: Option<Date>,
+    /// Invoices are created on the last day of month.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_day_in_month: Option<bool>,
+    /// Created invoices are sent to client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_email: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer_note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bank_account_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_method: Option<PaymentMethod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<Currency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<InvoiceLanguage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vat_price_mode: Option<VatPriceMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round_total: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transferred_tax_liability: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eu_electronic_service: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtotal: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_subtotal: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_total: Option<Decimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject_url: Option<String>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub updated_at: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<Vec<InvoiceLine>>,
+}
+
+impl Generator {
+    /// Computes dates of next `count` invoices created by recurring generator from
+    /// `next_occurrence_on` (or `start_date`), `months_period`, `last_day_in_month` and
+    /// `end_date`. Fewer dates are returned if generation ends earlier, no dates are returned
+    /// for generators which are not recurring.
+    pub fn upcoming_occurrences(&self, count: usize) -> Vec<Date> {
+        let mut occurrences = Vec::new();
+        if !self.recurring.unwrap_or(false) {
+            return occurrences;
+        }
+        let first = match self.next_occurrence_on.or(self.start_date) {
+            Some(first) => first,
+            None => return occurrences,
+        };
+        let (_, _, day) = dates::ymd(&self.start_date.unwrap_or(first));
+        let day = if self.last_day_in_month.unwrap_or(false) {
+            31
+        } else {
+            day
+        };
+        let period = self.months_period.unwrap_or(1).max(1) as u32;
+
+        let mut date = dates::add_months(&first, 0, day);
+        while occurrences.len() < count {
+            if self.end_date.map(|end| date > end).unwrap_or(false) {
+                break;
+            }
+            occurrences.push(date);
+            date = dates::add_months(&date, period, day);
+        }
+        occurrences
     }
 }
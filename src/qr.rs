@@ -0,0 +1,258 @@
+//! Payment QR codes of invoices.
+//!
+//! Two formats are supported: Czech [QR Platba](https://qr-platba.cz/pro-vyvojare/) (Short
+//! Payment Descriptor, SPAYD) read by Czech and Slovak banking apps and
+//! [EPC QR code](https://www.europeanpaymentscouncil.eu/document-library/guidance-documents/quick-response-code-guidelines-enable-data-capture-initiation)
+//! for SEPA credit transfers in euro. Only text payload is generated, it can be rendered by any
+//! QR code library.
+//!
+//! Payment is to IBAN of invoice, Czech account number (`bank_account`) is converted to IBAN
+//! if IBAN is not set. Amount is the remaining amount of invoice (total if it is not known yet),
+//! variable symbol and due date are used as well. Paid invoices (nothing remains to pay) and
+//! variable symbols which are not 1 to 10 digits are reported as errors.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::models::Invoice;
+//! use fakturoid::fixtures::INVOICE;
+//!
+//! let mut invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+//! invoice.remaining_amount = invoice.total;
+//! assert_eq!(
+//!     invoice.payment_qr().unwrap(),
+//!     "SPD*1.0*ACC:CZ2820100000000000001234*AM:12039.50*CC:CZK*DT:20111023\
+//!      *MSG:Faktura 2012-0021*RN:Alexandr Hejsek*X-VS:20120021"
+//! );
+//! ```
+
+use crate::currency::Currency;
+use crate::dates::{self, Date};
+use crate::error::PaymentQrError;
+use crate::models::Invoice;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Maximal length of message for recipient in QR Platba.
+const SPAYD_MESSAGE_LEN: usize = 60;
+/// Maximal length of name of recipient in QR Platba.
+const SPAYD_NAME_LEN: usize = 35;
+/// Maximal length of name of beneficiary in EPC QR code.
+const EPC_NAME_LEN: usize = 70;
+/// Maximal length of unstructured remittance information in EPC QR code.
+const EPC_TEXT_LEN: usize = 140;
+
+/// Payment details of invoice encoded into QR code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentQr {
+    pub iban: String,
+    pub bic: Option<String>,
+    pub amount: Decimal,
+    pub currency: Currency,
+    /// At most 10 digits, it is checked by [`from_invoice()`](#method.from_invoice).
+    pub variable_symbol: Option<String>,
+    /// Message for recipient, invoice number by default.
+    pub message: Option<String>,
+    pub due_on: Option<Date>,
+    /// Name of recipient (supplier).
+    pub recipient: Option<String>,
+}
+
+impl PaymentQr {
+    /// Payment details of invoice, see [module documentation](index.html).
+    pub fn from_invoice(invoice: &Invoice) -> Result<Self, PaymentQrError> {
+        let iban = match non_empty(&invoice.iban) {
+            Some(iban) => normalize_iban(&iban)?,
+            None => match non_empty(&invoice.bank_account) {
+                Some(account) => czech_iban(&account)?,
+                None => return Err(PaymentQrError::MissingField("iban")),
+            },
+        };
+        let amount = invoice
+            .remaining_amount
+            .or(invoice.total)
+            .or_else(|| {
+                invoice
+                    .lines
+                    .as_ref()
+                    .filter(|lines| !lines.is_empty())
+                    .map(|_| invoice.compute_totals().total)
+            })
+            .ok_or(PaymentQrError::MissingField("total"))?;
+        if amount <= Decimal::ZERO {
+            return Err(PaymentQrError::NothingToPay);
+        }
+        let variable_symbol = non_empty(&invoice.variable_symbol);
+        if let Some(variable_symbol) = variable_symbol.as_ref() {
+            if variable_symbol.len() > 10 || !variable_symbol.chars().all(|c| c.is_ascii_digit()) {
+                return Err(PaymentQrError::InvalidVariableSymbol(
+                    variable_symbol.clone(),
+                ));
+            }
+        }
+        Ok(Self {
+            iban,
+            bic: non_empty(&invoice.swift_bic).map(|bic| bic.replace(' ', "").to_uppercase()),
+            amount: amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+            currency: invoice
+                .currency
+                .ok_or(PaymentQrError::MissingField("currency"))?,
+            variable_symbol,
+            message: non_empty(&invoice.number).map(|number| format!("Faktura {}", number)),
+            due_on: invoice.due_on,
+            recipient: non_empty(&invoice.your_name),
+        })
+    }
+
+    /// Payload of QR Platba, e.g. `SPD*1.0*ACC:CZ...*AM:100.00*CC:CZK*X-VS:2012021`.
+    pub fn to_spayd(&self) -> String {
+        let mut account = self.iban.clone();
+        if let Some(bic) = self.bic.as_ref() {
+            account.push('+');
+            account.push_str(bic);
+        }
+        let mut fields = vec![
+            ("ACC", account),
+            ("AM", format!("{:.2}", self.amount)),
+            ("CC", self.currency.to_string()),
+        ];
+        if let Some(due_on) = self.due_on.as_ref() {
+            let (year, month, day) = dates::ymd(due_on);
+            fields.push(("DT", format!("{:04}{:02}{:02}", year, month, day)));
+        }
+        if let Some(message) = self.message.as_ref() {
+            fields.push(("MSG", truncate(message, SPAYD_MESSAGE_LEN)));
+        }
+        if let Some(recipient) = self.recipient.as_ref() {
+            fields.push(("RN", truncate(recipient, SPAYD_NAME_LEN)));
+        }
+        if let Some(variable_symbol) = self.variable_symbol.as_ref() {
+            fields.push(("X-VS", variable_symbol.clone()));
+        }
+        let mut payload = "SPD*1.0".to_string();
+        for (key, value) in fields {
+            payload.push('*');
+            payload.push_str(key);
+            payload.push(':');
+            payload.push_str(&value.replace('*', "%2A"));
+        }
+        payload
+    }
+
+    /// Payload of EPC QR code (version 002), only payments in euro are supported. Variable
+    /// symbol is part of remittance text, as Slovak banks expect it (`/VS20120021/`).
+    pub fn to_epc(&self) -> Result<String, PaymentQrError> {
+        if self.currency != Currency::EUR {
+            return Err(PaymentQrError::UnsupportedCurrency(self.currency));
+        }
+        let recipient = self
+            .recipient
+            .as_ref()
+            .ok_or(PaymentQrError::MissingField("your_name"))?;
+        let mut text = String::new();
+        if let Some(variable_symbol) = self.variable_symbol.as_ref() {
+            text.push_str(&format!("/VS{}/", variable_symbol));
+        }
+        if let Some(message) = self.message.as_ref() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(message);
+        }
+        let lines = [
+            "BCD".to_string(),
+            "002".to_string(),
+            "1".to_string(),
+            "SCT".to_string(),
+            self.bic.clone().unwrap_or_default(),
+            truncate(recipient, EPC_NAME_LEN),
+            self.iban.clone(),
+            format!("EUR{:.2}", self.amount),
+            String::new(),
+            String::new(),
+            truncate(&text, EPC_TEXT_LEN),
+        ];
+        Ok(lines.join("\n"))
+    }
+}
+
+impl Invoice {
+    /// Payload of QR Platba (SPAYD) for payment of invoice, see [`qr`](../qr/index.html)
+    /// module.
+    pub fn payment_qr(&self) -> Result<String, PaymentQrError> {
+        Ok(PaymentQr::from_invoice(self)?.to_spayd())
+    }
+
+    /// Payload of EPC QR code for payment of invoice in euro, see [`qr`](../qr/index.html)
+    /// module.
+    pub fn payment_qr_epc(&self) -> Result<String, PaymentQrError> {
+        PaymentQr::from_invoice(self)?.to_epc()
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+fn truncate(value: &str, len: usize) -> String {
+    value.chars().take(len).collect()
+}
+
+/// IBAN without spaces in upper case, its check digits are verified.
+fn normalize_iban(iban: &str) -> Result<String, PaymentQrError> {
+    let normalized: String = iban
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase();
+    let valid = normalized.len() >= 15
+        && normalized.len() <= 34
+        && normalized.chars().all(|c| c.is_ascii_alphanumeric())
+        && normalized.is_char_boundary(4)
+        && iban_remainder(&normalized[4..], &normalized[..4]) == Some(1);
+    if valid {
+        Ok(normalized)
+    } else {
+        Err(PaymentQrError::InvalidAccount(iban.to_string()))
+    }
+}
+
+/// IBAN of Czech account number `prefix-number/bank`, prefix is optional.
+fn czech_iban(account: &str) -> Result<String, PaymentQrError> {
+    let invalid = || PaymentQrError::InvalidAccount(account.to_string());
+    let account_no: String = account.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parts = account_no.splitn(2, '/');
+    let number = parts.next().ok_or_else(invalid)?;
+    let bank = parts.next().ok_or_else(invalid)?;
+    let (prefix, number) = match number.find('-') {
+        Some(index) => (&number[..index], &number[index + 1..]),
+        None => ("0", number),
+    };
+    let digits = |value: &str, max: usize| {
+        !value.is_empty() && value.len() <= max && value.chars().all(|c| c.is_ascii_digit())
+    };
+    if bank.len() != 4 || !digits(bank, 4) || !digits(prefix, 6) || !digits(number, 10) {
+        return Err(invalid());
+    }
+    let bban = format!("{}{:0>6}{:0>10}", bank, prefix, number);
+    let check = 98 - iban_remainder(&bban, "CZ00").ok_or_else(invalid)?;
+    Ok(format!("CZ{:02}{}", check, bban))
+}
+
+/// Remainder of IBAN (BBAN followed by country code and check digits) modulo 97, letters are
+/// converted to numbers (A = 10, ..., Z = 35).
+fn iban_remainder(bban: &str, country_and_check: &str) -> Option<u32> {
+    let mut remainder = 0u32;
+    for c in bban.chars().chain(country_and_check.chars()) {
+        let value = c.to_digit(36)?;
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+    Some(remainder)
+}
@@ -0,0 +1,125 @@
+//! Convenience queries built on filters, which download all pages of results.
+
+use crate::client::Fakturoid;
+use crate::dates;
+use crate::error::FakturoidError;
+use crate::filters::{EventFilters, InvoiceFilters};
+use crate::models::{Event, Invoice, InvoiceState};
+use futures::TryStreamExt;
+
+impl Fakturoid {
+    /// Proformas past their due date without related tax document, i.e. proformas which were
+    /// not paid and need follow-up. All pages of overdue invoices are downloaded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     for proforma in client.expired_proformas().await? {
+    ///         println!("{}", proforma);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn expired_proformas(&self) -> Result<Vec<Invoice>, FakturoidError> {
+        let today = dates::today();
+        let invoices: Vec<Invoice> = self
+            .list_filtered(InvoiceFilters::new().status(InvoiceState::Overdue))
+            .await?
+            .into_stream()
+            .try_collect()
+            .await?;
+        Ok(invoices
+            .into_iter()
+            .filter(|i| i.is_proforma() && i.related_id.is_none())
+            .filter(|i| i.due_on.map(|due_on| due_on < today).unwrap_or(true))
+            .collect())
+    }
+
+    /// Overdue invoices, proformas are not included (see
+    /// [`expired_proformas()`](#method.expired_proformas)). All pages are downloaded.
+    pub async fn overdue_invoices(&self) -> Result<Vec<Invoice>, FakturoidError> {
+        self.unpaid_invoices(1).await
+    }
+
+    /// Invoices which are not paid and are at least `min_days_overdue` days after their due
+    /// date. With zero days also open and sent invoices which are not due yet are returned.
+    /// Proformas are not included. All pages are downloaded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     let to_remind = client.unpaid_invoices(14).await?;
+    ///     println!("{} invoices are more than two weeks overdue", to_remind.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn unpaid_invoices(
+        &self,
+        min_days_overdue: u32,
+    ) -> Result<Vec<Invoice>, FakturoidError> {
+        let statuses = if min_days_overdue == 0 {
+            vec![
+                InvoiceState::Open,
+                InvoiceState::Sent,
+                InvoiceState::Overdue,
+            ]
+        } else {
+            vec![InvoiceState::Overdue]
+        };
+        let latest_due = dates::add_days(&dates::today(), -i64::from(min_days_overdue));
+
+        let mut unpaid = Vec::new();
+        for status in statuses {
+            let invoices: Vec<Invoice> = self
+                .list_filtered(InvoiceFilters::new().status(status))
+                .await?
+                .into_stream()
+                .try_collect()
+                .await?;
+            unpaid.extend(invoices.into_iter().filter(|i| {
+                !i.is_proforma()
+                    && (min_days_overdue == 0
+                        || i.due_on.map(|due_on| due_on <= latest_due).unwrap_or(false))
+            }));
+        }
+        Ok(unpaid)
+    }
+
+    /// History of events related to subject, newest first, e.g. for support tooling showing
+    /// what happened with the client. All pages are downloaded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use fakturoid::client::Fakturoid;
+    ///
+    /// # extern crate tokio;
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+    ///     for event in client.subject_events(16).await? {
+    ///         println!("{}", event.text.unwrap_or_default());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn subject_events(&self, subject_id: i32) -> Result<Vec<Event>, FakturoidError> {
+        self.list_filtered(EventFilters::new().subject_id(subject_id))
+            .await?
+            .into_stream()
+            .try_collect()
+            .await
+    }
+}
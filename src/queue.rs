@@ -0,0 +1,181 @@
+//! Scheduling of requests within rate limit of fakturoid.cz API shared by requests of
+//! different priority.
+
+use crate::error::FakturoidError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Priority of requests sent by client, see `Fakturoid::with_priority()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Priority {
+    /// Requests a user is waiting for. They can use whole rate limit and they are served
+    /// first when the limit is exhausted. Default priority.
+    Interactive,
+    /// Background requests, e.g. synchronization. They can't use part of rate limit reserved
+    /// for interactive requests and wait while any interactive request is waiting.
+    Batch,
+}
+
+struct QueueState {
+    window_start: Instant,
+    used: u32,
+    interactive_waiting: usize,
+}
+
+/// Usage of current window saved by `Fakturoid::shutdown()`, see
+/// `FakturoidBuilder::rate_limit_state()`.
+#[derive(Serialize, Deserialize)]
+struct SavedState {
+    /// Start of window in milliseconds since Unix epoch.
+    window_start: u64,
+    used: u32,
+}
+
+/// Fixed window rate limiter with part of the window reserved for interactive requests.
+pub(crate) struct RequestQueue {
+    limit: u32,
+    reserved: u32,
+    window: Duration,
+    state: Mutex<QueueState>,
+    state_file: Option<PathBuf>,
+}
+
+/// How long batch request sleeps when it yields to waiting interactive requests.
+const YIELD_DELAY: Duration = Duration::from_millis(20);
+
+impl RequestQueue {
+    /// Queue allowing `limit` requests per window, `limit` must not be zero.
+    pub(crate) fn new(limit: u32, reserved: u32, window: Duration) -> Self {
+        Self {
+            limit,
+            reserved: reserved.min(limit.saturating_sub(1)),
+            window,
+            state: Mutex::new(QueueState {
+                window_start: Instant::now(),
+                used: 0,
+                interactive_waiting: 0,
+            }),
+            state_file: None,
+        }
+    }
+
+    /// Restores usage of current window from given file and saves it there in `save()`, so
+    /// restarted program doesn't exceed the limit. Missing or invalid file is ignored.
+    pub(crate) fn persistent(mut self, path: PathBuf) -> Self {
+        if let Some(saved) = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<SavedState>(&content).ok())
+        {
+            let start = UNIX_EPOCH + Duration::from_millis(saved.window_start);
+            let elapsed = SystemTime::now().duration_since(start).unwrap_or_default();
+            if let Some(window_start) = Instant::now().checked_sub(elapsed) {
+                if elapsed < self.window {
+                    let state = self.state.get_mut().unwrap_or_else(|e| e.into_inner());
+                    state.window_start = window_start;
+                    state.used = saved.used;
+                }
+            }
+        }
+        self.state_file = Some(path);
+        self
+    }
+
+    /// Writes usage of current window into file given to `persistent()`.
+    pub(crate) fn save(&self) -> Result<(), FakturoidError> {
+        let path = match self.state_file.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let (elapsed, used) = {
+            let state = self.lock();
+            (state.window_start.elapsed(), state.used)
+        };
+        let start = SystemTime::now()
+            .checked_sub(elapsed)
+            .and_then(|start| start.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+        let saved = SavedState {
+            window_start: start.as_millis() as u64,
+            used,
+        };
+        let content = serde_json::to_vec(&saved).map_err(FakturoidError::from_std_err)?;
+        fs::write(path, content).map_err(FakturoidError::from_std_err)
+    }
+
+    /// Takes one request from the budget or returns how long to wait before next attempt.
+    /// Waiting interactive request is counted until it succeeds, `waiting` tracks whether it
+    /// is already counted.
+    pub(crate) fn try_acquire(
+        &self,
+        priority: Priority,
+        waiting: &mut bool,
+    ) -> Result<(), Duration> {
+        let mut state = self.lock();
+        let now = Instant::now();
+        if now.duration_since(state.window_start) >= self.window {
+            state.window_start = now;
+            state.used = 0;
+        }
+        let available = match priority {
+            Priority::Interactive => state.used < self.limit,
+            Priority::Batch => {
+                state.interactive_waiting == 0 && state.used < self.limit - self.reserved
+            }
+        };
+        if available {
+            state.used += 1;
+            if *waiting {
+                state.interactive_waiting -= 1;
+                *waiting = false;
+            }
+            return Ok(());
+        }
+        if priority == Priority::Interactive && !*waiting {
+            state.interactive_waiting += 1;
+            *waiting = true;
+        }
+        let window_end = self.window - now.duration_since(state.window_start);
+        if priority == Priority::Batch && state.used < self.limit - self.reserved {
+            Err(YIELD_DELAY.min(window_end))
+        } else {
+            Err(window_end)
+        }
+    }
+
+    /// Waits until request with given priority fits into rate limit. Waiting interactive
+    /// request stops being counted when future is dropped, e.g. by timeout.
+    pub(crate) async fn acquire(&self, priority: Priority) {
+        let mut waiting = Waiting {
+            queue: self,
+            counted: false,
+        };
+        while let Err(delay) = self.try_acquire(priority, &mut waiting.counted) {
+            tokio::time::delay_for(delay).await;
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Interactive request waiting in `acquire()`, it is removed from count of waiting requests
+/// when dropped.
+struct Waiting<'a> {
+    queue: &'a RequestQueue,
+    counted: bool,
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        if self.counted {
+            self.queue.lock().interactive_waiting -= 1;
+        }
+    }
+}
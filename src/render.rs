@@ -0,0 +1,244 @@
+//! Localized textual rendering of documents, useful e.g. for chat notifications.
+//!
+//! Texts are rendered in the language of the document. Czech, Slovak, English and German are
+//! supported, other languages fall back to English.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::fixtures::INVOICE;
+//! use fakturoid::models::Invoice;
+//! use fakturoid::render::{render_invoice, TextFormat};
+//!
+//! let invoice: Invoice = serde_json::from_str(INVOICE).unwrap();
+//! assert_eq!(
+//!     render_invoice(&invoice, TextFormat::Plain),
+//!     "Faktura 2012-0021 pro Apple Czech s.r.o.: 12 039,50 CZK, uhrazená, splatná 23. 10. 2011"
+//! );
+//! ```
+
+use crate::dates::{self, Date};
+use crate::models::{Invoice, InvoiceLanguage, InvoiceState};
+use rust_decimal::Decimal;
+
+/// Format of rendered text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextFormat {
+    /// Plain text without any markup.
+    Plain,
+    /// Markdown, document number and total are emphasized.
+    Markdown,
+}
+
+/// Renders one line summary of invoice in its language, e.g.
+/// `Faktura 2024-0042 pro ACME: 12 100 CZK, otevřená, splatná 14. 3. 2024`. Czech is used
+/// when invoice has no language set.
+pub fn render_invoice(invoice: &Invoice, format: TextFormat) -> String {
+    let texts = Texts::for_language(invoice.language.as_ref());
+    let kind = if invoice.proforma.unwrap_or(false) {
+        texts.proforma
+    } else if invoice.correction.unwrap_or(false) {
+        texts.correction
+    } else {
+        texts.invoice
+    };
+    let title = match invoice.number.as_ref() {
+        Some(number) => format!("{} {}", kind, escape(number, format)),
+        None => kind.to_string(),
+    };
+
+    let mut out = emphasize(&title, format);
+    if let Some(client) = invoice.client_name.as_ref() {
+        out.push_str(&format!(" {} {}", texts.client, escape(client, format)));
+    }
+
+    let mut parts = Vec::new();
+    if let Some(total) = invoice.total.as_ref() {
+        let amount = match invoice.currency.as_ref() {
+            Some(currency) => format!("{} {}", texts.amount(total), currency),
+            None => texts.amount(total),
+        };
+        parts.push(emphasize(&amount, format));
+    }
+    if let Some(status) = invoice.status.as_ref() {
+        parts.push(texts.status(status).to_string());
+    }
+    match (invoice.due_on.as_ref(), invoice.due) {
+        (Some(due_on), _) => parts.push(format!("{} {}", texts.due_on, texts.date(due_on))),
+        (None, Some(days)) => parts.push(format!("{} {} {}", texts.due_in, days, texts.days)),
+        (None, None) => {}
+    }
+    if !parts.is_empty() {
+        out.push_str(": ");
+        out.push_str(&parts.join(", "));
+    }
+    out
+}
+
+fn emphasize(text: &str, format: TextFormat) -> String {
+    match format {
+        TextFormat::Plain => text.to_string(),
+        TextFormat::Markdown => format!("**{}**", text),
+    }
+}
+
+fn escape(text: &str, format: TextFormat) -> String {
+    match format {
+        TextFormat::Plain => text.to_string(),
+        TextFormat::Markdown => {
+            let mut escaped = String::with_capacity(text.len());
+            for c in text.chars() {
+                if "\\`*_[]<>#|~".contains(c) {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+            }
+            escaped
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DateStyle {
+    Iso,
+    Spaced,
+    Dotted,
+}
+
+/// Language specific texts and number formatting.
+struct Texts {
+    invoice: &'static str,
+    proforma: &'static str,
+    correction: &'static str,
+    client: &'static str,
+    due_on: &'static str,
+    due_in: &'static str,
+    days: &'static str,
+    statuses: [&'static str; 5],
+    thousands: char,
+    decimal: char,
+    date_style: DateStyle,
+}
+
+const CS: Texts = Texts {
+    invoice: "Faktura",
+    proforma: "Zálohová faktura",
+    correction: "Opravný daňový doklad",
+    client: "pro",
+    due_on: "splatná",
+    due_in: "splatná za",
+    days: "dní",
+    statuses: [
+        "otevřená",
+        "odeslaná",
+        "po splatnosti",
+        "uhrazená",
+        "stornovaná",
+    ],
+    thousands: ' ',
+    decimal: ',',
+    date_style: DateStyle::Spaced,
+};
+
+const SK: Texts = Texts {
+    invoice: "Faktúra",
+    proforma: "Zálohová faktúra",
+    correction: "Opravná faktúra",
+    client: "pre",
+    due_on: "splatná",
+    due_in: "splatná za",
+    days: "dní",
+    statuses: [
+        "otvorená",
+        "odoslaná",
+        "po splatnosti",
+        "uhradená",
+        "stornovaná",
+    ],
+    thousands: ' ',
+    decimal: ',',
+    date_style: DateStyle::Spaced,
+};
+
+const EN: Texts = Texts {
+    invoice: "Invoice",
+    proforma: "Proforma invoice",
+    correction: "Corrective invoice",
+    client: "for",
+    due_on: "due",
+    due_in: "due in",
+    days: "days",
+    statuses: ["open", "sent", "overdue", "paid", "cancelled"],
+    thousands: ',',
+    decimal: '.',
+    date_style: DateStyle::Iso,
+};
+
+const DE: Texts = Texts {
+    invoice: "Rechnung",
+    proforma: "Proformarechnung",
+    correction: "Korrekturrechnung",
+    client: "für",
+    due_on: "fällig am",
+    due_in: "fällig in",
+    days: "Tagen",
+    statuses: ["offen", "versendet", "überfällig", "bezahlt", "storniert"],
+    thousands: '.',
+    decimal: ',',
+    date_style: DateStyle::Dotted,
+};
+
+impl Texts {
+    fn for_language(language: Option<&InvoiceLanguage>) -> &'static Texts {
+        match language {
+            None | Some(InvoiceLanguage::Cz) => &CS,
+            Some(InvoiceLanguage::Sk) => &SK,
+            Some(InvoiceLanguage::De) => &DE,
+            Some(_) => &EN,
+        }
+    }
+
+    fn status(&self, status: &InvoiceState) -> &'static str {
+        match status {
+            InvoiceState::Open => self.statuses[0],
+            InvoiceState::Sent => self.statuses[1],
+            InvoiceState::Overdue => self.statuses[2],
+            InvoiceState::Paid => self.statuses[3],
+            InvoiceState::Cancelled => self.statuses[4],
+        }
+    }
+
+    /// Formats amount with thousands separator. Whole amounts are rendered without decimal
+    /// places, others with two.
+    // `usize::is_multiple_of()` suggested by clippy needs Rust 1.87.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn amount(&self, amount: &Decimal) -> String {
+        let rounded = amount.round_dp(2);
+        let whole = rounded.trunc().abs().to_string();
+        let mut out = String::new();
+        if rounded.is_sign_negative() && !rounded.is_zero() {
+            out.push('-');
+        }
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                out.push(self.thousands);
+            }
+            out.push(c);
+        }
+        let fract = rounded.fract().abs();
+        if !fract.is_zero() {
+            out.push(self.decimal);
+            out.push_str(&format!("{:02}", (fract * Decimal::new(100, 0)).trunc()));
+        }
+        out
+    }
+
+    fn date(&self, date: &Date) -> String {
+        let (year, month, day) = dates::ymd(date);
+        match self.date_style {
+            DateStyle::Iso => format!("{:04}-{:02}-{:02}", year, month, day),
+            DateStyle::Spaced => format!("{}. {}. {}", day, month, year),
+            DateStyle::Dotted => format!("{:02}.{:02}.{}", day, month, year),
+        }
+    }
+}
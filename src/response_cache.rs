@@ -0,0 +1,193 @@
+//! Cache of GET responses validated with `ETag` and `Last-Modified` headers.
+
+use lru::LruCache;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Header marking response replayed from cache after API answered `304 Not Modified`.
+const CACHE_STATUS: &str = "x-fakturoid-cache";
+
+/// Response stored in cache together with its validators.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    headers: Vec<(String, String)>,
+    /// Raw body, so binary responses (PDFs, attachments) are replayed unchanged. It is stored
+    /// as base64 on disk.
+    #[serde(with = "base64_body")]
+    body: Vec<u8>,
+}
+
+impl CachedResponse {
+    /// Reads body of successful response with validators. Cache entry is returned together with
+    /// response of the same content, original response is returned if it can't be cached.
+    pub(crate) async fn from_response(
+        url: &str,
+        response: Response,
+    ) -> Result<(Option<Self>, Response), reqwest::Error> {
+        let etag = header_str(response.headers(), &ETAG);
+        let last_modified = header_str(response.headers(), &LAST_MODIFIED);
+        if response.status() != StatusCode::OK || (etag.is_none() && last_modified.is_none()) {
+            return Ok((None, response));
+        }
+
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|v| (name.to_string(), v.to_string()))
+            })
+            .collect();
+        let body = response.bytes().await?.to_vec();
+        let entry = Self {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            headers,
+            body,
+        };
+        let response = entry.to_response();
+        Ok((Some(entry), response))
+    }
+
+    /// Adds conditional headers to request, so API returns `304 Not Modified` if cached
+    /// response is still valid.
+    pub(crate) fn apply_conditions(&self, request: &mut Request) {
+        if let Some(etag) = self
+            .etag
+            .as_ref()
+            .and_then(|e| HeaderValue::from_str(e).ok())
+        {
+            request.headers_mut().insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = self
+            .last_modified
+            .as_ref()
+            .and_then(|m| HeaderValue::from_str(m).ok())
+        {
+            request.headers_mut().insert(IF_MODIFIED_SINCE, modified);
+        }
+    }
+
+    /// Response with cached status, headers and body.
+    pub(crate) fn to_response(&self) -> Response {
+        let mut response = http::Response::new(self.body.clone());
+        for (name, value) in self.headers.iter() {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response.into()
+    }
+
+    /// Response replayed after API confirmed that cached response is still valid. It is marked,
+    /// see [`is_revalidated()`](fn.is_revalidated.html).
+    pub(crate) fn to_revalidated_response(&self) -> Response {
+        let mut response = self.to_response();
+        response
+            .headers_mut()
+            .insert(CACHE_STATUS, HeaderValue::from_static("hit"));
+        response
+    }
+}
+
+/// True if response was replayed from cache.
+pub(crate) fn is_revalidated(response: &Response) -> bool {
+    response.headers().contains_key(CACHE_STATUS)
+}
+
+mod base64_body {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(body: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(body))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        base64::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+fn header_str(headers: &HeaderMap, name: &HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// Storage of cached responses.
+pub(crate) enum ResponseCache {
+    /// Persisted in directory, one file per URL.
+    Disk(PathBuf),
+    /// Kept in memory, least recently used responses are dropped when capacity is reached.
+    Memory(Mutex<LruCache<String, CachedResponse>>),
+}
+
+impl ResponseCache {
+    pub(crate) fn disk(dir: PathBuf) -> Self {
+        ResponseCache::Disk(dir)
+    }
+
+    pub(crate) fn memory(capacity: usize) -> Self {
+        ResponseCache::Memory(Mutex::new(LruCache::new(capacity)))
+    }
+
+    pub(crate) fn get(&self, url: &str) -> Option<CachedResponse> {
+        match self {
+            ResponseCache::Disk(dir) => {
+                let content = fs::read(Self::path(dir, url)).ok()?;
+                let entry: CachedResponse = serde_json::from_slice(&content).ok()?;
+                if entry.url == url {
+                    Some(entry)
+                } else {
+                    None
+                }
+            }
+            ResponseCache::Memory(entries) => entries.lock().ok()?.get(&url.to_string()).cloned(),
+        }
+    }
+
+    /// Stores response. Cache is only an optimization, so I/O errors are ignored.
+    pub(crate) fn put(&self, entry: &CachedResponse) {
+        match self {
+            ResponseCache::Disk(dir) => {
+                if fs::create_dir_all(dir).is_ok() {
+                    if let Ok(content) = serde_json::to_vec(entry) {
+                        let _ = fs::write(Self::path(dir, &entry.url), content);
+                    }
+                }
+            }
+            ResponseCache::Memory(entries) => {
+                if let Ok(mut entries) = entries.lock() {
+                    entries.put(entry.url.clone(), entry.clone());
+                }
+            }
+        }
+    }
+
+    fn path(dir: &Path, url: &str) -> PathBuf {
+        dir.join(format!("{:016x}.json", fnv1a(url)))
+    }
+}
+
+/// FNV-1a hash, stable across program runs and Rust versions unlike `DefaultHasher`.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
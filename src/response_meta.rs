@@ -0,0 +1,89 @@
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Metadata of HTTP response: status, headers, rate limit and id of request.
+///
+/// Rate limit is read from `X-RateLimit-Policy` and `X-RateLimit` headers of API v3
+/// (e.g. `default;q=400;w=60` and `default;r=398;t=55`) or from `X-RateLimit-Limit`,
+/// `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers.
+#[derive(Clone, Debug)]
+pub struct ResponseMeta {
+    status: StatusCode,
+    headers: HeaderMap,
+    cached: bool,
+}
+
+impl ResponseMeta {
+    pub(crate) fn new(status: StatusCode, headers: HeaderMap, cached: bool) -> Self {
+        Self {
+            status,
+            headers,
+            cached,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// All headers of response.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// True if response was served from response cache, because API reported it unchanged.
+    pub fn was_cached(&self) -> bool {
+        self.cached
+    }
+
+    /// Id assigned to request by API, it should be mentioned when contacting support.
+    pub fn request_id(&self) -> Option<&str> {
+        self.header("X-Request-Id")
+    }
+
+    /// Number of requests allowed in rate limit window.
+    pub fn rate_limit(&self) -> Option<u32> {
+        self.policy_param("X-RateLimit-Policy", "q")
+            .or_else(|| self.number("X-RateLimit-Limit"))
+    }
+
+    /// Number of requests remaining in current rate limit window.
+    pub fn rate_limit_remaining(&self) -> Option<u32> {
+        self.policy_param("X-RateLimit", "r")
+            .or_else(|| self.number("X-RateLimit-Remaining"))
+    }
+
+    /// Time until rate limit window is reset.
+    pub fn rate_limit_reset(&self) -> Option<Duration> {
+        self.policy_param("X-RateLimit", "t")
+            .or_else(|| self.number("X-RateLimit-Reset"))
+            .map(|secs| Duration::from_secs(u64::from(secs)))
+    }
+
+    /// Total number of records in list, if API sends it in `X-Total-Count` header.
+    pub fn total_count(&self) -> Option<u32> {
+        self.number("X-Total-Count")
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok().map(|v| v.trim())
+    }
+
+    fn number(&self, name: &str) -> Option<u32> {
+        self.header(name)?.parse().ok()
+    }
+
+    /// Parameter of structured rate limit header, e.g. `r` of `default;r=398;t=55`.
+    fn policy_param(&self, name: &str, param: &str) -> Option<u32> {
+        self.header(name)?
+            .split(';')
+            .skip(1)
+            .filter_map(|part| {
+                let mut pair = part.splitn(2, '=');
+                Some((pair.next()?.trim(), pair.next()?.trim()))
+            })
+            .find(|(key, _)| *key == param)
+            .and_then(|(_, value)| value.parse().ok())
+    }
+}
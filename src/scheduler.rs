@@ -0,0 +1,310 @@
+//! Scheduler of recurring jobs, e.g. daily dunning run or hourly synchronization. Available with
+//! `scheduler` feature.
+//!
+//! Jobs are planned by cron-like expressions with five fields: minute, hour, day of month, month
+//! and day of week (0 or 7 is Sunday). Fields can contain `*`, numbers, ranges `1-5`, steps
+//! `*/15` and lists `1,15`. Shortcuts `@hourly`, `@daily`, `@weekly` and `@monthly` are supported
+//! too. Times are in local time zone.
+//!
+//! Jobs get client with batch priority (see `Fakturoid::with_priority()`), so with rate limit set
+//! they don't starve interactive requests. Job is never run twice at the same time, run which
+//! would overlap with still running previous one is skipped.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::filters::InvoiceFilters;
+//! use fakturoid::models::{Invoice, InvoiceState};
+//! use fakturoid::scheduler::Scheduler;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+//!         .rate_limit(200, 50)
+//!         .build()?;
+//!     let mut scheduler = Scheduler::new(client);
+//!     scheduler.add_job("overdue report", "0 6 * * 1-5", |client| async move {
+//!         let invoices = client
+//!             .list_filtered(InvoiceFilters::new().status(InvoiceState::Overdue))
+//!             .await?;
+//!         println!("{} overdue invoices", invoices.data().len());
+//!         Ok(())
+//!     })?;
+//!     scheduler.run().await;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::{Fakturoid, Priority};
+use crate::dates;
+use crate::error::{FakturoidError, InvalidSchedule};
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Timelike,
+};
+use futures::future::BoxFuture;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::delay_for;
+
+/// Parsed cron-like expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days: Vec<u32>,
+    months: Vec<u32>,
+    weekdays: Vec<u32>,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+/// How many days ahead next run time is searched.
+const MAX_DAYS_AHEAD: i64 = 366 * 5;
+
+fn parse_field(field: &str, min: u32, max: u32) -> Option<Vec<u32>> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.find('/') {
+            Some(slash) => (&part[..slash], part[slash + 1..].parse().ok()?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return None;
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some(dash) = range.find('-') {
+            (range[..dash].parse().ok()?, range[dash + 1..].parse().ok()?)
+        } else {
+            let value = range.parse().ok()?;
+            (value, if step > 1 { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        values.extend((start..=end).step_by(step));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Some(values)
+}
+
+impl Schedule {
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let day = self.days.contains(&date.day());
+        let weekday = self
+            .weekdays
+            .contains(&date.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// First time matching the schedule which is strictly after given time.
+    pub fn next_after(&self, after: &dates::DateTime) -> Option<dates::DateTime> {
+        dates::from_local(self.next_after_local(&dates::to_local(after)?)?)
+    }
+
+    pub(crate) fn next_after_local(&self, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = (after.naive_local() + ChronoDuration::minutes(1))
+            .with_second(0)?
+            .with_nanosecond(0)?;
+        let start_date = start.date();
+        for offset in 0..MAX_DAYS_AHEAD {
+            let date = start_date + ChronoDuration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            for &hour in &self.hours {
+                for &minute in &self.minutes {
+                    let time = date.and_hms_opt(hour, minute, 0)?;
+                    if offset == 0 && time < start {
+                        continue;
+                    }
+                    // times skipped by daylight saving change don't exist
+                    if let Some(time) = Local.from_local_datetime(&time).earliest() {
+                        return Some(time);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = InvalidSchedule;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(InvalidSchedule::new(expr));
+        }
+        let parse = |index: usize, min: u32, max: u32| {
+            parse_field(fields[index], min, max).ok_or_else(|| InvalidSchedule::new(expr))
+        };
+        let mut weekdays = parse(4, 0, 7)?;
+        if weekdays.contains(&7) {
+            weekdays.retain(|d| *d != 7);
+            if !weekdays.contains(&0) {
+                weekdays.insert(0, 0);
+            }
+        }
+        Ok(Self {
+            minutes: parse(0, 0, 59)?,
+            hours: parse(1, 0, 23)?,
+            days: parse(2, 1, 31)?,
+            months: parse(3, 1, 12)?,
+            weekdays,
+            days_restricted: fields[2] != "*",
+            weekdays_restricted: fields[4] != "*",
+        })
+    }
+}
+
+type JobFn = Arc<dyn Fn(Fakturoid) -> BoxFuture<'static, Result<(), FakturoidError>> + Send + Sync>;
+type ErrorHandler = Arc<dyn Fn(&str, &FakturoidError) + Send + Sync>;
+
+struct Job {
+    name: String,
+    schedule: Schedule,
+    task: JobFn,
+    running: Arc<AtomicBool>,
+    next_run: Option<DateTime<Local>>,
+}
+
+/// Runs registered jobs according to their schedules.
+pub struct Scheduler {
+    client: Fakturoid,
+    jobs: Vec<Job>,
+    on_error: Option<ErrorHandler>,
+}
+
+impl Scheduler {
+    /// Creates scheduler which passes batch priority clone of given client to jobs.
+    pub fn new(client: Fakturoid) -> Self {
+        Self {
+            client: client.with_priority(Priority::Batch),
+            jobs: Vec::new(),
+            on_error: None,
+        }
+    }
+
+    /// Registers job run according to cron-like expression, see [module](index.html)
+    /// documentation.
+    pub fn add_job<F, Fut>(
+        &mut self,
+        name: &str,
+        schedule: &str,
+        job: F,
+    ) -> Result<&mut Self, InvalidSchedule>
+    where
+        F: Fn(Fakturoid) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), FakturoidError>> + Send + 'static,
+    {
+        let schedule: Schedule = schedule.parse()?;
+        self.jobs.push(Job {
+            name: name.to_string(),
+            next_run: schedule.next_after_local(&Local::now()),
+            schedule,
+            task: Arc::new(move |client| Box::pin(job(client))),
+            running: Arc::new(AtomicBool::new(false)),
+        });
+        Ok(self)
+    }
+
+    /// Handler called with name of job and error when job fails. Failures are logged on warning
+    /// level with `log` feature.
+    pub fn on_error<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&str, &FakturoidError) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(handler));
+        self
+    }
+
+    /// Time when next job is due.
+    pub fn next_run(&self) -> Option<dates::DateTime> {
+        dates::from_local(self.next_run_local()?)
+    }
+
+    fn next_run_local(&self) -> Option<DateTime<Local>> {
+        self.jobs.iter().filter_map(|j| j.next_run).min()
+    }
+
+    /// Runs jobs forever. Each run of job is spawned as separate task.
+    pub async fn run(mut self) {
+        while let Some(next_run) = self.next_run_local() {
+            let now = Local::now();
+            if next_run > now {
+                delay_for((next_run - now).to_std().unwrap_or_default()).await;
+                continue;
+            }
+            for job in self.jobs.iter_mut() {
+                if job.next_run.map(|t| t <= now) != Some(true) {
+                    continue;
+                }
+                job.next_run = job.schedule.next_after_local(&now);
+                start(job, &self.client, &self.on_error);
+            }
+        }
+    }
+
+    /// Starts job with given name at once, outside of its schedule (e.g. synchronization
+    /// requested by user). Handle of spawned run is returned, `None` if there is no such job or
+    /// it is still running. Must be called within Tokio runtime.
+    pub fn run_now(&self, name: &str) -> Option<JoinHandle<()>> {
+        let job = self.jobs.iter().find(|j| j.name == name)?;
+        start(job, &self.client, &self.on_error)
+    }
+}
+
+/// Spawns run of job, `None` if previous run is still running.
+fn start(job: &Job, client: &Fakturoid, on_error: &Option<ErrorHandler>) -> Option<JoinHandle<()>> {
+    if job.running.swap(true, Ordering::SeqCst) {
+        #[cfg(feature = "log")]
+        log::warn!("job {} is still running, run is skipped", job.name);
+        return None;
+    }
+    let running = Running(job.running.clone());
+    let name = job.name.clone();
+    let task = (job.task)(client.clone());
+    let on_error = on_error.clone();
+    Some(tokio::spawn(async move {
+        let _running = running;
+        if let Err(err) = task.await {
+            #[cfg(feature = "log")]
+            log::warn!("job {} failed: {}", name, err);
+            if let Some(handler) = on_error {
+                handler(&name, &err);
+            }
+        }
+    }))
+}
+
+/// Marks job as running, flag is cleared when run ends even if job panics.
+struct Running(Arc<AtomicBool>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
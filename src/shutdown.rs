@@ -0,0 +1,61 @@
+//! Tracking of requests in flight, so client can be shut down without interrupting them.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::delay_for;
+
+/// How often shutdown checks whether in-flight requests have finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Shutdown state shared by all clones of client.
+#[derive(Default)]
+pub(crate) struct ShutdownState {
+    closed: AtomicBool,
+    in_flight: AtomicUsize,
+}
+
+/// Marks request as in flight until dropped. It is kept together with response until its body
+/// is consumed.
+pub(crate) struct InFlight(Arc<ShutdownState>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ShutdownState {
+    /// Registers new request, `None` is returned if client is shut down.
+    pub(crate) fn enter(self: &Arc<Self>) -> Option<InFlight> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(self.clone());
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Stops accepting new requests and waits until requests in flight finish. Returns number of
+    /// requests which are still in flight after timeout.
+    pub(crate) async fn close(&self, timeout: Duration) -> usize {
+        self.closed.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + timeout;
+        loop {
+            let in_flight = self.in_flight();
+            let now = Instant::now();
+            if in_flight == 0 || now >= deadline {
+                return in_flight;
+            }
+            delay_for(POLL_INTERVAL.min(deadline - now)).await;
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Incremental parsing of JSON arrays, so items of list can be deserialized while response body
+//! is still being downloaded and only one item is kept in memory at a time.
+
+use serde::de::{DeserializeOwned, Error as _};
+
+enum Position {
+    /// Before opening bracket of array.
+    Start,
+    /// After opening bracket, array can be empty.
+    Opened,
+    /// After comma between items, next item must follow.
+    Between,
+    /// Inside of item.
+    Item,
+    /// After closing bracket of array.
+    End,
+}
+
+/// Splits JSON array fed in chunks into its items.
+pub(crate) struct ArrayParser {
+    position: Position,
+    item: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl ArrayParser {
+    pub(crate) fn new() -> Self {
+        Self {
+            position: Position::Start,
+            item: Vec::new(),
+            depth: 0,
+            in_string: false,
+            escaped: false,
+        }
+    }
+
+    /// Checks that the whole array was read, called at the end of body.
+    pub(crate) fn finish(&self) -> Result<(), serde_json::Error> {
+        match self.position {
+            Position::End => Ok(()),
+            _ => Err(serde_json::Error::custom("unexpected end of JSON array")),
+        }
+    }
+
+    /// Feeds next chunk of body, items completed by this chunk are deserialized into `items`.
+    pub(crate) fn feed<T: DeserializeOwned>(
+        &mut self,
+        chunk: &[u8],
+        items: &mut Vec<T>,
+    ) -> Result<(), serde_json::Error> {
+        for &byte in chunk {
+            match self.position {
+                Position::Start => match byte {
+                    b'[' => self.position = Position::Opened,
+                    b if b.is_ascii_whitespace() => {}
+                    _ => return Err(serde_json::Error::custom("expected JSON array")),
+                },
+                Position::Opened | Position::Between => match byte {
+                    b']' if matches!(self.position, Position::Opened) => {
+                        self.position = Position::End
+                    }
+                    b',' | b']' => return Err(serde_json::Error::custom("expected array item")),
+                    b if b.is_ascii_whitespace() => {}
+                    _ => {
+                        self.position = Position::Item;
+                        self.push(byte);
+                    }
+                },
+                Position::Item => {
+                    if self.depth == 0 && !self.in_string && (byte == b',' || byte == b']') {
+                        items.push(serde_json::from_slice(&self.item)?);
+                        self.item.clear();
+                        self.position = if byte == b']' {
+                            Position::End
+                        } else {
+                            Position::Between
+                        };
+                    } else {
+                        self.push(byte);
+                    }
+                }
+                Position::End => {
+                    if !byte.is_ascii_whitespace() {
+                        return Err(serde_json::Error::custom("trailing data after JSON array"));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.item.push(byte);
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+            }
+            return;
+        }
+        match byte {
+            b'"' => self.in_string = true,
+            b'{' | b'[' => self.depth += 1,
+            b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+}
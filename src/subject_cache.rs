@@ -0,0 +1,66 @@
+//! In-memory LRU cache of subject details.
+
+use lru::LruCache;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Cache of subjects keyed by subject id. Subjects are stored as JSON values, entries older
+/// than TTL are dropped on access.
+pub(crate) struct SubjectCache {
+    entries: Mutex<LruCache<i32, (Instant, Value)>>,
+    ttl: Duration,
+}
+
+impl SubjectCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            ttl,
+        }
+    }
+
+    /// Empty cache with the same capacity and TTL.
+    pub(crate) fn empty_copy(&self) -> Self {
+        let capacity = match self.entries.lock() {
+            Ok(entries) => entries.cap(),
+            Err(err) => err.into_inner().cap(),
+        };
+        Self::new(capacity, self.ttl)
+    }
+
+    pub(crate) fn get(&self, id: i32) -> Option<Value> {
+        let mut entries = self.entries.lock().ok()?;
+        let expired = match entries.get(&id) {
+            Some((stored, value)) => {
+                if stored.elapsed() < self.ttl {
+                    return Some(value.clone());
+                }
+                true
+            }
+            None => false,
+        };
+        if expired {
+            entries.pop(&id);
+        }
+        None
+    }
+
+    pub(crate) fn insert(&self, id: i32, value: Value) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.put(id, (Instant::now(), value));
+        }
+    }
+
+    pub(crate) fn remove(&self, id: i32) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.pop(&id);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Incremental synchronization of subjects, invoices and expenses, e.g. for mirroring of
+//! fakturoid.cz into local database.
+//!
+//! [`Fakturoid::changes_since()`](../client/struct.Fakturoid.html#method.changes_since) lists
+//! records updated since given time. Subjects, invoices and expenses are downloaded in parallel,
+//! all pages of each list are followed and records are merged into one stream of
+//! [`Change`](enum.Change.html)s as they arrive, so records of different kinds are interleaved.
+//! The latest `updated_at` of received records should be stored as time of next synchronization
+//! only after the stream ended without error.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::sync::Change;
+//! use futures::TryStreamExt;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let last_sync = None;
+//!     let mut changes = client.changes_since(last_sync);
+//!     let mut latest = last_sync;
+//!     while let Some(change) = changes.try_next().await? {
+//!         match &change {
+//!             Change::Subject(subject) => println!("subject {:?}", subject.id),
+//!             Change::Invoice(invoice) => println!("invoice {:?}", invoice.id),
+//!             Change::Expense(expense) => println!("expense {:?}", expense.id),
+//!         }
+//!         latest = latest.max(change.updated_at().cloned());
+//!     }
+//!     println!("next synchronization since {:?}", latest);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::Fakturoid;
+use crate::dates::DateTime;
+use crate::error::FakturoidError;
+use crate::filters::{ExpenseFilters, InvoiceFilters, SubjectFilters};
+use crate::models::{Expense, Invoice, Subject};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use std::pin::Pin;
+
+/// Record updated since given time.
+#[derive(Clone, Debug)]
+pub enum Change {
+    Subject(Box<Subject>),
+    Invoice(Box<Invoice>),
+    Expense(Box<Expense>),
+}
+
+/// Stream of changes returned by `Fakturoid::changes_since()`.
+pub type ChangeStream = Pin<Box<dyn Stream<Item = Result<Change, FakturoidError>>>>;
+
+impl Change {
+    /// Id of changed record.
+    pub fn id(&self) -> Option<i32> {
+        match self {
+            Change::Subject(subject) => subject.id,
+            Change::Invoice(invoice) => invoice.id,
+            Change::Expense(expense) => expense.id,
+        }
+    }
+
+    /// Time of last update of record.
+    pub fn updated_at(&self) -> Option<&DateTime> {
+        match self {
+            Change::Subject(subject) => subject.updated_at.as_ref(),
+            Change::Invoice(invoice) => invoice.updated_at.as_ref(),
+            Change::Expense(expense) => expense.updated_at.as_ref(),
+        }
+    }
+}
+
+impl Fakturoid {
+    /// Stream of subjects, invoices and expenses updated since given time, all records are
+    /// listed without time. See [`sync`](../sync/index.html) module. Stream ends after first
+    /// error.
+    pub fn changes_since(&self, updated_since: Option<DateTime>) -> ChangeStream {
+        let mut subjects = SubjectFilters::new();
+        let mut invoices = InvoiceFilters::new();
+        let mut expenses = ExpenseFilters::new();
+        if let Some(since) = updated_since {
+            subjects = subjects.updated_since(since);
+            invoices = invoices.updated_since(since);
+            expenses = expenses.updated_since(since);
+        }
+        let streams: Vec<ChangeStream> = vec![
+            Box::pin(
+                self.list_streamed::<Subject>(Some(subjects.into()))
+                    .map_ok(|s| Change::Subject(Box::new(s))),
+            ),
+            Box::pin(
+                self.list_streamed::<Invoice>(Some(invoices.into()))
+                    .map_ok(|i| Change::Invoice(Box::new(i))),
+            ),
+            Box::pin(
+                self.list_streamed::<Expense>(Some(expenses.into()))
+                    .map_ok(|e| Change::Expense(Box::new(e))),
+            ),
+        ];
+        Box::pin(stream::select_all(streams).scan(false, |failed, change| {
+            let item = if *failed {
+                None
+            } else {
+                *failed = change.is_err();
+                Some(change)
+            };
+            futures::future::ready(item)
+        }))
+    }
+}
@@ -0,0 +1,458 @@
+//! Helpers for tax related fields of invoices following common Czech rules.
+//!
+//! Includes checks of invoices for electronic services sold to consumers in other EU member
+//! states under One Stop Shop (OSS) scheme. Such invoices have `eu_electronic_service` set and
+//! VAT rates of customer's country.
+//!
+//! VAT rates of lines can be chosen as [`VatRate`](enum.VatRate.html) (standard, reduced or
+//! zero) and resolved to rate of invoice's country, see `Invoice::resolve_vat_rate()`.
+//! [`validate_vat_rates()`](fn.validate_vat_rates.html) checks that all lines have rate which
+//! exists in that country.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::models::{Invoice, InvoiceLine};
+//! use fakturoid::tax::{validate_eu_electronic_service, TaxIssue};
+//! use rust_decimal::Decimal;
+//!
+//! let mut invoice = Invoice::default();
+//! invoice.eu_electronic_service = Some(true);
+//! invoice.your_country = Some("CZ".to_string());
+//! invoice.client_country = Some("DE".to_string());
+//! invoice.add_line(InvoiceLine::new("E-book", Decimal::from(1), None, Decimal::from(10), 21));
+//!
+//! let issues = validate_eu_electronic_service(&invoice).unwrap_err();
+//! let vat_rate = Decimal::from(21);
+//! assert_eq!(issues, vec![TaxIssue::VatRateNotAllowed { line: 0, vat_rate }]);
+//!
+//! // standard rate of Finland is 25.5 %
+//! invoice.client_country = Some("FI".to_string());
+//! invoice.lines.as_mut().unwrap()[0].vat_rate = Decimal::new(255, 1);
+//! assert!(validate_eu_electronic_service(&invoice).is_ok());
+//! ```
+
+use crate::dates::{self, Date};
+use crate::models::{Invoice, InvoiceLanguage};
+use rust_decimal::Decimal;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Rule deriving date of taxable supply (`taxable_fulfillment_due`, DUZP) of invoice.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaxPointRule {
+    /// Date of taxable supply is the issue date, usual when goods are delivered together with
+    /// invoice.
+    IssueDate,
+    /// Goods or services were delivered on given date. Date of taxable supply is the earlier
+    /// of delivery date and date of payment, if payment was received in advance.
+    Delivery {
+        /// Date of delivery.
+        delivered_on: Date,
+        /// Date when payment was received, if it was.
+        paid_on: Option<Date>,
+    },
+    /// Recurring service billed after the period, e.g. monthly subscription invoiced at the
+    /// beginning of the next month. Date of taxable supply is the last day of month preceding
+    /// the issue date.
+    RecurringService,
+}
+
+impl TaxPointRule {
+    /// Date of taxable supply for invoice issued on given date.
+    pub fn taxable_fulfillment_due(&self, issued_on: &Date) -> Date {
+        match self {
+            TaxPointRule::IssueDate => *issued_on,
+            TaxPointRule::Delivery {
+                delivered_on,
+                paid_on,
+            } => match paid_on {
+                Some(paid_on) if paid_on < delivered_on => *paid_on,
+                _ => *delivered_on,
+            },
+            TaxPointRule::RecurringService => {
+                let (year, month, _) = dates::ymd(issued_on);
+                let first =
+                    dates::from_ymd(year, month, 1).expect("first day of month always exists");
+                dates::add_days(&first, -1)
+            }
+        }
+    }
+}
+
+/// VAT rates of EU member states in tenths of percent: country, standard rate, reduced rates.
+/// Rates valid in 2025, they change from time to time.
+const EU_VAT_RATES: &[(&str, u32, &[u32])] = &[
+    ("AT", 200, &[100, 130]),
+    ("BE", 210, &[60, 120]),
+    ("BG", 200, &[90]),
+    ("CY", 190, &[50, 90]),
+    ("CZ", 210, &[120]),
+    ("DE", 190, &[70]),
+    ("DK", 250, &[]),
+    ("EE", 240, &[90, 130]),
+    ("EL", 240, &[60, 130]),
+    ("ES", 210, &[40, 100]),
+    ("FI", 255, &[100, 140]),
+    ("FR", 200, &[21, 55, 100]),
+    ("HR", 250, &[50, 130]),
+    ("HU", 270, &[50, 180]),
+    ("IE", 230, &[48, 90, 135]),
+    ("IT", 220, &[40, 50, 100]),
+    ("LT", 210, &[50, 90]),
+    ("LU", 170, &[30, 80, 140]),
+    ("LV", 210, &[50, 120]),
+    ("MT", 180, &[50, 70]),
+    ("NL", 210, &[90]),
+    ("PL", 230, &[50, 80]),
+    ("PT", 230, &[60, 130]),
+    ("RO", 210, &[110]),
+    ("SE", 250, &[60, 120]),
+    ("SI", 220, &[50, 95]),
+    ("SK", 230, &[50, 190]),
+];
+
+/// VAT rates of EU member state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VatRates {
+    /// Standard rate in percent.
+    pub standard: Decimal,
+    /// Reduced rates in percent.
+    pub reduced: Vec<Decimal>,
+}
+
+impl VatRates {
+    /// Returns `true` if rate is standard or one of reduced rates.
+    pub fn contains(&self, rate: Decimal) -> bool {
+        self.standard == rate || self.reduced.contains(&rate)
+    }
+}
+
+/// Kind of VAT rate, actual rate depends on country, see [`percent()`](#method.percent).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VatRate {
+    /// Standard rate of country.
+    Standard,
+    /// Reduced rate of country. Countries with more reduced rates have them numbered from the
+    /// highest one, `Reduced(0)` is the highest reduced rate.
+    Reduced(usize),
+    /// Zero rate, used for exempt supplies, reverse charge and by non VAT payers.
+    Zero,
+}
+
+impl VatRate {
+    /// Rate in percent in EU member state with given ISO code, `None` if country is not EU
+    /// member state or it doesn't have such reduced rate.
+    pub fn percent(self, country: &str) -> Option<Decimal> {
+        let rates = eu_vat_rates(country)?;
+        match self {
+            VatRate::Standard => Some(rates.standard),
+            VatRate::Reduced(index) => rates.reduced.iter().rev().nth(index).copied(),
+            VatRate::Zero => Some(Decimal::new(0, 0)),
+        }
+    }
+
+    /// Kind of given rate in EU member state with given ISO code, `None` if rate is not valid
+    /// in that country.
+    pub fn from_percent(country: &str, rate: Decimal) -> Option<Self> {
+        let rates = eu_vat_rates(country)?;
+        if rate.is_zero() {
+            Some(VatRate::Zero)
+        } else if rates.standard == rate {
+            Some(VatRate::Standard)
+        } else {
+            rates
+                .reduced
+                .iter()
+                .rev()
+                .position(|reduced| *reduced == rate)
+                .map(VatRate::Reduced)
+        }
+    }
+}
+
+impl fmt::Display for VatRate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VatRate::Standard => f.write_str("standard rate"),
+            VatRate::Reduced(0) => f.write_str("reduced rate"),
+            VatRate::Reduced(index) => f.write_fmt(format_args!("reduced rate {}", index + 1)),
+            VatRate::Zero => f.write_str("zero rate"),
+        }
+    }
+}
+
+/// Country which VAT rates apply to invoice: client's country for electronic services sold
+/// under OSS scheme, otherwise country of supplier. If country of supplier is not set, it is
+/// derived from language of invoice (e.g. `Cz` is Czech Republic), English and Russian
+/// invoices give no country.
+pub fn vat_country(invoice: &Invoice) -> Option<String> {
+    let country = if invoice.eu_electronic_service.unwrap_or(false) {
+        invoice.client_country.as_deref()
+    } else {
+        invoice.your_country.as_deref()
+    };
+    match country.map(str::trim) {
+        Some(country) if !country.is_empty() => Some(eu_country(country)),
+        _ => invoice
+            .language
+            .and_then(country_of_language)
+            .map(|c| c.to_string()),
+    }
+}
+
+fn country_of_language(language: InvoiceLanguage) -> Option<&'static str> {
+    match language {
+        InvoiceLanguage::Cz => Some("CZ"),
+        InvoiceLanguage::Sk => Some("SK"),
+        InvoiceLanguage::De => Some("DE"),
+        InvoiceLanguage::Fr => Some("FR"),
+        InvoiceLanguage::It => Some("IT"),
+        InvoiceLanguage::Es => Some("ES"),
+        InvoiceLanguage::Hu => Some("HU"),
+        InvoiceLanguage::Pl => Some("PL"),
+        InvoiceLanguage::Ro => Some("RO"),
+        InvoiceLanguage::En | InvoiceLanguage::Ru => None,
+    }
+}
+
+/// Normalizes country code, Greece has code `GR` in ISO 3166 but `EL` in EU VAT system.
+fn eu_country(country: &str) -> String {
+    match country.trim().to_uppercase().as_str() {
+        "GR" => "EL".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Returns `true` if country with given ISO code is EU member state.
+pub fn is_eu_member(country: &str) -> bool {
+    let country = eu_country(country);
+    EU_VAT_RATES.iter().any(|(code, _, _)| *code == country)
+}
+
+/// VAT rates of EU member state with given ISO code, `None` for other countries. Rates valid
+/// in 2025 are returned, they should be checked against current legislation.
+pub fn eu_vat_rates(country: &str) -> Option<VatRates> {
+    let country = eu_country(country);
+    let tenths = |rate: &u32| Decimal::new(i64::from(*rate), 1).normalize();
+    EU_VAT_RATES
+        .iter()
+        .find(|(code, _, _)| *code == country)
+        .map(|(_, standard, reduced)| VatRates {
+            standard: tenths(standard),
+            reduced: reduced.iter().map(tenths).collect(),
+        })
+}
+
+/// Problem with tax related fields of invoice found before it is sent to API.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaxIssue {
+    /// Client country is not set.
+    MissingClientCountry,
+    /// Client country is not EU member state or it is the same as country of supplier.
+    NotCrossBorderEu,
+    /// Client has VAT number, supplies to VAT payers are not part of OSS scheme.
+    ClientIsVatPayer,
+    /// VAT rate of line at given index is not valid in client's country.
+    VatRateNotAllowed {
+        /// Index of line.
+        line: usize,
+        /// VAT rate of line.
+        vat_rate: Decimal,
+    },
+    /// Line at given index has non zero VAT rate on reverse charge invoice.
+    NonZeroVatRate {
+        /// Index of line.
+        line: usize,
+        /// VAT rate of line.
+        vat_rate: Decimal,
+    },
+    /// Reverse charge invoice has no supply code.
+    MissingSupplyCode,
+    /// Reverse charge invoice has no client VAT number.
+    MissingClientVatNo,
+    /// Client VAT number doesn't look like valid EU VAT number.
+    InvalidClientVatNo(String),
+}
+
+impl fmt::Display for TaxIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TaxIssue::MissingClientCountry => f.write_str("Client country is missing"),
+            TaxIssue::NotCrossBorderEu => {
+                f.write_str("Client is not from other EU member state than supplier")
+            }
+            TaxIssue::ClientIsVatPayer => f.write_str("Client is VAT payer"),
+            TaxIssue::VatRateNotAllowed { line, vat_rate } => f.write_fmt(format_args!(
+                "VAT rate {} % of line {} is not valid in client's country",
+                vat_rate,
+                line + 1
+            )),
+            TaxIssue::NonZeroVatRate { line, vat_rate } => f.write_fmt(format_args!(
+                "Line {} has VAT rate {} %, reverse charge requires zero rate",
+                line + 1,
+                vat_rate
+            )),
+            TaxIssue::MissingSupplyCode => f.write_str("Supply code is missing"),
+            TaxIssue::MissingClientVatNo => f.write_str("Client VAT number is missing"),
+            TaxIssue::InvalidClientVatNo(vat_no) => {
+                f.write_fmt(format_args!("Client VAT number {} is not valid", vat_no))
+            }
+        }
+    }
+}
+
+/// Checks invoice for electronic services sold to consumer in other EU member state (OSS).
+/// Client must be from other EU member state than supplier, must not have VAT number and all
+/// lines must have VAT rates of client's country. Invoices without `eu_electronic_service` are
+/// not checked.
+pub fn validate_eu_electronic_service(invoice: &Invoice) -> Result<(), Vec<TaxIssue>> {
+    if !invoice.eu_electronic_service.unwrap_or(false) {
+        return Ok(());
+    }
+    let country = match invoice.client_country.as_deref() {
+        Some(country) if !country.trim().is_empty() => country,
+        _ => return Err(vec![TaxIssue::MissingClientCountry]),
+    };
+    let rates = match eu_vat_rates(country) {
+        Some(rates) => rates,
+        None => return Err(vec![TaxIssue::NotCrossBorderEu]),
+    };
+
+    let mut issues = Vec::new();
+    let domestic = invoice
+        .your_country
+        .as_deref()
+        .map(|your| eu_country(your) == eu_country(country))
+        .unwrap_or(false);
+    if domestic {
+        issues.push(TaxIssue::NotCrossBorderEu);
+    }
+    if invoice
+        .client_vat_no
+        .as_deref()
+        .map(|vat_no| !vat_no.trim().is_empty())
+        .unwrap_or(false)
+    {
+        issues.push(TaxIssue::ClientIsVatPayer);
+    }
+    for (index, line) in invoice.lines.iter().flatten().enumerate() {
+        if !rates.contains(line.vat_rate) {
+            issues.push(TaxIssue::VatRateNotAllowed {
+                line: index,
+                vat_rate: line.vat_rate,
+            });
+        }
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+/// Checks that VAT rates of all lines are zero, standard or reduced rates of invoice's country
+/// (see [`vat_country()`](fn.vat_country.html)), so invoice isn't rejected by API because of
+/// nonsensical rate. Invoices of countries outside EU or without known country are not
+/// checked.
+pub fn validate_vat_rates(invoice: &Invoice) -> Result<(), Vec<TaxIssue>> {
+    let country = match vat_country(invoice) {
+        Some(country) if is_eu_member(&country) => country,
+        _ => return Ok(()),
+    };
+    let issues: Vec<TaxIssue> = invoice
+        .lines
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, line)| VatRate::from_percent(&country, line.vat_rate).is_none())
+        .map(|(index, line)| TaxIssue::VatRateNotAllowed {
+            line: index,
+            vat_rate: line.vat_rate,
+        })
+        .collect();
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+/// Checks format of EU VAT number: country prefix followed by digits or letters of length used
+/// by that country. Only format is checked, use VIES to verify that number exists.
+pub fn is_valid_vat_no(vat_no: &str) -> bool {
+    let vat_no: String = vat_no
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '.')
+        .collect::<String>()
+        .to_uppercase();
+    if vat_no.len() < 4 || !vat_no.is_char_boundary(2) {
+        return false;
+    }
+    let (country, number) = vat_no.split_at(2);
+    let digits = |min: usize, max: usize| {
+        number.len() >= min && number.len() <= max && number.chars().all(|c| c.is_ascii_digit())
+    };
+    match country {
+        "CZ" => digits(8, 10),
+        "SK" | "PL" => digits(10, 10),
+        "DE" | "EE" | "EL" | "PT" => digits(9, 9),
+        "AT" => {
+            number.starts_with('U')
+                && number[1..].chars().all(|c| c.is_ascii_digit())
+                && number.len() == 9
+        }
+        "BE" => digits(10, 10),
+        "DK" | "FI" | "HU" | "LU" | "MT" | "SI" => digits(8, 8),
+        "IT" | "LV" | "HR" => digits(11, 11),
+        "SE" => digits(12, 12),
+        code if is_eu_member(code) || code == "XI" => {
+            number.len() >= 2
+                && number.len() <= 12
+                && number
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '*')
+        }
+        _ => false,
+    }
+}
+
+/// Checks invoice with reverse charge (`transferred_tax_liability`) before it is sent to API.
+/// All lines must have zero VAT rate, supply code must be set and client must have valid VAT
+/// number. Invoices without reverse charge are not checked.
+pub fn validate_reverse_charge(invoice: &Invoice) -> Result<(), Vec<TaxIssue>> {
+    if !invoice.transferred_tax_liability.unwrap_or(false) {
+        return Ok(());
+    }
+    let mut issues = Vec::new();
+    for (index, line) in invoice.lines.iter().flatten().enumerate() {
+        if !line.vat_rate.is_zero() {
+            issues.push(TaxIssue::NonZeroVatRate {
+                line: index,
+                vat_rate: line.vat_rate,
+            });
+        }
+    }
+    if invoice
+        .supply_code
+        .as_deref()
+        .map(|code| code.trim().is_empty())
+        .unwrap_or(true)
+    {
+        issues.push(TaxIssue::MissingSupplyCode);
+    }
+    match invoice.client_vat_no.as_deref().map(str::trim) {
+        None | Some("") => issues.push(TaxIssue::MissingClientVatNo),
+        Some(vat_no) if !is_valid_vat_no(vat_no) => {
+            issues.push(TaxIssue::InvalidClientVatNo(vat_no.to_string()))
+        }
+        Some(_) => {}
+    }
+
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
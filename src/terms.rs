@@ -0,0 +1,103 @@
+//! Payment terms of invoices.
+//!
+//! API expects payment terms as number of days between issue date and due date (`due` field of
+//! invoice). [`PaymentTerms`](enum.PaymentTerms.html) describes common presets and converts them
+//! into number of days for given issue date.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::models::Invoice;
+//! use fakturoid::terms::PaymentTerms;
+//!
+//! let mut invoice = Invoice::default();
+//! invoice.set_payment_terms(PaymentTerms::Days(14)).unwrap();
+//! assert_eq!(invoice.due, Some(14));
+//! assert_eq!(invoice.payment_terms(), Some(PaymentTerms::Days(14)));
+//! ```
+
+use crate::dates::{self, Date};
+use crate::error::InvalidPaymentTerms;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Maximum number of days accepted as payment terms. Longer terms are almost always mistake.
+pub const MAX_DUE_DAYS: u32 = 365;
+
+/// Payment terms preset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaymentTerms {
+    /// Due on issue date.
+    Immediate,
+    /// Due given number of days after issue date.
+    Days(u32),
+    /// Due given number of days after the last day of month of issue date, e.g.
+    /// `EndOfMonth(15)` for invoice issued on 10th March is due on 15th April.
+    EndOfMonth(u32),
+}
+
+impl PaymentTerms {
+    /// Checks that terms are within [`MAX_DUE_DAYS`](constant.MAX_DUE_DAYS.html).
+    pub fn validate(&self) -> Result<(), InvalidPaymentTerms> {
+        match self {
+            PaymentTerms::Immediate => Ok(()),
+            PaymentTerms::Days(days) | PaymentTerms::EndOfMonth(days) => {
+                if *days > MAX_DUE_DAYS {
+                    Err(InvalidPaymentTerms::new(i64::from(*days)))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Due date of invoice issued on given date.
+    pub fn due_on(&self, issued_on: &Date) -> Date {
+        match self {
+            PaymentTerms::Immediate => *issued_on,
+            PaymentTerms::Days(days) => dates::add_days(issued_on, i64::from(*days)),
+            PaymentTerms::EndOfMonth(days) => {
+                dates::add_days(&dates::end_of_month(issued_on), i64::from(*days))
+            }
+        }
+    }
+
+    /// Number of days between issue date and due date as API expects in `due` field.
+    pub fn due_days(&self, issued_on: &Date) -> Result<i32, InvalidPaymentTerms> {
+        self.validate()?;
+        let days = dates::days_between(issued_on, &self.due_on(issued_on));
+        if days > i64::from(MAX_DUE_DAYS) {
+            return Err(InvalidPaymentTerms::new(days));
+        }
+        Ok(days as i32)
+    }
+}
+
+/// Converts `due` field of invoice. Zero days are `Immediate`, negative or too long terms are
+/// rejected.
+impl TryFrom<i32> for PaymentTerms {
+    type Error = InvalidPaymentTerms;
+
+    fn try_from(days: i32) -> Result<Self, Self::Error> {
+        let terms = match days {
+            0 => PaymentTerms::Immediate,
+            days if days < 0 => return Err(InvalidPaymentTerms::new(i64::from(days))),
+            days => PaymentTerms::Days(days as u32),
+        };
+        terms.validate()?;
+        Ok(terms)
+    }
+}
+
+impl fmt::Display for PaymentTerms {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PaymentTerms::Immediate => f.write_str("immediate"),
+            PaymentTerms::Days(days) => f.write_fmt(format_args!("{} days", days)),
+            PaymentTerms::EndOfMonth(days) => {
+                f.write_fmt(format_args!("end of month + {} days", days))
+            }
+        }
+    }
+}
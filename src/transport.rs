@@ -0,0 +1,198 @@
+//! Transport sending HTTP requests of client. By default requests are sent by `reqwest`,
+//! custom transport can be set by `FakturoidBuilder::transport()`.
+//!
+//! [`MockTransport`](struct.MockTransport.html) answers requests with canned responses without
+//! network access, so code using client can be tested offline. Documents from
+//! [`fixtures`](../fixtures/index.html) can be used as responses. Access tokens of OAuth are
+//! requested through transport too, so mock of OAuth client has to answer `/oauth/token`.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::fixtures::INVOICE;
+//! use fakturoid::models::Invoice;
+//! use fakturoid::transport::MockTransport;
+//! use reqwest::Method;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let mock = MockTransport::new();
+//!     mock.json(Method::GET, "/invoices/27.json", INVOICE);
+//!     let client = Fakturoid::builder("user@company.com", "apicode", "slug")
+//!         .transport(mock.clone())
+//!         .build()?;
+//!
+//!     let invoice = client.detail::<Invoice>(27).await?;
+//!     assert_eq!(invoice.number.as_deref(), Some("2012-0021"));
+//!     assert_eq!(mock.requests().len(), 1);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::FakturoidError;
+use reqwest::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Method, Request, Response, ResponseBuilderExt, StatusCode, Url};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// Future returned by `Transport::execute()`.
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response, FakturoidError>> + Send + 'a>>;
+
+/// Sends request and receives response.
+pub trait Transport: Send + Sync {
+    fn execute(&self, request: Request) -> TransportFuture<'_>;
+}
+
+/// Canned response of [`MockTransport`](struct.MockTransport.html).
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    status: StatusCode,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl MockResponse {
+    /// Response with given status and body.
+    pub fn new(status: StatusCode, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Response `200 OK` with JSON body.
+    pub fn json(body: &str) -> Self {
+        Self::new(StatusCode::OK, body).header(CONTENT_TYPE.as_str(), "application/json")
+    }
+
+    /// Adds header, e.g. `Link` with URLs of other pages.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn to_response(&self, url: &Url) -> Response {
+        let mut response = http::Response::builder()
+            .status(self.status)
+            .url(url.clone())
+            .body(self.body.clone())
+            .expect("valid mock response");
+        for (name, value) in self.headers.iter() {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response.into()
+    }
+}
+
+/// Request received by [`MockTransport`](struct.MockTransport.html).
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: Method,
+    pub url: String,
+    /// Body of request, `None` if it has no body or body isn't valid UTF-8.
+    pub body: Option<String>,
+}
+
+struct Route {
+    method: Method,
+    path: String,
+    responses: VecDeque<MockResponse>,
+}
+
+#[derive(Default)]
+struct MockState {
+    routes: Vec<Route>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Transport answering requests with canned responses. Clones share routes and recorded
+/// requests, so one clone can be given to client and other one used for assertions.
+///
+/// Route matches request with the same method which URL path ends with path of route, routes
+/// are searched in order in which they were added. If route has several responses they are
+/// returned one by one, the last one is repeated. Requests without matching route get
+/// `404 Not Found`.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds route answered by given response.
+    pub fn on(&self, method: Method, path: &str, response: MockResponse) {
+        let mut state = self.lock();
+        let existing = state
+            .routes
+            .iter_mut()
+            .find(|r| r.method == method && r.path == path);
+        match existing {
+            Some(route) => route.responses.push_back(response),
+            None => state.routes.push(Route {
+                method,
+                path: path.to_string(),
+                responses: vec![response].into(),
+            }),
+        }
+    }
+
+    /// Adds route answered by `200 OK` with given JSON body.
+    pub fn json(&self, method: Method, path: &str, body: &str) {
+        self.on(method, path, MockResponse::json(body));
+    }
+
+    /// All requests received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn respond(&self, request: &Request) -> Response {
+        let mut state = self.lock();
+        state.requests.push(RecordedRequest {
+            method: request.method().clone(),
+            url: request.url().to_string(),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .and_then(|b| String::from_utf8(b.to_vec()).ok()),
+        });
+        let url = request.url();
+        let path = url.path();
+        let route = state
+            .routes
+            .iter_mut()
+            .find(|r| r.method == request.method() && path.ends_with(r.path.as_str()));
+        match route {
+            Some(route) if route.responses.len() > 1 => {
+                route.responses.pop_front().unwrap().to_response(url)
+            }
+            Some(route) => route.responses[0].to_response(url),
+            None => MockResponse::new(StatusCode::NOT_FOUND, "").to_response(url),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(&self, request: Request) -> TransportFuture<'_> {
+        let response = self.respond(&request);
+        Box::pin(async move { Ok(response) })
+    }
+}
@@ -0,0 +1,155 @@
+//! Lightweight read-only views of entities for large listings, available with `performance`
+//! feature.
+//!
+//! Views contain only frequently used fields and their strings borrow from downloaded body
+//! instead of being allocated (strings with escape sequences are the only exception). Body of
+//! page is kept in [`RawPage`](struct.RawPage.html) and views are parsed from it on demand.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use fakturoid::client::Fakturoid;
+//! use fakturoid::models::Invoice;
+//! use fakturoid::views::InvoiceView;
+//!
+//! # extern crate tokio;
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let client = Fakturoid::new("user@company.com", "apicode", "slug", None);
+//!     let mut page = Some(client.list_raw::<Invoice>(None).await?);
+//!     while let Some(current) = page {
+//!         for invoice in current.views::<InvoiceView>()? {
+//!             println!("{:?} {:?}", invoice.number, invoice.total);
+//!         }
+//!         page = current.next_page().await?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::client::Fakturoid;
+use crate::dates::{self, Date, DateTime};
+use crate::error::FakturoidError;
+use crate::models::InvoiceState;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Body of one page of list with links to other pages.
+pub struct RawPage {
+    body: Vec<u8>,
+    links: HashMap<String, String>,
+    client: Fakturoid,
+}
+
+impl RawPage {
+    pub(crate) fn new(body: Vec<u8>, links: HashMap<String, String>, client: Fakturoid) -> Self {
+        Self {
+            body,
+            links,
+            client,
+        }
+    }
+
+    /// Raw JSON body of page.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Parses items of page into views (or any other type) borrowing from body.
+    pub fn views<'a, V: Deserialize<'a>>(&'a self) -> Result<Vec<V>, FakturoidError> {
+        serde_json::from_slice(&self.body).map_err(FakturoidError::from_std_err)
+    }
+
+    /// True if next page exists.
+    pub fn has_next(&self) -> bool {
+        self.links.contains_key("next")
+    }
+
+    /// Downloads next page, `None` is returned on last page.
+    pub async fn next_page(&self) -> Result<Option<RawPage>, FakturoidError> {
+        match self.links.get("next") {
+            Some(url) => Ok(Some(self.client.get_raw(url, None).await?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// String which can be `null`, borrowed from input if possible.
+fn option_cow<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Cow<'de, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|w| w.0))
+}
+
+/// List of strings borrowed from input if possible.
+fn vec_cow<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Cow<'de, str>>, D::Error> {
+    #[derive(Deserialize)]
+    struct Wrapper<'a>(#[serde(borrow)] Cow<'a, str>);
+
+    Ok(Option::<Vec<Wrapper>>::deserialize(deserializer)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|w| w.0)
+        .collect())
+}
+
+/// Frequently used fields of invoice.
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvoiceView<'a> {
+    pub id: i32,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub custom_id: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub proforma: Option<bool>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub number: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub variable_symbol: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub client_name: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub subject_id: Option<i32>,
+    #[serde(default)]
+    pub status: Option<InvoiceState>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub order_number: Option<Cow<'a, str>>,
+    #[serde(default, with = "dates::option_date")]
+    pub issued_on: Option<Date>,
+    #[serde(default, with = "dates::option_date")]
+    pub due_on: Option<Date>,
+    #[serde(default, borrow, deserialize_with = "vec_cow")]
+    pub tags: Vec<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub currency: Option<Cow<'a, str>>,
+    #[serde(default)]
+    pub total: Option<Decimal>,
+    #[serde(default)]
+    pub remaining_amount: Option<Decimal>,
+    #[serde(default, with = "dates::option_datetime")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Frequently used fields of subject.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SubjectView<'a> {
+    pub id: i32,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub custom_id: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub name: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub email: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub registration_no: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub vat_no: Option<Cow<'a, str>>,
+    #[serde(default, borrow, deserialize_with = "option_cow")]
+    pub country: Option<Cow<'a, str>>,
+    #[serde(default, with = "dates::option_datetime")]
+    pub updated_at: Option<DateTime>,
+}
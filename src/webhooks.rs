@@ -0,0 +1,315 @@
+//! Parsing of webhook deliveries sent by fakturoid.cz and their dispatching to callbacks
+//! registered per event type. Webhooks are registered by `Fakturoid::create()` with
+//! [`Webhook`](../models/struct.Webhook.html) model.
+//!
+//! Deliveries carry value of `auth_header` of webhook in `Authorization` header, so
+//! [`parse_verified()`](fn.parse_verified.html) rejects requests which don't come from
+//! fakturoid.cz. Invoice or expense the event is about is deserialized into the same models as
+//! API responses.
+//!
+//! # Example
+//!
+//! ```
+//! use fakturoid::webhooks::{parse_verified, WebhookEvent};
+//!
+//! // in request handler of warp, axum or other framework
+//! let authorization = Some("Bearer s3cr3t");
+//! let body = br#"{"webhook_id": 3, "event_name": "invoice_paid",
+//!     "body": {"invoice": {"id": 27, "number": "2012-0021", "total": "12039.5"}}}"#;
+//! match parse_verified(body, authorization, "Bearer s3cr3t").unwrap() {
+//!     WebhookEvent::Invoice { invoice, .. } => println!("{} paid", invoice),
+//!     WebhookEvent::Expense { expense, .. } => println!("{} changed", expense),
+//!     WebhookEvent::Other(payload) => println!("unhandled event {}", payload.event_name),
+//! }
+//! ```
+//!
+//! Callbacks can be registered per event type:
+//!
+//! ```
+//! use fakturoid::webhooks::WebhookDispatcher;
+//!
+//! let mut dispatcher = WebhookDispatcher::new();
+//! dispatcher
+//!     .on_invoice_paid(|payload| println!("invoice {:?} paid", payload.invoice_id))
+//!     .on_unknown(|payload| println!("unhandled event {}", payload.event_name));
+//!
+//! let body = br#"{"event_name": "invoice_paid", "invoice_id": 27, "number": "2012-0021"}"#;
+//! assert!(dispatcher.dispatch_json(body).unwrap());
+//! ```
+
+use crate::dates::{self, DateTime};
+use crate::error::{FakturoidError, InvalidWebhookAuthorization};
+use crate::models::{Expense, Invoice};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Type of webhook event.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WebhookEventType {
+    InvoiceCreated,
+    InvoiceUpdated,
+    InvoiceRemoved,
+    InvoiceSent,
+    InvoicePaid,
+    InvoicePaymentRemoved,
+    InvoiceOverdue,
+    InvoiceCancelled,
+    ExpenseCreated,
+    ExpenseUpdated,
+    ExpenseRemoved,
+    ExpensePaid,
+    ExpensePaymentRemoved,
+    /// Event this library doesn't know yet, with its name.
+    Unknown(String),
+}
+
+impl WebhookEventType {
+    /// Parses event name sent in `event_name` field.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "invoice_created" => WebhookEventType::InvoiceCreated,
+            "invoice_updated" => WebhookEventType::InvoiceUpdated,
+            "invoice_removed" => WebhookEventType::InvoiceRemoved,
+            "invoice_sent" => WebhookEventType::InvoiceSent,
+            "invoice_paid" => WebhookEventType::InvoicePaid,
+            "invoice_payment_removed" => WebhookEventType::InvoicePaymentRemoved,
+            "invoice_overdue" => WebhookEventType::InvoiceOverdue,
+            "invoice_cancelled" => WebhookEventType::InvoiceCancelled,
+            "expense_created" => WebhookEventType::ExpenseCreated,
+            "expense_updated" => WebhookEventType::ExpenseUpdated,
+            "expense_removed" => WebhookEventType::ExpenseRemoved,
+            "expense_paid" => WebhookEventType::ExpensePaid,
+            "expense_payment_removed" => WebhookEventType::ExpensePaymentRemoved,
+            other => WebhookEventType::Unknown(other.to_string()),
+        }
+    }
+}
+
+/// Body of webhook delivery. Fields which are not known are kept in `extra`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_id: Option<i32>,
+    pub event_name: String,
+    #[serde(
+        default,
+        with = "dates::option_datetime",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub created_at: Option<DateTime>,
+    /// Object the event is about, e.g. `{"invoice": {...}}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invoice_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expense_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl WebhookPayload {
+    /// Parses JSON body of webhook delivery.
+    pub fn parse(body: &[u8]) -> Result<Self, FakturoidError> {
+        serde_json::from_slice(body).map_err(FakturoidError::from_std_err)
+    }
+
+    /// Type of event.
+    pub fn event_type(&self) -> WebhookEventType {
+        WebhookEventType::from_name(&self.event_name)
+    }
+
+    /// Invoice the event is about, `None` if body doesn't contain invoice.
+    pub fn invoice(&self) -> Result<Option<Invoice>, FakturoidError> {
+        self.object("invoice")
+    }
+
+    /// Expense the event is about, `None` if body doesn't contain expense.
+    pub fn expense(&self) -> Result<Option<Expense>, FakturoidError> {
+        self.object("expense")
+    }
+
+    fn object<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, FakturoidError> {
+        match self.body.as_ref().and_then(|body| body.get(key)) {
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(FakturoidError::from_std_err),
+            None => Ok(None),
+        }
+    }
+
+    /// Converts payload into typed event according to object in body.
+    pub fn into_event(self) -> Result<WebhookEvent, FakturoidError> {
+        if let Some(invoice) = self.invoice()? {
+            return Ok(WebhookEvent::Invoice {
+                event_type: self.event_type(),
+                invoice: Box::new(invoice),
+            });
+        }
+        if let Some(expense) = self.expense()? {
+            return Ok(WebhookEvent::Expense {
+                event_type: self.event_type(),
+                expense: Box::new(expense),
+            });
+        }
+        Ok(WebhookEvent::Other(self))
+    }
+}
+
+/// Webhook delivery with deserialized object.
+#[derive(Clone, Debug)]
+pub enum WebhookEvent {
+    Invoice {
+        event_type: WebhookEventType,
+        invoice: Box<Invoice>,
+    },
+    Expense {
+        event_type: WebhookEventType,
+        expense: Box<Expense>,
+    },
+    /// Event about other object or without object in body.
+    Other(WebhookPayload),
+}
+
+/// Checks that `Authorization` header of delivery matches `auth_header` of webhook. Comparison
+/// takes the same time regardless of where values differ. Empty `auth_header` never matches, so
+/// deliveries can't be verified against webhook without it.
+pub fn verify_authorization(authorization: Option<&str>, auth_header: &str) -> bool {
+    let received = authorization.unwrap_or_default().as_bytes();
+    let expected = auth_header.as_bytes();
+    !expected.is_empty()
+        && received.len() == expected.len()
+        && received
+            .iter()
+            .zip(expected.iter())
+            .fold(0, |diff, (r, e)| diff | (r ^ e))
+            == 0
+}
+
+/// Verifies `Authorization` header of delivery (see
+/// [`verify_authorization()`](fn.verify_authorization.html)) and parses its body into typed
+/// event. Fails with `InvalidWebhookAuthorization` error if header doesn't match.
+pub fn parse_verified(
+    body: &[u8],
+    authorization: Option<&str>,
+    auth_header: &str,
+) -> Result<WebhookEvent, FakturoidError> {
+    if !verify_authorization(authorization, auth_header) {
+        return Err(FakturoidError::from_std_err(InvalidWebhookAuthorization));
+    }
+    WebhookPayload::parse(body)?.into_event()
+}
+
+type Handler = Box<dyn Fn(&WebhookPayload) + Send + Sync>;
+
+/// Routes webhook deliveries to callbacks registered for their event type. Events without
+/// registered callback are passed to callback registered by
+/// [`on_unknown()`](#method.on_unknown).
+#[derive(Default)]
+pub struct WebhookDispatcher {
+    handlers: HashMap<WebhookEventType, Vec<Handler>>,
+    fallback: Option<Handler>,
+}
+
+impl WebhookDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers callback for given event type. More callbacks can be registered for one type,
+    /// they are called in order of registration.
+    pub fn on<F>(&mut self, event_type: WebhookEventType, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.handlers
+            .entry(event_type)
+            .or_default()
+            .push(Box::new(handler));
+        self
+    }
+
+    /// Registers callback for events without registered callback, including events unknown to
+    /// this library.
+    pub fn on_unknown<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    pub fn on_invoice_created<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.on(WebhookEventType::InvoiceCreated, handler)
+    }
+
+    pub fn on_invoice_updated<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.on(WebhookEventType::InvoiceUpdated, handler)
+    }
+
+    pub fn on_invoice_paid<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.on(WebhookEventType::InvoicePaid, handler)
+    }
+
+    pub fn on_invoice_overdue<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.on(WebhookEventType::InvoiceOverdue, handler)
+    }
+
+    pub fn on_expense_created<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.on(WebhookEventType::ExpenseCreated, handler)
+    }
+
+    pub fn on_expense_paid<F>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(&WebhookPayload) + Send + Sync + 'static,
+    {
+        self.on(WebhookEventType::ExpensePaid, handler)
+    }
+
+    /// Calls callbacks registered for event type of payload. Returns `false` if there was no
+    /// callback for the event, including fallback one.
+    pub fn dispatch(&self, payload: &WebhookPayload) -> bool {
+        match self.handlers.get(&payload.event_type()) {
+            Some(handlers) if !handlers.is_empty() => {
+                for handler in handlers {
+                    handler(payload);
+                }
+                true
+            }
+            _ => match self.fallback.as_ref() {
+                Some(fallback) => {
+                    fallback(payload);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Parses JSON body of webhook delivery and dispatches it, see
+    /// [`dispatch()`](#method.dispatch).
+    pub fn dispatch_json(&self, body: &[u8]) -> Result<bool, FakturoidError> {
+        Ok(self.dispatch(&WebhookPayload::parse(body)?))
+    }
+}